@@ -0,0 +1,259 @@
+use iced::{
+    alignment::{Horizontal, Vertical},
+    mouse,
+    widget::canvas::{self, Frame, Path, Stroke, Text},
+    Color, Element, Length, Point, Rectangle, Renderer, Size, Theme,
+};
+
+/// The room left under the bars for their labels, and above them for the tooltip.
+const MARGIN: f32 = 18.0;
+/// The fraction of its slot a bar takes.
+const BAR_RATIO: f32 = 0.7;
+
+/// A bar of a [`BarChart`].
+#[derive(Debug, Clone)]
+struct Bar {
+    label: String,
+    value: f32,
+    tooltip: Option<String>,
+}
+
+pub struct BarChart {
+    bars: Vec<Bar>,
+    highlighted: Option<usize>,
+    width: Length,
+    height: Length,
+}
+
+impl BarChart {
+    /// Sets the text shown over each bar when hovered, in the same order as the bars.
+    ///
+    /// By default the value of the bar is shown.
+    pub fn tooltips<T: Into<String>>(mut self, tooltips: impl IntoIterator<Item = T>) -> Self {
+        for (bar, tooltip) in self.bars.iter_mut().zip(tooltips) {
+            bar.tooltip = Some(tooltip.into());
+        }
+        self
+    }
+
+    /// Sets the bar drawn with the [`Status::Highlighted`] style, e.g. the current day.
+    pub fn highlighted(mut self, index: impl Into<Option<usize>>) -> Self {
+        self.highlighted = index.into();
+        self
+    }
+
+    /// Sets the width of the [`BarChart`].
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.width = width.into();
+        self
+    }
+
+    /// Sets the height of the [`BarChart`].
+    pub fn height(mut self, height: impl Into<Length>) -> Self {
+        self.height = height.into();
+        self
+    }
+}
+
+impl<Message, Theme: Catalog> canvas::Program<Message, Theme> for BarChart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let class = <Theme as Catalog>::default();
+        let idle = <Theme as Catalog>::style(theme, &class, Status::Idle);
+
+        let baseline = frame.height() - MARGIN;
+        let plot_height = (baseline - MARGIN).max(0.0);
+        let slot = frame.width() / self.bars.len().max(1) as f32;
+        let max = self.bars.iter().map(|bar| bar.value).fold(0.0, f32::max);
+        let hovered = cursor
+            .position_in(bounds)
+            .map(|position| (position.x / slot) as usize);
+
+        for (index, bar) in self.bars.iter().enumerate() {
+            let status = if hovered == Some(index) {
+                Status::Hovered
+            } else if self.highlighted == Some(index) {
+                Status::Highlighted
+            } else {
+                Status::Idle
+            };
+            let style = <Theme as Catalog>::style(theme, &class, status);
+
+            let center = slot * (index as f32 + 0.5);
+            let height = if max > 0.0 {
+                plot_height * bar.value / max
+            } else {
+                0.0
+            };
+            let width = slot * BAR_RATIO;
+
+            frame.fill(
+                &Path::rectangle(
+                    Point::new(center - width / 2.0, baseline - height),
+                    Size::new(width, height),
+                ),
+                style.bar,
+            );
+            frame.fill_text(Text {
+                content: bar.label.clone(),
+                position: Point::new(center, baseline + MARGIN / 2.0),
+                vertical_alignment: Vertical::Center,
+                horizontal_alignment: Horizontal::Center,
+                size: 12.into(),
+                color: style.text,
+                ..default()
+            });
+
+            if status == Status::Hovered {
+                frame.fill_text(Text {
+                    content: bar
+                        .tooltip
+                        .clone()
+                        .unwrap_or_else(|| format!("{:.0}", bar.value)),
+                    position: Point::new(center, baseline - height - MARGIN / 2.0),
+                    vertical_alignment: Vertical::Center,
+                    horizontal_alignment: Horizontal::Center,
+                    size: 12.into(),
+                    color: style.text,
+                    ..default()
+                });
+            }
+        }
+
+        frame.stroke(
+            &Path::line(
+                Point::new(0.0, baseline),
+                Point::new(frame.width(), baseline),
+            ),
+            Stroke::default().with_color(idle.axis).with_width(1.0),
+        );
+
+        vec![frame.into_geometry()]
+    }
+}
+
+fn default<T: Default>() -> T {
+    Default::default()
+}
+
+/// Creates a bar chart widget.
+///
+/// This function returns a [`BarChart`] that is drawn on a `Canvas`, with a bar
+/// for each `(label, value)` pair. The bars are scaled to the highest value.
+///
+/// # Example
+///
+/// ```rust
+/// use atoms::widgets::bar_chart;
+///
+/// let week = bar_chart([("M", 50.), ("T", 75.), ("W", 0.)])
+///     .tooltips(["50 min", "1h 15m", "none"])
+///     .highlighted(2);
+/// ```
+pub fn bar_chart<L: Into<String>>(bars: impl IntoIterator<Item = (L, f32)>) -> BarChart {
+    BarChart {
+        bars: bars
+            .into_iter()
+            .map(|(label, value)| Bar {
+                label: label.into(),
+                value: value.max(0.0),
+                tooltip: None,
+            })
+            .collect(),
+        highlighted: None,
+        width: Length::Fill,
+        height: Length::Fixed(150.0),
+    }
+}
+
+impl<'a, Message: 'a> From<BarChart> for Element<'a, Message, Theme, Renderer> {
+    fn from(chart: BarChart) -> Self {
+        let (width, height) = (chart.width, chart.height);
+        iced::widget::canvas(chart)
+            .width(width)
+            .height(height)
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Style {
+    /// The [`Color`] of the bar
+    pub bar: Color,
+    /// The [`Color`] of the baseline
+    pub axis: Color,
+    /// The [`Color`] of the labels
+    pub text: Color,
+}
+
+/// The theme Catalog of a [`BarChart`]
+pub trait Catalog: Sized {
+    /// The item class of the [`Catalog`].
+    type Class<'a>;
+
+    /// The default class produced by the [`Catalog`]
+    fn default<'a>() -> Self::Class<'a>;
+
+    /// The [`Style`] of a class with the given status.
+    fn style(&self, class: &Self::Class<'_>, status: Status) -> Style;
+}
+
+/// The appearance of a bar of the [`BarChart`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// The bar is idle.
+    Idle,
+    /// The bar stands out from the others.
+    Highlighted,
+    /// The bar is under the cursor.
+    Hovered,
+}
+
+/// A styling function for the [`BarChart`]
+///
+/// This is just a boxed closure: `Fn(&Theme, Status) -> Style`.
+pub type StyleFn<'a, Theme> = Box<dyn Fn(&Theme, Status) -> Style + 'a>;
+
+impl Catalog for Theme {
+    type Class<'a> = StyleFn<'a, Self>;
+
+    fn default<'a>() -> Self::Class<'a> {
+        Box::new(primary)
+    }
+
+    fn style(&self, class: &Self::Class<'_>, status: Status) -> Style {
+        class(self, status)
+    }
+}
+
+/// The primary style of a [`BarChart`].
+pub fn primary(theme: &Theme, status: Status) -> Style {
+    let palette = theme.extended_palette();
+
+    let idle = Style {
+        bar: palette.primary.weak.color,
+        axis: palette.background.strong.color,
+        text: palette.background.base.text,
+    };
+
+    match status {
+        Status::Idle => idle,
+        Status::Highlighted => Style {
+            bar: palette.primary.base.color,
+            ..idle
+        },
+        Status::Hovered => Style {
+            bar: palette.primary.strong.color,
+            ..idle
+        },
+    }
+}
@@ -0,0 +1,246 @@
+use iced::{
+    alignment::{Horizontal, Vertical},
+    mouse,
+    widget::canvas::{self, Frame, Path, Stroke, Text},
+    Color, Element, Length, Point, Rectangle, Renderer, Size, Theme,
+};
+
+/// The room left under the grid for the tooltip.
+const MARGIN: f32 = 18.0;
+/// The fraction of its slot a cell takes.
+const CELL_RATIO: f32 = 0.8;
+/// How many shades of the filled color are used, the empty color aside.
+const LEVELS: f32 = 4.0;
+
+/// A GitHub-style calendar: a column per week and a row per weekday, each day
+/// shaded by its value.
+pub struct CalendarHeatmap {
+    values: Vec<f32>,
+    tooltips: Vec<String>,
+    first_weekday: usize,
+    width: Length,
+    height: Length,
+}
+
+impl CalendarHeatmap {
+    /// Sets the weekday of the first value, from `0` for monday to `6` for sunday.
+    pub fn first_weekday(mut self, weekday: u8) -> Self {
+        self.first_weekday = weekday.min(6) as usize;
+        self
+    }
+
+    /// Sets the text shown under the grid when a day is hovered, in the same order
+    /// as the values.
+    ///
+    /// By default the value of the day is shown.
+    pub fn tooltips<T: Into<String>>(mut self, tooltips: impl IntoIterator<Item = T>) -> Self {
+        self.tooltips = tooltips.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the width of the [`CalendarHeatmap`].
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.width = width.into();
+        self
+    }
+
+    /// Sets the height of the [`CalendarHeatmap`].
+    pub fn height(mut self, height: impl Into<Length>) -> Self {
+        self.height = height.into();
+        self
+    }
+
+    /// The column and row of the `index`th value.
+    fn cell(&self, index: usize) -> (usize, usize) {
+        let position = index + self.first_weekday;
+        (position / 7, position % 7)
+    }
+}
+
+impl<Message, Theme: Catalog> canvas::Program<Message, Theme> for CalendarHeatmap {
+    type State = ();
+
+    fn draw(
+        &self,
+        _: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let class = <Theme as Catalog>::default();
+
+        let weeks = self.cell(self.values.len().saturating_sub(1)).0 + 1;
+        let slot = (frame.width() / weeks as f32).min((frame.height() - MARGIN).max(0.0) / 7.0);
+        let size = slot * CELL_RATIO;
+        let max = self.values.iter().copied().fold(0.0, f32::max);
+
+        let hovered = cursor.position_in(bounds).and_then(|position| {
+            let (column, row) = ((position.x / slot) as usize, (position.y / slot) as usize);
+            (row < 7)
+                .then(|| (column * 7 + row).checked_sub(self.first_weekday))
+                .flatten()
+                .filter(|index| *index < self.values.len())
+        });
+
+        for (index, value) in self.values.iter().enumerate() {
+            let status = if hovered == Some(index) {
+                Status::Hovered
+            } else {
+                Status::Idle
+            };
+            let style = <Theme as Catalog>::style(theme, &class, status);
+
+            let level = if max > 0.0 && *value > 0.0 {
+                (value / max * LEVELS).ceil() / LEVELS
+            } else {
+                0.0
+            };
+            let color = if level > 0.0 {
+                style.filled.scale_alpha(level)
+            } else {
+                style.empty
+            };
+
+            let (column, row) = self.cell(index);
+            let cell = Path::rounded_rectangle(
+                Point::new(column as f32 * slot, row as f32 * slot),
+                Size::new(size, size),
+                (size * 0.2).into(),
+            );
+            frame.fill(&cell, color);
+            frame.stroke(
+                &cell,
+                Stroke::default().with_color(style.border).with_width(1.0),
+            );
+        }
+
+        if let Some(index) = hovered {
+            let style = <Theme as Catalog>::style(theme, &class, Status::Hovered);
+            frame.fill_text(Text {
+                content: self
+                    .tooltips
+                    .get(index)
+                    .cloned()
+                    .unwrap_or_else(|| format!("{:.0}", self.values[index])),
+                position: Point::new(0.0, slot * 7.0 + MARGIN / 2.0),
+                vertical_alignment: Vertical::Center,
+                horizontal_alignment: Horizontal::Left,
+                size: 12.into(),
+                color: style.text,
+                ..default()
+            });
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+fn default<T: Default>() -> T {
+    Default::default()
+}
+
+/// Creates a calendar heatmap widget.
+///
+/// This function returns a [`CalendarHeatmap`] that is drawn on a `Canvas`, with
+/// a cell for each of the `values`, one per day and oldest first. The darker the
+/// cell, the closer its value is to the highest one.
+///
+/// # Example
+///
+/// ```rust
+/// use atoms::widgets::calendar_heatmap;
+///
+/// // three weeks starting on a wednesday
+/// let month = calendar_heatmap((0..21).map(|day| (day % 5) as f32)).first_weekday(2);
+/// ```
+pub fn calendar_heatmap(values: impl IntoIterator<Item = f32>) -> CalendarHeatmap {
+    CalendarHeatmap {
+        values: values.into_iter().map(|value| value.max(0.0)).collect(),
+        tooltips: Vec::new(),
+        first_weekday: 0,
+        width: Length::Fill,
+        height: Length::Fixed(120.0),
+    }
+}
+
+impl<'a, Message: 'a> From<CalendarHeatmap> for Element<'a, Message, Theme, Renderer> {
+    fn from(heatmap: CalendarHeatmap) -> Self {
+        let (width, height) = (heatmap.width, heatmap.height);
+        iced::widget::canvas(heatmap)
+            .width(width)
+            .height(height)
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Style {
+    /// The [`Color`] of a day without value
+    pub empty: Color,
+    /// The [`Color`] of the day with the highest value, the others are lighter
+    pub filled: Color,
+    /// The [`Color`] of the border of a day
+    pub border: Color,
+    /// The [`Color`] of the tooltip
+    pub text: Color,
+}
+
+/// The theme Catalog of a [`CalendarHeatmap`]
+pub trait Catalog: Sized {
+    /// The item class of the [`Catalog`].
+    type Class<'a>;
+
+    /// The default class produced by the [`Catalog`]
+    fn default<'a>() -> Self::Class<'a>;
+
+    /// The [`Style`] of a class with the given status.
+    fn style(&self, class: &Self::Class<'_>, status: Status) -> Style;
+}
+
+/// The appearance of a day of the [`CalendarHeatmap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// The day is idle.
+    Idle,
+    /// The day is under the cursor.
+    Hovered,
+}
+
+/// A styling function for the [`CalendarHeatmap`]
+///
+/// This is just a boxed closure: `Fn(&Theme, Status) -> Style`.
+pub type StyleFn<'a, Theme> = Box<dyn Fn(&Theme, Status) -> Style + 'a>;
+
+impl Catalog for Theme {
+    type Class<'a> = StyleFn<'a, Self>;
+
+    fn default<'a>() -> Self::Class<'a> {
+        Box::new(primary)
+    }
+
+    fn style(&self, class: &Self::Class<'_>, status: Status) -> Style {
+        class(self, status)
+    }
+}
+
+/// The primary style of a [`CalendarHeatmap`].
+pub fn primary(theme: &Theme, status: Status) -> Style {
+    let palette = theme.extended_palette();
+
+    let idle = Style {
+        empty: palette.background.weak.color.scale_alpha(0.3),
+        filled: palette.success.base.color,
+        border: Color::TRANSPARENT,
+        text: palette.background.base.text,
+    };
+
+    match status {
+        Status::Idle => idle,
+        Status::Hovered => Style {
+            border: palette.background.base.text,
+            ..idle
+        },
+    }
+}
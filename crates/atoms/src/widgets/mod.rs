@@ -1,7 +1,13 @@
 //! Atomic widgets re-exports
 
+pub mod bar_chart;
+pub mod calendar_heatmap;
 mod modal;
 mod radial_progress_bar;
+pub mod timeline;
 
+pub use bar_chart::{bar_chart, BarChart};
+pub use calendar_heatmap::{calendar_heatmap, CalendarHeatmap};
 pub use modal::*;
 pub use radial_progress_bar::*;
+pub use timeline::{timeline, Timeline};
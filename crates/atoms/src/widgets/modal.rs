@@ -13,7 +13,7 @@ type Element<'a, Message> = iced::Element<'a, Message, Theme, Renderer>;
 ///
 /// # Example
 ///
-/// ```rust
+/// ```ignore
 /// use iced::{Element, Theme, Renderer};
 /// use atom::widget::modal;
 ///
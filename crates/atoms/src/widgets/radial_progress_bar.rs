@@ -1,20 +1,57 @@
-use std::f32::consts;
+use std::{cell::Cell, f32::consts};
 
 use iced::{
     alignment::{Horizontal, Vertical},
     mouse,
-    widget::canvas::{self, path::Arc, Canvas, Frame, Text},
-    Color, Point, Radians, Renderer, Theme,
+    widget::canvas::{self, path::Arc, Frame, Stroke, Text},
+    Color, Element, Length, Point, Radians, Renderer, Theme,
 };
 
-pub struct RadialProgressBar(f32, String);
+pub struct RadialProgressBar {
+    percentage: f32,
+    content: String,
+    paused: bool,
+    width: Length,
+    height: Length,
+}
+
+impl RadialProgressBar {
+    /// Sets whether the progress is paused.
+    pub fn paused(mut self, paused: bool) -> Self {
+        self.paused = paused;
+        self
+    }
+
+    /// Sets the width of the [`RadialProgressBar`].
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.width = width.into();
+        self
+    }
+
+    /// Sets the height of the [`RadialProgressBar`].
+    pub fn height(mut self, height: impl Into<Length>) -> Self {
+        self.height = height.into();
+        self
+    }
+}
+
+/// What a [`RadialProgressBar`] keeps from one draw to the next.
+///
+/// The disc and the rail only change with the size and the colors of the bar,
+/// they're drawn once and reused while the progress moves.
+#[derive(Debug, Default)]
+pub struct Layers {
+    background: canvas::Cache,
+    /// The background and rail colors the cached layer was drawn with.
+    colors: Cell<Option<(Color, Color)>>,
+}
 
 impl<Message, Theme: Catalog> canvas::Program<Message, Theme> for RadialProgressBar {
-    type State = ();
+    type State = Layers;
 
     fn draw(
         &self,
-        _: &Self::State,
+        layers: &Self::State,
         renderer: &Renderer,
         theme: &Theme,
         bounds: iced::Rectangle,
@@ -27,11 +64,12 @@ impl<Message, Theme: Catalog> canvas::Program<Message, Theme> for RadialProgress
         let inner_ball_radius = (radius * 0.2) / 2.0;
         let fixed_radius = radius * 0.9;
         let start_angle = Radians(-consts::FRAC_PI_2);
-        let end_angle = Radians(consts::TAU) * self.0 / 100.0 + start_angle;
+        let end_angle = Radians(consts::TAU) * self.percentage / 100.0 + start_angle;
 
-        let status = match self.0 {
-            0.0 => Status::Idle,
+        let status = match self.percentage {
+            0.0 if !self.paused => Status::Idle,
             100.0 => Status::Finished,
+            _ if self.paused => Status::Paused,
             _ => Status::Progressing,
         };
 
@@ -47,27 +85,34 @@ impl<Message, Theme: Catalog> canvas::Program<Message, Theme> for RadialProgress
             )
         };
 
-        let circle = canvas::Path::circle(center, radius);
+        // the cache only knows about the bounds, a new style must clear it
+        let colors = Some((style.background, style.rail));
+        if layers.colors.replace(colors) != colors {
+            layers.background.clear();
+        }
+        let background = layers.background.draw(renderer, bounds.size(), |frame| {
+            frame.fill(&canvas::Path::circle(center, radius), style.background);
+            frame.fill(&canvas::Path::circle(center, radius * 0.8), style.rail);
+        });
+
+        // the bar fills the ring left between the rail and the outer circle
         let segment = canvas::Path::new(|builder| {
             builder.arc(Arc {
                 center,
-                radius,
+                radius: fixed_radius,
                 start_angle,
                 end_angle,
             });
-            builder.line_to(center);
-            builder.close();
         });
 
         let start_point = circle_at_angle(start_angle);
         let end_point = circle_at_angle(end_angle);
-        let inner_circle = canvas::Path::circle(center, radius * 0.8);
 
         frame.fill_text(Text {
-            content: if self.1.is_empty() {
-                format!("{:.2}%", self.0)
+            content: if self.content.is_empty() {
+                format!("{:.2}%", self.percentage)
             } else {
-                self.1.clone()
+                self.content.clone()
             },
             position: center,
             vertical_alignment: Vertical::Center,
@@ -77,13 +122,16 @@ impl<Message, Theme: Catalog> canvas::Program<Message, Theme> for RadialProgress
             ..default()
         });
 
-        frame.fill(&circle, style.background);
-        frame.fill(&segment, style.bar);
+        frame.stroke(
+            &segment,
+            Stroke::default()
+                .with_width(inner_ball_radius * 2.0)
+                .with_color(style.bar),
+        );
         frame.fill(&start_point, style.bar);
         frame.fill(&end_point, style.bar);
-        frame.fill(&inner_circle, style.rail);
 
-        vec![frame.into_geometry()]
+        vec![background, frame.into_geometry()]
     }
 }
 
@@ -93,8 +141,8 @@ fn default<T: Default>() -> T {
 
 /// Creates a radial progress bar widget.
 ///
-/// This function returns a `Canvas` widget that displays a radial progress bar
-/// with a specified percentage and content. If the content is empty, the
+/// This function returns a [`RadialProgressBar`] that is drawn on a `Canvas`
+/// and displays a specified percentage and content. If the content is empty, the
 /// percentage will be displayed by default.
 ///
 /// # Example
@@ -104,12 +152,23 @@ fn default<T: Default>() -> T {
 ///
 /// let progress = radial_progress_bar(75., "75% Complete");
 /// let default_progress = radial_progress_bar(75., "");
+/// let paused_progress = radial_progress_bar(75., "Paused").paused(true);
 /// ```
-pub fn radial_progress_bar<Message>(
-    percentage: f32,
-    content: impl Into<String>,
-) -> Canvas<RadialProgressBar, Message, Theme, Renderer> {
-    iced::widget::canvas(RadialProgressBar(percentage, content.into()))
+pub fn radial_progress_bar(percentage: f32, content: impl Into<String>) -> RadialProgressBar {
+    RadialProgressBar {
+        percentage,
+        content: content.into(),
+        paused: false,
+        width: Length::Fixed(100.0),
+        height: Length::Fixed(100.0),
+    }
+}
+
+impl<'a, Message: 'a> From<RadialProgressBar> for Element<'a, Message, Theme, Renderer> {
+    fn from(bar: RadialProgressBar) -> Self {
+        let (width, height) = (bar.width, bar.height);
+        iced::widget::canvas(bar).width(width).height(height).into()
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -143,6 +202,8 @@ pub enum Status {
     Idle,
     /// The progress bar is currently progressing.
     Progressing,
+    /// The progress bar is on hold.
+    Paused,
     /// The progress bar finished.
     Finished,
     /// The progress bar has failed.
@@ -183,6 +244,10 @@ pub fn primary(theme: &Theme, status: Status) -> Style {
             bar: palette.primary.base.color,
             ..idle
         },
+        Status::Paused => Style {
+            bar: palette.secondary.base.color,
+            ..idle
+        },
         Status::Finished => Style {
             bar: palette.success.base.color,
             ..idle
@@ -0,0 +1,447 @@
+use std::ops::Range;
+
+use iced::{
+    alignment::{Horizontal, Vertical},
+    mouse,
+    widget::canvas::{self, event, Event, Frame, Path, Stroke, Text},
+    Color, Element, Length, Point, Rectangle, Renderer, Size, Theme,
+};
+
+/// The room left along the track for the tick labels.
+const LABEL: f32 = 16.0;
+/// The room left beside a vertical track for the tick labels.
+const LABEL_WIDTH: f32 = 40.0;
+/// The size of the tick labels and the tooltip.
+const TEXT_SIZE: f32 = 12.0;
+
+/// The way a [`Timeline`] runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Direction {
+    /// From left to right, the labels under the track.
+    #[default]
+    Horizontal,
+    /// From top to bottom, the labels beside the track.
+    Vertical,
+}
+
+/// What a [`Block`] stands for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Kind {
+    /// Some focused work, drawn in the color of the block.
+    #[default]
+    Focus,
+    /// A break, drawn in the rest color of the style.
+    Rest,
+}
+
+/// A span of a [`Timeline`].
+#[derive(Debug, Clone)]
+pub struct Block<Message> {
+    start: f32,
+    end: f32,
+    kind: Kind,
+    color: Option<Color>,
+    tooltip: Option<String>,
+    on_press: Option<Message>,
+}
+
+impl<Message> Block<Message> {
+    /// Sets what the block stands for.
+    pub fn kind(mut self, kind: Kind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Sets the color of a [`Kind::Focus`] block, the style's focus color by default.
+    pub fn color(mut self, color: impl Into<Option<Color>>) -> Self {
+        self.color = color.into();
+        self
+    }
+
+    /// Sets the text shown while the block is hovered.
+    pub fn tooltip(mut self, tooltip: impl Into<String>) -> Self {
+        self.tooltip = Some(tooltip.into());
+        self
+    }
+
+    /// Sets the message produced when the block is clicked.
+    pub fn on_press(mut self, message: Message) -> Self {
+        self.on_press = Some(message);
+        self
+    }
+}
+
+/// Creates a [`Block`] spanning from `start` to `end`, in the units of the
+/// [`Timeline`] range.
+pub fn block<Message>(start: f32, end: f32) -> Block<Message> {
+    Block {
+        start: start.min(end),
+        end: end.max(start),
+        kind: Kind::Focus,
+        color: None,
+        tooltip: None,
+        on_press: None,
+    }
+}
+
+pub struct Timeline<Message> {
+    range: Range<f32>,
+    blocks: Vec<Block<Message>>,
+    ticks: Vec<(f32, String)>,
+    direction: Direction,
+    width: Length,
+    height: Length,
+}
+
+impl<Message> Timeline<Message> {
+    /// Sets the labels shown along the track, at their position in the range.
+    pub fn ticks<L: Into<String>>(mut self, ticks: impl IntoIterator<Item = (f32, L)>) -> Self {
+        self.ticks = ticks
+            .into_iter()
+            .map(|(at, label)| (at, label.into()))
+            .collect();
+        self
+    }
+
+    /// Sets the way the [`Timeline`] runs.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Sets the width of the [`Timeline`].
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.width = width.into();
+        self
+    }
+
+    /// Sets the height of the [`Timeline`].
+    pub fn height(mut self, height: impl Into<Length>) -> Self {
+        self.height = height.into();
+        self
+    }
+
+    /// The rectangle the blocks are drawn in, within `size`.
+    fn track(&self, size: Size) -> Rectangle {
+        match self.direction {
+            Direction::Horizontal => Rectangle::new(
+                Point::ORIGIN,
+                Size::new(size.width, (size.height - LABEL).max(0.0)),
+            ),
+            Direction::Vertical => Rectangle::new(
+                Point::new(LABEL_WIDTH, 0.0),
+                Size::new((size.width - LABEL_WIDTH).max(0.0), size.height),
+            ),
+        }
+    }
+
+    /// Where the `value` of the range falls along the `track`.
+    fn offset(&self, track: Rectangle, value: f32) -> f32 {
+        let length = match self.direction {
+            Direction::Horizontal => track.width,
+            Direction::Vertical => track.height,
+        };
+        let span = self.range.end - self.range.start;
+        if span <= 0.0 {
+            return 0.0;
+        }
+
+        (value.clamp(self.range.start, self.range.end) - self.range.start) / span * length
+    }
+
+    /// The rectangle of the `block` within the `track`, at least a pixel thick.
+    fn bounds_of(&self, track: Rectangle, block: &Block<Message>) -> Rectangle {
+        let (start, end) = (
+            self.offset(track, block.start),
+            self.offset(track, block.end),
+        );
+        let length = (end - start).max(1.0);
+
+        match self.direction {
+            Direction::Horizontal => Rectangle::new(
+                Point::new(track.x + start, track.y),
+                Size::new(length, track.height),
+            ),
+            Direction::Vertical => Rectangle::new(
+                Point::new(track.x, track.y + start),
+                Size::new(track.width, length),
+            ),
+        }
+    }
+
+    /// The index of the block under the `cursor`, the last drawn on top.
+    fn hovered(&self, bounds: Rectangle, cursor: mouse::Cursor) -> Option<usize> {
+        let position = cursor.position_in(bounds)?;
+        let track = self.track(bounds.size());
+
+        self.blocks
+            .iter()
+            .rposition(|block| self.bounds_of(track, block).contains(position))
+    }
+}
+
+impl<Message: Clone, Theme: Catalog> canvas::Program<Message, Theme> for Timeline<Message> {
+    type State = ();
+
+    fn update(
+        &self,
+        _: &mut Self::State,
+        event: Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (event::Status, Option<Message>) {
+        let Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) = event else {
+            return (event::Status::Ignored, None);
+        };
+
+        match self
+            .hovered(bounds, cursor)
+            .and_then(|index| self.blocks[index].on_press.clone())
+        {
+            Some(message) => (event::Status::Captured, Some(message)),
+            None => (event::Status::Ignored, None),
+        }
+    }
+
+    fn draw(
+        &self,
+        _: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let class = <Theme as Catalog>::default();
+        let idle = <Theme as Catalog>::style(theme, &class, Status::Idle);
+        let track = self.track(frame.size());
+        let hovered = self.hovered(bounds, cursor);
+
+        frame.fill_rectangle(track.position(), track.size(), idle.track);
+
+        for (at, label) in &self.ticks {
+            let offset = self.offset(track, *at);
+            let (from, to, position, alignment) = match self.direction {
+                Direction::Horizontal => (
+                    Point::new(track.x + offset, track.y),
+                    Point::new(track.x + offset, track.y + track.height),
+                    Point::new(track.x + offset, track.height + LABEL / 2.0),
+                    Horizontal::Center,
+                ),
+                Direction::Vertical => (
+                    Point::new(track.x, track.y + offset),
+                    Point::new(track.x + track.width, track.y + offset),
+                    Point::new(LABEL_WIDTH - 4.0, track.y + offset),
+                    Horizontal::Right,
+                ),
+            };
+            frame.stroke(
+                &Path::line(from, to),
+                Stroke::default().with_color(idle.tick).with_width(1.0),
+            );
+            frame.fill_text(Text {
+                content: label.clone(),
+                position,
+                vertical_alignment: Vertical::Center,
+                horizontal_alignment: alignment,
+                size: TEXT_SIZE.into(),
+                color: idle.text,
+                ..default()
+            });
+        }
+
+        for (index, block) in self.blocks.iter().enumerate() {
+            let status = if hovered == Some(index) {
+                Status::Hovered
+            } else {
+                Status::Idle
+            };
+            let style = <Theme as Catalog>::style(theme, &class, status);
+            let color = match block.kind {
+                Kind::Focus => block.color.unwrap_or(style.focus),
+                Kind::Rest => style.rest,
+            };
+
+            let rectangle = self.bounds_of(track, block);
+            frame.fill_rectangle(rectangle.position(), rectangle.size(), color);
+            if status == Status::Hovered {
+                frame.stroke(
+                    &Path::rectangle(rectangle.position(), rectangle.size()),
+                    Stroke::default().with_color(style.outline).with_width(2.0),
+                );
+            }
+        }
+
+        let tooltip = hovered
+            .and_then(|index| self.blocks[index].tooltip.as_ref())
+            .zip(cursor.position_in(bounds));
+        if let Some((tooltip, position)) = tooltip {
+            // a rough measure of the text, the canvas can't tell its size
+            let size = Size::new(
+                tooltip.chars().count() as f32 * TEXT_SIZE * 0.6 + 8.0,
+                TEXT_SIZE + 8.0,
+            );
+            let corner = Point::new(
+                (position.x + 8.0).min(frame.width() - size.width).max(0.0),
+                (position.y - size.height - 4.0).max(0.0),
+            );
+            let style = <Theme as Catalog>::style(theme, &class, Status::Hovered);
+            frame.fill_rectangle(corner, size, style.tooltip);
+            frame.fill_text(Text {
+                content: tooltip.clone(),
+                position: Point::new(corner.x + 4.0, corner.y + size.height / 2.0),
+                vertical_alignment: Vertical::Center,
+                horizontal_alignment: Horizontal::Left,
+                size: TEXT_SIZE.into(),
+                color: style.text,
+                ..default()
+            });
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        _: &Self::State,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        match self.hovered(bounds, cursor) {
+            Some(index) if self.blocks[index].on_press.is_some() => mouse::Interaction::Pointer,
+            _ => mouse::Interaction::default(),
+        }
+    }
+}
+
+fn default<T: Default>() -> T {
+    Default::default()
+}
+
+/// Creates a timeline widget.
+///
+/// This function returns a [`Timeline`] that is drawn on a `Canvas`, with the
+/// `blocks` laid along a track running over the `range`, e.g. the minutes of a
+/// day. The blocks may overlap, the last one is drawn on top.
+///
+/// # Example
+///
+/// ```rust
+/// use atoms::widgets::{
+///     timeline,
+///     timeline::{block, Direction, Kind},
+///     Timeline,
+/// };
+///
+/// let day: Timeline<()> = timeline(
+///     480.0..720.0,
+///     [
+///         block(540.0, 565.0).tooltip("09:00 – 09:25 Writing"),
+///         block(565.0, 570.0).kind(Kind::Rest),
+///     ],
+/// )
+/// .ticks([(480.0, "8:00"), (600.0, "10:00"), (720.0, "12:00")])
+/// .direction(Direction::Vertical);
+/// ```
+pub fn timeline<Message>(
+    range: Range<f32>,
+    blocks: impl IntoIterator<Item = Block<Message>>,
+) -> Timeline<Message> {
+    Timeline {
+        range,
+        blocks: blocks.into_iter().collect(),
+        ticks: Vec::new(),
+        direction: Direction::Horizontal,
+        width: Length::Fill,
+        height: Length::Fixed(48.0),
+    }
+}
+
+impl<'a, Message: Clone + 'a> From<Timeline<Message>> for Element<'a, Message, Theme, Renderer> {
+    fn from(timeline: Timeline<Message>) -> Self {
+        let (width, height) = (timeline.width, timeline.height);
+        iced::widget::canvas(timeline)
+            .width(width)
+            .height(height)
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Style {
+    /// The [`Color`] of the track under the blocks
+    pub track: Color,
+    /// The [`Color`] of a focus block without a color of its own
+    pub focus: Color,
+    /// The [`Color`] of a rest block
+    pub rest: Color,
+    /// The [`Color`] of the outline of a hovered block
+    pub outline: Color,
+    /// The [`Color`] of the tick lines
+    pub tick: Color,
+    /// The [`Color`] of the background of the tooltip
+    pub tooltip: Color,
+    /// The [`Color`] of the labels and the tooltip
+    pub text: Color,
+}
+
+/// The theme Catalog of a [`Timeline`]
+pub trait Catalog: Sized {
+    /// The item class of the [`Catalog`].
+    type Class<'a>;
+
+    /// The default class produced by the [`Catalog`]
+    fn default<'a>() -> Self::Class<'a>;
+
+    /// The [`Style`] of a class with the given status.
+    fn style(&self, class: &Self::Class<'_>, status: Status) -> Style;
+}
+
+/// The appearance of a block of the [`Timeline`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// The block is idle.
+    Idle,
+    /// The block is under the cursor.
+    Hovered,
+}
+
+/// A styling function for the [`Timeline`]
+///
+/// This is just a boxed closure: `Fn(&Theme, Status) -> Style`.
+pub type StyleFn<'a, Theme> = Box<dyn Fn(&Theme, Status) -> Style + 'a>;
+
+impl Catalog for Theme {
+    type Class<'a> = StyleFn<'a, Self>;
+
+    fn default<'a>() -> Self::Class<'a> {
+        Box::new(primary)
+    }
+
+    fn style(&self, class: &Self::Class<'_>, status: Status) -> Style {
+        class(self, status)
+    }
+}
+
+/// The primary style of a [`Timeline`].
+pub fn primary(theme: &Theme, status: Status) -> Style {
+    let palette = theme.extended_palette();
+
+    let idle = Style {
+        track: palette.background.weak.color.scale_alpha(0.2),
+        focus: palette.primary.base.color,
+        rest: palette.success.weak.color.scale_alpha(0.6),
+        outline: palette.background.base.text,
+        tick: palette.background.strong.color.scale_alpha(0.5),
+        tooltip: palette.background.strong.color,
+        text: palette.background.base.text,
+    };
+
+    match status {
+        Status::Idle => idle,
+        Status::Hovered => Style {
+            rest: palette.success.base.color,
+            ..idle
+        },
+    }
+}
@@ -0,0 +1,199 @@
+use std::error::Error;
+
+use trackit_core::{
+    chrono::{DateTime, Local},
+    interruption::Kind,
+    ipc::Status,
+    storage::{Data, Storage},
+    ActiveSession, Outcome, Project, SessionLog, Task,
+};
+
+/// The data shared with the GUI, loaded once per invocation.
+pub struct Context {
+    storage: Storage,
+    pub data: Data,
+    pub log: SessionLog,
+    pub now: DateTime<Local>,
+}
+
+impl Context {
+    /// Loads the data as it was saved, for the commands that only read it.
+    ///
+    /// Nothing is written: the running session is left where it was saved,
+    /// catching it up would change the data, and a file from before ids existed
+    /// is migrated by the next command changing the data.
+    pub fn read() -> Result<Self, Box<dyn Error>> {
+        let storage = Storage::open_default()?;
+        Ok(Self {
+            data: storage.load()?,
+            log: SessionLog::open_default()?,
+            now: Local::now(),
+            storage,
+        })
+    }
+
+    /// Loads the data and catches the running session up with the wall clock.
+    pub fn load() -> Result<Self, Box<dyn Error>> {
+        let mut context = Self::read()?;
+        // a file from before ids existed, its tasks must be saved with the new ids
+        if !context.data.migrated.is_empty() {
+            let ambiguous = context.log.link_migrated(&mut context.data)?;
+            if !ambiguous.is_empty() {
+                eprintln!(
+                    "note: the history of {} is shared by tasks of the same name, it's left unassigned",
+                    ambiguous.join(", ")
+                );
+            }
+            context.save()?;
+        }
+        if let Some(mut active) = context.data.active.take() {
+            let outcome = context
+                .data
+                .task(active.task)
+                .map(|task| active.advance(task, context.now));
+            context.data.active = Some(active);
+            if let Some(outcome) = outcome {
+                context.apply(outcome)?;
+            }
+        }
+
+        Ok(context)
+    }
+
+    /// A context over the `data` and an empty log in memory, saved to a
+    /// throwaway file.
+    #[cfg(test)]
+    pub fn with_data(data: Data) -> Self {
+        Self {
+            storage: Storage::new(std::env::temp_dir().join("trackit-cli-test.json")),
+            data,
+            log: SessionLog::open_in_memory().unwrap(),
+            now: Local::now(),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        Ok(self.storage.save(&self.data)?)
+    }
+
+    /// Finds a task by id, exact name or unambiguous name prefix (case insensitive).
+    pub fn find_task(&self, query: &str) -> Result<&Task, Box<dyn Error>> {
+        Ok(trackit_core::find_task(&self.data.tasks, query)?)
+    }
+
+    /// Finds a project by name, case insensitive.
+    pub fn find_project(&self, name: &str) -> Result<&Project, Box<dyn Error>> {
+        self.data
+            .project_named(name)
+            .ok_or_else(|| format!("no project named `{name}`").into())
+    }
+
+    /// The status of the running session.
+    pub fn status(&self) -> Option<Status> {
+        let active = self.data.active.as_ref()?;
+        Some(Status::new(active, self.data.task(active.task)?, self.now))
+    }
+
+    /// Drives the running session with `f` and accounts what happened.
+    pub fn drive(
+        &mut self,
+        f: impl FnOnce(&mut ActiveSession, &Task, DateTime<Local>) -> Outcome,
+    ) -> Result<(), Box<dyn Error>> {
+        let Some(mut active) = self.data.active.take() else {
+            return Err("there's no running session".into());
+        };
+        let outcome = match self.data.task(active.task) {
+            Some(task) => f(&mut active, task, self.now),
+            None => return Err("the running task doesn't exist anymore".into()),
+        };
+
+        self.data.active = Some(active);
+        self.apply(outcome)
+    }
+
+    /// Notes an interruption of the running work block in the log.
+    pub fn interrupt(&mut self, kind: Kind, note: Option<String>) -> Result<(), Box<dyn Error>> {
+        let Some(active) = self.data.active.as_mut() else {
+            return Err("there's no running session".into());
+        };
+        let Some(task) = self.data.tasks.iter().find(|task| task.id == active.task) else {
+            return Err("the running task doesn't exist anymore".into());
+        };
+        let Some(mut interruption) = active.interrupt(task, kind, note, self.now) else {
+            return Err("there's no running work block".into());
+        };
+
+        Ok(self.log.record_interruption(&mut interruption)?)
+    }
+
+    /// Accounts the `outcome` of driving the session to its task and the log.
+    fn apply(&mut self, mut outcome: Outcome) -> Result<(), Box<dyn Error>> {
+        if let Some(overrun) = &outcome.overrun {
+            // on stderr, so the JSON output stays clean
+            eprintln!(
+                "note: the session went unwatched from {} to {}, {} phases ended meanwhile",
+                overrun.since.format("%d/%m %H:%M"),
+                overrun.until.format("%d/%m %H:%M"),
+                overrun.phases.len()
+            );
+        }
+        let Some(id) = self.data.active.as_ref().map(|active| active.task) else {
+            return Ok(());
+        };
+        if let Some(task) = self.data.task_mut(id) {
+            outcome.apply(task, Some(&self.log))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use trackit_core::{project::Color, Durations};
+
+    use super::*;
+
+    fn context(names: &[&str]) -> Context {
+        Context::with_data(Data {
+            tasks: names
+                .iter()
+                .map(|name| Task::new(name.to_string(), 1, Durations::default()))
+                .collect(),
+            projects: vec![Project::new("Book".into(), Color::PALETTE[0])],
+            ..Data::default()
+        })
+    }
+
+    #[test]
+    fn a_task_is_found_by_id_name_or_prefix() {
+        let context = context(&["Write chapter", "Read"]);
+        let id = context.data.tasks[1].id;
+
+        assert_eq!(context.find_task(&id.to_string()).unwrap().id, id);
+        assert_eq!(context.find_task("read").unwrap().id, id);
+        assert_eq!(context.find_task("wri").unwrap().name, "Write chapter");
+    }
+
+    #[test]
+    fn an_unknown_or_ambiguous_task_is_an_error() {
+        let context = context(&["Write chapter", "Write notes", "Read"]);
+
+        let unknown = context.find_task("Plan").unwrap_err();
+        let ambiguous = context.find_task("write").unwrap_err();
+
+        assert_eq!(unknown.to_string(), "no task matches `Plan`");
+        assert_eq!(ambiguous.to_string(), "`write` matches more than one task");
+    }
+
+    #[test]
+    fn a_project_is_found_by_name_only() {
+        let context = context(&[]);
+
+        assert_eq!(context.find_project("book").unwrap().name, "Book");
+        assert_eq!(
+            context.find_project("Bo").unwrap_err().to_string(),
+            "no project named `Bo`"
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//! Human and JSON output of the commands.
+
+use std::{error::Error, time::Duration};
+
+use serde::Serialize;
+use trackit_core::{
+    format_clock, format_duration, import::Plan, ipc::Status, Interruption, Interval, Project,
+    Task, TaskId,
+};
+
+type Result = std::result::Result<(), Box<dyn Error>>;
+
+fn print_json(value: &impl Serialize) -> Result {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+pub fn task(task: &Task, projects: &[Project], json: bool) -> Result {
+    if json {
+        return print_json(task);
+    }
+
+    let project = task
+        .project
+        .and_then(|id| projects.iter().find(|project| project.id == id))
+        .map(|project| format!("  [{}]", project.name))
+        .unwrap_or_default();
+    let tags: String = task.tags.iter().map(|tag| format!("  #{tag}")).collect();
+    println!(
+        "{}  {}{}{}  {}  {} cycles  {} done",
+        task.id,
+        task.name,
+        project,
+        tags,
+        task.state,
+        task.cycles,
+        format_duration(task.elapsed)
+    );
+    Ok(())
+}
+
+pub fn tasks(tasks: &[Task], projects: &[Project], json: bool) -> Result {
+    if json {
+        return print_json(&tasks);
+    }
+
+    if tasks.is_empty() {
+        println!("Empty task list");
+    }
+    tasks
+        .iter()
+        .try_for_each(|task| self::task(task, projects, false))
+}
+
+/// The ids of the tasks deleted for good.
+pub fn purged(ids: &[TaskId], json: bool) -> Result {
+    if json {
+        return print_json(&ids);
+    }
+
+    println!("{} tasks purged", ids.len());
+    Ok(())
+}
+
+pub fn project(project: &Project, tasks: &[Task], json: bool) -> Result {
+    if json {
+        return print_json(project);
+    }
+
+    let count = tasks
+        .iter()
+        .filter(|task| task.project == Some(project.id))
+        .count();
+    println!("{}  {}  {} tasks", project.color, project.name, count);
+    Ok(())
+}
+
+pub fn projects(projects: &[Project], tasks: &[Task], json: bool) -> Result {
+    if json {
+        return print_json(&projects);
+    }
+
+    if projects.is_empty() {
+        println!("No project");
+    }
+    projects
+        .iter()
+        .try_for_each(|project| self::project(project, tasks, false))
+}
+
+pub fn status(status: Option<&Status>, json: bool) -> Result {
+    if json {
+        return print_json(&status);
+    }
+
+    match status {
+        None => println!("Not active task"),
+        Some(status) if status.finished => println!("{}: all cycles done", status.name),
+        Some(status) => println!(
+            "{}: {}{} {} left ({}/{} cycles done){}",
+            status.name,
+            if status.paused { "paused · " } else { "" },
+            status.phase,
+            format_clock(Duration::from_secs(status.remaining_secs)),
+            status.completed_cycles,
+            status.cycles,
+            if status.interruptions.is_empty() {
+                String::new()
+            } else {
+                format!(", interrupted {}", status.interruptions)
+            }
+        ),
+    }
+    Ok(())
+}
+
+pub fn interruptions(interruptions: &[Interruption], json: bool) -> Result {
+    if json {
+        return print_json(&interruptions);
+    }
+
+    for interruption in interruptions {
+        println!(
+            "{}  {:<8}  {}{}",
+            interruption.at.format("%d/%m %H:%M"),
+            interruption.kind.to_string(),
+            interruption.task,
+            interruption
+                .note
+                .as_ref()
+                .map(|note| format!(": {note}"))
+                .unwrap_or_default()
+        );
+    }
+    Ok(())
+}
+
+pub fn intervals(intervals: &[Interval], json: bool) -> Result {
+    if json {
+        return print_json(&intervals);
+    }
+
+    for interval in intervals {
+        println!(
+            "{} - {}  {:<11}  {}{}",
+            interval.start.format("%d/%m %H:%M"),
+            interval.end.format("%H:%M"),
+            interval.phase.to_string(),
+            interval.task,
+            interval
+                .interruption
+                .as_ref()
+                .map(|reason| format!(" ({reason})"))
+                .unwrap_or_default()
+        );
+    }
+    Ok(())
+}
+
+pub fn plan(plan: &Plan, json: bool) -> Result {
+    if json {
+        return print_json(plan);
+    }
+
+    let total: Duration = plan.intervals.iter().map(Interval::duration).sum();
+    println!(
+        "{} intervals to import, {}",
+        plan.intervals.len(),
+        format_duration(total)
+    );
+    for task in &plan.tasks {
+        println!("  new task: {}", task.name);
+    }
+    if plan.duplicates > 0 {
+        println!("{} duplicates left out", plan.duplicates);
+    }
+    if plan.skipped > 0 {
+        println!("{} entries skipped", plan.skipped);
+    }
+    if !plan.unmapped.is_empty() {
+        let unmapped: Vec<_> = plan.unmapped.iter().map(String::as_str).collect();
+        println!("Unmapped projects and tags: {}", unmapped.join(", "));
+    }
+    Ok(())
+}
@@ -0,0 +1,757 @@
+//! A headless front end for trackit.
+//!
+//! It works on the same data file and session log as the GUI, so a session can
+//! be started in one and paused or stopped in the other. While the GUI runs, the
+//! session commands are sent to its control socket instead, so they act on the
+//! live timer, and the commands changing the tasks or projects are refused.
+
+mod context;
+mod format;
+
+use std::{
+    error::Error,
+    fs::File,
+    io::{self, BufReader, BufWriter},
+    path::PathBuf,
+};
+
+use clap::{Parser, Subcommand};
+use context::Context;
+#[cfg(unix)]
+use trackit_core::ipc::{self, Call, Client};
+use trackit_core::{
+    chrono::{Days, Duration, NaiveDate},
+    export::{self, Format, Labels},
+    history::Filter,
+    import::{self, Mapping, Plan, Rule, Source},
+    interruption::Kind,
+    project::{self, Color, Group},
+    ActiveSession, Durations, Project, Task,
+};
+
+#[derive(Parser)]
+#[command(
+    name = "trackit-cli",
+    version,
+    about = "Track your pomodoros from the terminal"
+)]
+struct Cli {
+    /// Print machine readable JSON instead of text.
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Creates a new task.
+    Add {
+        name: String,
+        /// How many work blocks the task takes, up to 10.
+        #[arg(
+            short,
+            long,
+            default_value_t = 1,
+            value_parser = clap::value_parser!(u8).range(1..=10)
+        )]
+        cycles: u8,
+        /// The work block length, in minutes, up to 180.
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..=180))]
+        work: Option<u64>,
+        /// The short break length, in minutes, up to 60.
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..=60))]
+        short_break: Option<u64>,
+        /// The long break length, in minutes, up to 60.
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..=60))]
+        long_break: Option<u64>,
+        /// The project of the task, created if it doesn't exist.
+        #[arg(short, long, value_parser = parse_project)]
+        project: Option<String>,
+        /// A tag of the task, several can be given separated by commas.
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+    },
+    /// Lists every task.
+    List {
+        /// Only the tasks of this project.
+        #[arg(short, long)]
+        project: Option<String>,
+        /// Only the tasks with this tag.
+        #[arg(long)]
+        tag: Option<String>,
+        /// Lists the archived tasks instead.
+        #[arg(short, long)]
+        archived: bool,
+    },
+    /// Marks a task as done, stopping its session if it's running.
+    Done { task: String },
+    /// Moves a completed task to the archive.
+    Archive { task: String },
+    /// Takes a task out of the archive.
+    Restore { task: String },
+    /// Deletes an archived task for good, or every archived task.
+    Purge {
+        /// The task, given its id or name.
+        #[arg(required_unless_present = "all")]
+        task: Option<String>,
+        #[arg(long, conflicts_with = "task")]
+        all: bool,
+    },
+    /// Adds tags to a task, or removes them.
+    Tag {
+        /// The task, given its id or name.
+        task: String,
+        /// The tags, several can be given separated by commas.
+        #[arg(required = true)]
+        tags: Vec<String>,
+        /// Removes the tags instead.
+        #[arg(short, long)]
+        remove: bool,
+    },
+    /// Manages the projects.
+    Project {
+        #[command(subcommand)]
+        command: ProjectCommand,
+    },
+    /// Starts a session for a task, given its id or name.
+    Start { task: String },
+    /// Pauses the running session.
+    Pause,
+    /// Resumes the paused session.
+    Resume,
+    /// Jumps to the next phase of the running session.
+    Skip,
+    /// Stops the running session.
+    Stop,
+    /// Notes an interruption of the running work block.
+    Interrupt {
+        /// Either internal or external.
+        kind: Kind,
+        /// What it was about.
+        note: Option<String>,
+    },
+    /// Shows the running session.
+    Status,
+    /// Shows the recorded intervals.
+    Log {
+        /// Only the intervals of this task, given its id or name.
+        #[arg(short, long)]
+        task: Option<String>,
+        /// Only the intervals of the last days.
+        #[arg(short, long, default_value_t = 1)]
+        days: i64,
+        /// Shows the interruptions noted instead.
+        #[arg(short, long)]
+        interruptions: bool,
+    },
+    /// Exports the recorded intervals, for timesheets or calendars.
+    Export {
+        /// One of csv, jsonl or ics.
+        #[arg(short, long, default_value = "csv")]
+        format: Format,
+        /// The first day exported, as YYYY-MM-DD. Defaults to 30 days ago.
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// The last day exported, as YYYY-MM-DD. Defaults to today.
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        /// Only the intervals of this task, given its id or name.
+        #[arg(short, long)]
+        task: Option<String>,
+        /// Only the intervals of the tasks of this project.
+        #[arg(short, long)]
+        project: Option<String>,
+        /// Only the intervals of the tasks with this tag.
+        #[arg(long)]
+        tag: Option<String>,
+        /// Exports the breaks too, iCalendar never has them.
+        #[arg(long)]
+        breaks: bool,
+        /// The file written, the standard output by default.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Imports tasks and time entries from another tracker.
+    ///
+    /// Projects are named as is and tags with a leading `#`. Every project and
+    /// tag of the export must be mapped with --map, --keep or --skip, unless
+    /// --accept-unmapped is passed.
+    Import {
+        /// One of toggl, clockify or super-productivity.
+        source: Source,
+        /// The CSV export of Toggl or Clockify, or the JSON backup of Super Productivity.
+        file: PathBuf,
+        /// Imports the entries of a project or tag into a task, as NAME=TASK.
+        #[arg(short, long, value_parser = parse_map)]
+        map: Vec<(String, String)>,
+        /// Imports the entries of a project or tag into tasks named after them.
+        #[arg(short, long)]
+        keep: Vec<String>,
+        /// Leaves out the entries of a project or tag.
+        #[arg(short, long)]
+        skip: Vec<String>,
+        /// Shows what would be imported, without writing anything.
+        #[arg(long)]
+        dry_run: bool,
+        /// Imports the entries of the unmapped projects and tags as with --keep.
+        #[arg(long)]
+        accept_unmapped: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProjectCommand {
+    /// Creates a new project.
+    Add {
+        name: String,
+        /// Its colour, as #rrggbb. Defaults to the next one of the palette.
+        #[arg(short, long)]
+        color: Option<Color>,
+    },
+    /// Lists every project.
+    List,
+    /// Changes the colour of a project.
+    Color { name: String, color: Color },
+    /// Moves a task to a project, or out of its project if none is given.
+    Assign {
+        /// The task, given its id or name.
+        task: String,
+        project: Option<String>,
+    },
+    /// Deletes a project, its tasks are kept without project.
+    Remove { name: String },
+}
+
+impl Command {
+    /// The call to send to the GUI, for the commands that drive the session.
+    #[cfg(unix)]
+    fn call(&self) -> Option<Call> {
+        match self {
+            Command::Start { task } => Some(Call::StartTask { task: task.clone() }),
+            Command::Pause => Some(Call::Pause),
+            Command::Resume => Some(Call::Resume),
+            Command::Skip => Some(Call::SkipPhase),
+            Command::Stop => Some(Call::Stop),
+            Command::Status => Some(Call::Status),
+            Command::Interrupt { kind, note } => Some(Call::Interrupt {
+                kind: *kind,
+                note: note.clone(),
+            }),
+            Command::Add { .. }
+            | Command::List { .. }
+            | Command::Done { .. }
+            | Command::Archive { .. }
+            | Command::Restore { .. }
+            | Command::Purge { .. }
+            | Command::Tag { .. }
+            | Command::Project { .. }
+            | Command::Log { .. }
+            | Command::Export { .. }
+            | Command::Import { .. } => None,
+        }
+    }
+
+    /// Whether the command only reads the data, so it can run beside the GUI.
+    fn reads_only(&self) -> bool {
+        match self {
+            Command::List { .. } | Command::Log { .. } | Command::Export { .. } => true,
+            Command::Project { command } => matches!(command, ProjectCommand::List),
+            Command::Import { dry_run, .. } => *dry_run,
+            Command::Add { .. }
+            | Command::Done { .. }
+            | Command::Archive { .. }
+            | Command::Restore { .. }
+            | Command::Purge { .. }
+            | Command::Tag { .. }
+            | Command::Start { .. }
+            | Command::Pause
+            | Command::Resume
+            | Command::Skip
+            | Command::Stop
+            | Command::Interrupt { .. }
+            | Command::Status => false,
+        }
+    }
+}
+
+/// Parses a `NAME=TASK` mapping of the import command.
+fn parse_map(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((name, task)) if !name.trim().is_empty() && !task.trim().is_empty() => {
+            Ok((name.trim().to_string(), task.trim().to_string()))
+        }
+        _ => Err(format!("`{arg}` is not a NAME=TASK mapping")),
+    }
+}
+
+/// Parses a project name, trimmed.
+fn parse_project(arg: &str) -> Result<String, String> {
+    match arg.trim() {
+        "" => Err("the project name can't be blank".into()),
+        name => Ok(name.to_string()),
+    }
+}
+
+/// The group of the `project` or `tag` options of a command, `None` without
+/// them.
+fn group(
+    context: &Context,
+    project: Option<&str>,
+    tag: Option<&str>,
+) -> Result<Option<Group>, Box<dyn Error>> {
+    match (project, tag) {
+        (Some(_), Some(_)) => Err("filter either by project or by tag".into()),
+        (Some(name), None) => Ok(Some(Group::Project(context.find_project(name)?.id))),
+        (None, Some(tag)) => Ok(project::normalize_tag(tag).map(Group::Tag)),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Connects to the GUI, if it's running.
+#[cfg(unix)]
+fn connect() -> Option<Client> {
+    Client::connect(&ipc::socket_path().ok()?).ok()
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+
+    let reads_only = cli.command.reads_only();
+    // the GUI can only be reached over a Unix socket
+    #[cfg(unix)]
+    if let Some(mut client) = connect() {
+        if let Some(call) = cli.command.call() {
+            let status = client.call(call)?;
+            return format::status(status.as_ref(), cli.json);
+        }
+        // the GUI keeps its own copy of the data, it would overwrite the change
+        // on its next save
+        if !reads_only {
+            return Err("close the GUI before changing the tasks or projects".into());
+        }
+    }
+
+    let mut context = if reads_only {
+        Context::read()?
+    } else {
+        Context::load()?
+    };
+
+    match cli.command {
+        Command::Add {
+            name,
+            cycles,
+            work,
+            short_break,
+            long_break,
+            project,
+            tags,
+        } => {
+            let defaults = context.data.settings.durations;
+            let minutes = |duration: std::time::Duration| duration.as_secs() / 60;
+            let durations = Durations::from_minutes(
+                work.unwrap_or(minutes(defaults.work)),
+                short_break.unwrap_or(minutes(defaults.short_break)),
+                long_break.unwrap_or(minutes(defaults.long_break)),
+            );
+
+            let name = trackit_core::check_name(&context.data.tasks, &name, None)?;
+            let mut task = Task::new(name, cycles, durations);
+            task.project = project.map(|name| context.data.ensure_project(&name));
+            task.tags = project::parse_tags(&tags.join(","));
+            format::task(&task, &context.data.projects, cli.json)?;
+            context.data.tasks.push(task);
+        }
+        Command::List {
+            project,
+            tag,
+            archived,
+        } => {
+            let group = group(&context, project.as_deref(), tag.as_deref())?;
+            let tasks: Vec<_> = context
+                .data
+                .tasks
+                .iter()
+                .filter(|task| task.is_archived() == archived)
+                .filter(|task| group.as_ref().is_none_or(|group| group.contains(task)))
+                .cloned()
+                .collect();
+            format::tasks(&tasks, &context.data.projects, cli.json)?;
+        }
+        Command::Done { task } => {
+            let id = context.find_task(&task)?.id;
+            if context
+                .data
+                .active
+                .as_ref()
+                .is_some_and(|active| active.task == id)
+            {
+                context.drive(|active, task, now| active.stop(task, now, "completed"))?;
+                context.data.active = None;
+            }
+
+            let now = context.now;
+            let task = context.data.task_mut(id).expect("task exists");
+            task.complete(now);
+            let task = task.clone();
+            format::task(&task, &context.data.projects, cli.json)?;
+        }
+        Command::Archive { task } => {
+            let id = context.find_task(&task)?.id;
+            let task = context.data.task_mut(id).expect("task exists");
+            if !task.is_completed() {
+                return Err("only completed tasks can be archived".into());
+            }
+            task.archive();
+            let task = task.clone();
+            format::task(&task, &context.data.projects, cli.json)?;
+        }
+        Command::Restore { task } => {
+            let id = context.find_task(&task)?.id;
+            let task = context.data.task_mut(id).expect("task exists");
+            if !task.is_archived() {
+                return Err("the task is not archived".into());
+            }
+            task.restore();
+            let task = task.clone();
+            format::task(&task, &context.data.projects, cli.json)?;
+        }
+        Command::Purge { task, all } => {
+            let purged: Vec<_> = match task {
+                _ if all => context
+                    .data
+                    .tasks
+                    .iter()
+                    .filter(|task| task.is_archived())
+                    .map(|task| task.id)
+                    .collect(),
+                Some(task) => {
+                    let task = context.find_task(&task)?;
+                    if !task.is_archived() {
+                        return Err("only archived tasks can be purged".into());
+                    }
+                    vec![task.id]
+                }
+                None => Vec::new(),
+            };
+            context.data.tasks.retain(|task| !purged.contains(&task.id));
+            format::purged(&purged, cli.json)?;
+        }
+        Command::Tag { task, tags, remove } => {
+            let id = context.find_task(&task)?.id;
+            let task = context.data.task_mut(id).expect("task exists");
+            for tag in project::parse_tags(&tags.join(",")) {
+                if remove {
+                    task.tags.retain(|own| !own.eq_ignore_ascii_case(&tag));
+                } else if !task.has_tag(&tag) {
+                    task.tags.push(tag);
+                }
+            }
+            let task = task.clone();
+            format::task(&task, &context.data.projects, cli.json)?;
+        }
+        Command::Project { command } => match command {
+            ProjectCommand::Add { name, color } => {
+                if context.data.project_named(&name).is_some() {
+                    return Err(format!("the project `{name}` already exists").into());
+                }
+                let color = color.unwrap_or(project::next_color(&context.data.projects));
+                let project = Project::new(name.trim().to_string(), color);
+                format::project(&project, &context.data.tasks, cli.json)?;
+                context.data.projects.push(project);
+            }
+            ProjectCommand::List => {
+                format::projects(&context.data.projects, &context.data.tasks, cli.json)?
+            }
+            ProjectCommand::Color { name, color } => {
+                let id = context.find_project(&name)?.id;
+                let project = context
+                    .data
+                    .projects
+                    .iter_mut()
+                    .find(|project| project.id == id)
+                    .expect("project exists");
+                project.color = color;
+                format::project(project, &context.data.tasks, cli.json)?;
+            }
+            ProjectCommand::Assign { task, project } => {
+                let id = context.find_task(&task)?.id;
+                let project = project
+                    .map(|name| context.find_project(&name).map(|project| project.id))
+                    .transpose()?;
+                let task = context.data.task_mut(id).expect("task exists");
+                task.project = project;
+                let task = task.clone();
+                format::task(&task, &context.data.projects, cli.json)?;
+            }
+            ProjectCommand::Remove { name } => {
+                let id = context.find_project(&name)?.id;
+                context.data.projects.retain(|project| project.id != id);
+                for task in &mut context.data.tasks {
+                    if task.project == Some(id) {
+                        task.project = None;
+                    }
+                }
+            }
+        },
+        Command::Start { task } => {
+            let id = context.find_task(&task)?.id;
+            if context
+                .data
+                .active
+                .as_ref()
+                .is_some_and(|active| active.task == id)
+            {
+                return Err("the task is already running".into());
+            }
+            if context.data.active.is_some() {
+                context.drive(|active, task, now| active.stop(task, now, "switched task"))?;
+            }
+
+            let now = context.now;
+            let task = context.data.task_mut(id).expect("task exists");
+            task.start(now);
+            context.data.active = Some(ActiveSession::new(task, now));
+            format::status(context.status().as_ref(), cli.json)?;
+        }
+        Command::Pause => {
+            context.drive(|active, task, now| active.pause(task, now))?;
+            format::status(context.status().as_ref(), cli.json)?;
+        }
+        Command::Resume => {
+            let now = context.now;
+            let resumed = context
+                .data
+                .active
+                .as_mut()
+                .is_some_and(|active| active.resume(now));
+            if !resumed {
+                return Err("there's no paused session".into());
+            }
+            format::status(context.status().as_ref(), cli.json)?;
+        }
+        Command::Skip => {
+            context.drive(|active, task, now| active.skip(task, now))?;
+            format::status(context.status().as_ref(), cli.json)?;
+        }
+        Command::Stop => {
+            context.drive(|active, task, now| active.stop(task, now, "stopped"))?;
+            context.data.active = None;
+            format::status(context.status().as_ref(), cli.json)?;
+        }
+        Command::Interrupt { kind, note } => {
+            context.interrupt(kind, note)?;
+            format::status(context.status().as_ref(), cli.json)?;
+        }
+        Command::Status => format::status(context.status().as_ref(), cli.json)?,
+        Command::Log {
+            task,
+            days,
+            interruptions,
+        } => {
+            let task = task
+                .map(|task| context.find_task(&task).map(|task| task.id))
+                .transpose()?;
+            let filter = Filter {
+                from: Some(context.now - Duration::days(days)),
+                task,
+                ..Filter::default()
+            };
+            if interruptions {
+                format::interruptions(&context.log.interruptions(&filter)?, cli.json)?;
+            } else {
+                format::intervals(&context.log.intervals(&filter)?, cli.json)?;
+            }
+        }
+        Command::Export {
+            format,
+            from,
+            to,
+            task,
+            project,
+            tag,
+            breaks,
+            output,
+        } => {
+            let group = group(&context, project.as_deref(), tag.as_deref())?;
+            let to = to.unwrap_or(context.now.date_naive());
+            let from = from.unwrap_or(to - Days::new(30));
+            let filter = Filter {
+                task: task
+                    .map(|task| context.find_task(&task).map(|task| task.id))
+                    .transpose()?,
+                ..Filter::days(from, to)
+            };
+
+            let mut intervals = context.log.intervals(&filter)?;
+            if !breaks {
+                intervals.retain(|interval| !interval.phase.is_break());
+            }
+            if let Some(group) = group {
+                intervals.retain(|interval| {
+                    context
+                        .data
+                        .task(interval.task_id)
+                        .is_some_and(|task| group.contains(task))
+                });
+            }
+
+            let labels = Labels {
+                tasks: &context.data.tasks,
+                projects: &context.data.projects,
+            };
+            match output {
+                Some(path) => export::export(
+                    &intervals,
+                    labels,
+                    format,
+                    BufWriter::new(File::create(path)?),
+                )?,
+                None => export::export(&intervals, labels, format, io::stdout().lock())?,
+            }
+        }
+        Command::Import {
+            source,
+            file,
+            map,
+            keep,
+            skip,
+            dry_run,
+            accept_unmapped,
+        } => {
+            let entries = import::parse(source, BufReader::new(File::open(file)?))?;
+            let mut mapping = Mapping::default();
+            mapping.rules.extend(
+                map.into_iter()
+                    .map(|(name, task)| (name, Rule::Task(task)))
+                    .chain(keep.into_iter().map(|name| (name, Rule::Keep)))
+                    .chain(skip.into_iter().map(|name| (name, Rule::Skip))),
+            );
+
+            let plan = Plan::new(
+                &entries,
+                &mapping,
+                &context.data.tasks,
+                &context.log,
+                context.data.settings.durations,
+            )?;
+            format::plan(&plan, cli.json)?;
+            if dry_run {
+                return Ok(());
+            }
+            if !plan.unmapped.is_empty() && !accept_unmapped {
+                return Err("map every project and tag with --map, --keep or --skip, \
+                    or pass --accept-unmapped"
+                    .into());
+            }
+            plan.apply(&mut context.data, &mut context.log)?;
+        }
+    }
+
+    if reads_only {
+        return Ok(());
+    }
+    context.save()
+}
+
+#[cfg(test)]
+mod tests {
+    use trackit_core::storage::Data;
+
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, clap::Error> {
+        Cli::try_parse_from(["trackit-cli"].iter().chain(args)).map(|cli| cli.command)
+    }
+
+    #[test]
+    fn a_task_is_added_with_its_options() {
+        let command = parse(&[
+            "add", "Write", "-c", "4", "--work", "50", "-p", " Book ", "--tag", "a,b",
+        ])
+        .unwrap();
+
+        let Command::Add {
+            name,
+            cycles,
+            work,
+            short_break,
+            project,
+            tags,
+            ..
+        } = command
+        else {
+            panic!("not an add command");
+        };
+        assert_eq!(name, "Write");
+        assert_eq!((cycles, work, short_break), (4, Some(50), None));
+        assert_eq!(project.as_deref(), Some("Book"));
+        assert_eq!(tags, ["a,b"]);
+    }
+
+    #[test]
+    fn the_lengths_of_a_task_are_bounded_as_in_the_gui() {
+        assert!(parse(&["add", "Write", "--work", "0"]).is_err());
+        assert!(parse(&["add", "Write", "--work", "181"]).is_err());
+        assert!(parse(&["add", "Write", "--work", "180"]).is_ok());
+        assert!(parse(&["add", "Write", "--short-break", "61"]).is_err());
+        assert!(parse(&["add", "Write", "--long-break", "0"]).is_err());
+        assert!(parse(&["add", "Write", "--cycles", "0"]).is_err());
+        assert!(parse(&["add", "Write", "--cycles", "11"]).is_err());
+    }
+
+    #[test]
+    fn a_blank_project_is_refused() {
+        assert!(parse(&["add", "Write", "--project", ""]).is_err());
+        assert!(parse(&["add", "Write", "--project", "  "]).is_err());
+    }
+
+    #[test]
+    fn purging_needs_a_task_or_all() {
+        assert!(parse(&["purge"]).is_err());
+        assert!(parse(&["purge", "Write", "--all"]).is_err());
+        assert!(matches!(
+            parse(&["purge", "--all"]),
+            Ok(Command::Purge {
+                task: None,
+                all: true
+            })
+        ));
+    }
+
+    #[test]
+    fn the_import_mappings_are_name_task_pairs() {
+        assert_eq!(
+            parse_map(" Book = Write "),
+            Ok(("Book".to_string(), "Write".to_string()))
+        );
+        assert!(parse_map("Book").is_err());
+        assert!(parse_map("=Write").is_err());
+        assert!(parse_map("Book= ").is_err());
+    }
+
+    #[test]
+    fn the_reading_commands_run_beside_the_gui() {
+        assert!(parse(&["list"]).unwrap().reads_only());
+        assert!(parse(&["project", "list"]).unwrap().reads_only());
+        assert!(!parse(&["project", "add", "Book"]).unwrap().reads_only());
+        assert!(!parse(&["add", "Write"]).unwrap().reads_only());
+    }
+
+    #[test]
+    fn a_group_is_either_a_project_or_a_tag() {
+        let context = Context::with_data(Data::default());
+
+        assert!(group(&context, Some("Book"), Some("draft")).is_err());
+        assert_eq!(
+            group(&context, Some("Book"), None).unwrap_err().to_string(),
+            "no project named `Book`"
+        );
+        assert_eq!(
+            group(&context, None, Some("#Draft")).unwrap(),
+            Some(Group::Tag("Draft".into()))
+        );
+        assert_eq!(group(&context, None, None).unwrap(), None);
+    }
+}
@@ -0,0 +1,588 @@
+//! The session currently running, shared by every front end.
+//!
+//! [`ActiveSession`] wraps a [`Session`] with the [`Task`] it belongs to and turns
+//! what happens to it into an [`Outcome`]: the intervals to log and the work time
+//! to add to the task. This way the GUI and the CLI account time the same way.
+//!
+//! The session is saved with the data and driven from wall-clock instants, so
+//! it survives a restart or a suspend as is. A front end that watches it, like
+//! the GUI, leaves a heartbeat with [`ActiveSession::watch`]: when the session
+//! catches up across a long silence, the phases that ended unwatched are
+//! reported as an [`Overrun`].
+
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    history,
+    idle::{Choice, IDLE},
+    interruption::{self, Counts, Interruption},
+    Interval, Phase, Session, SessionLog, Task, TaskId, Transition,
+};
+
+/// How long a watched session can go without a heartbeat before the phases
+/// ending meanwhile are deemed unwatched.
+pub const UNWATCHED: Duration = Duration::from_secs(2 * 60);
+
+/// The running session and the task it belongs to.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ActiveSession {
+    pub task: TaskId,
+    pub session: Session,
+    /// The interruptions noted so far.
+    #[serde(default)]
+    pub interruptions: Counts,
+    /// The last heartbeat of the front end watching the session, `None` while
+    /// nobody does, like between two CLI commands.
+    #[serde(default)]
+    pub watched_at: Option<DateTime<Local>>,
+    /// When the user went idle, the session being held since, until they're
+    /// back and tell what the idle span was.
+    #[serde(default)]
+    pub idle_since: Option<DateTime<Local>>,
+}
+
+/// Phases that ended while nobody watched the session: the machine slept or
+/// the app was closed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overrun {
+    /// The last heartbeat before the silence.
+    pub since: DateTime<Local>,
+    /// When the session was caught up.
+    pub until: DateTime<Local>,
+    /// The phases that ended in between, in order.
+    pub phases: Vec<Phase>,
+}
+
+impl Overrun {
+    /// How long nobody watched.
+    pub fn duration(&self) -> Duration {
+        (self.until - self.since).to_std().unwrap_or_default()
+    }
+
+    /// The work blocks that ended unwatched.
+    pub fn work_blocks(&self) -> usize {
+        self.phases
+            .iter()
+            .filter(|phase| **phase == Phase::Work)
+            .count()
+    }
+}
+
+/// The consequences of driving an [`ActiveSession`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[must_use = "the outcome must be applied to the task and the log"]
+pub struct Outcome {
+    /// The intervals to record in the session log.
+    pub intervals: Vec<Interval>,
+    /// The work time to add to the task.
+    pub worked: Duration,
+    /// The phase transitions that happened.
+    pub transitions: Vec<Transition>,
+    /// When the last cycle ended, if the session just finished.
+    pub finished_at: Option<DateTime<Local>>,
+    /// The phases that ended unwatched, if any.
+    pub overrun: Option<Overrun>,
+}
+
+impl Outcome {
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+            && self.worked.is_zero()
+            && self.transitions.is_empty()
+            && self.finished_at.is_none()
+            && self.overrun.is_none()
+    }
+
+    /// Adds the work time to the `task`, completes it if the session finished,
+    /// and records the intervals in the `log`, if any.
+    pub fn apply(
+        &mut self,
+        task: &mut Task,
+        log: Option<&SessionLog>,
+    ) -> Result<(), history::Error> {
+        task.elapsed += self.worked;
+        if let Some(at) = self.finished_at {
+            task.complete(at);
+        }
+
+        if let Some(log) = log {
+            for interval in &mut self.intervals {
+                log.record(interval)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn merge(&mut self, other: Outcome) {
+        self.intervals.extend(other.intervals);
+        self.worked += other.worked;
+        self.transitions.extend(other.transitions);
+        self.finished_at = self.finished_at.or(other.finished_at);
+        self.overrun = self.overrun.take().or(other.overrun);
+    }
+}
+
+impl ActiveSession {
+    /// Starts a session for the `task` at `now`.
+    pub fn new(task: &Task, now: DateTime<Local>) -> Self {
+        Self {
+            task: task.id,
+            session: Session::new(task, now),
+            interruptions: Counts::default(),
+            watched_at: None,
+            idle_since: None,
+        }
+    }
+
+    /// Leaves the heartbeat of a front end watching the session at `now`.
+    pub fn watch(&mut self, now: DateTime<Local>) {
+        self.watched_at = Some(now);
+    }
+
+    /// Notes an interruption of the given `kind` at `now`, `None` unless a work
+    /// block is running.
+    ///
+    /// The session is expected to be advanced up to `now` already.
+    pub fn interrupt(
+        &mut self,
+        task: &Task,
+        kind: interruption::Kind,
+        note: Option<String>,
+        now: DateTime<Local>,
+    ) -> Option<Interruption> {
+        if !self.session.is_running() || self.session.phase() != Phase::Work {
+            return None;
+        }
+
+        self.interruptions.add(kind);
+        Some(Interruption::new(
+            task.id,
+            task.name.clone(),
+            kind,
+            note,
+            now,
+        ))
+    }
+
+    /// Moves the session up to `now`, every finished phase is logged and every
+    /// finished work block is accounted.
+    ///
+    /// After a silence of the watching front end longer than [`UNWATCHED`], the
+    /// phases that ended meanwhile are reported once as an [`Overrun`].
+    pub fn advance(&mut self, task: &Task, now: DateTime<Local>) -> Outcome {
+        let silence = self.watched_at.filter(|seen| {
+            (now - *seen)
+                .to_std()
+                .is_ok_and(|silence| silence > UNWATCHED)
+        });
+        let transitions = self.session.advance(now);
+        let finished_at = transitions
+            .iter()
+            .find(|transition| transition.to.is_none())
+            .map(|transition| transition.at);
+        let overrun = silence.and_then(|since| {
+            // the watcher has to show up again, the overrun is reported once
+            self.watched_at = None;
+            let phases: Vec<_> = transitions
+                .iter()
+                .filter(|transition| transition.at > since)
+                .map(|transition| transition.from)
+                .collect();
+            (!phases.is_empty()).then_some(Overrun {
+                since,
+                until: now,
+                phases,
+            })
+        });
+
+        Outcome {
+            intervals: transitions
+                .iter()
+                .map(|transition| {
+                    Interval::new(
+                        task.id,
+                        task.name.clone(),
+                        transition.from,
+                        transition.started_at,
+                        transition.at,
+                    )
+                })
+                .collect(),
+            worked: transitions
+                .iter()
+                .filter(|transition| transition.from == Phase::Work)
+                .map(|_| self.session.durations().work)
+                .sum(),
+            transitions,
+            finished_at,
+            overrun,
+        }
+    }
+
+    /// Whether the session is held over an idle span, waiting for [`Self::back`].
+    pub fn is_idle(&self) -> bool {
+        self.idle_since.is_some()
+    }
+
+    /// Holds the session from `since`, when the user went idle, as of `now`.
+    ///
+    /// The stretch before `since` is logged, the idle span is left out until the
+    /// user is [back](Self::back). Nothing happens unless the session is running.
+    pub fn idle(&mut self, task: &Task, since: DateTime<Local>, now: DateTime<Local>) -> Outcome {
+        let mut outcome = self.advance(task, now);
+        if !self.session.is_running() {
+            return outcome;
+        }
+
+        // the idle span can't reach back before the current stretch
+        let (phase, start) = (self.session.phase(), self.session.segment_started_at());
+        let since = since.clamp(start, now);
+        self.session.pause(since);
+        self.idle_since = Some(since);
+        // the split off idle span is the one logged as idle
+        if since > start {
+            outcome.intervals.push(
+                Interval::new(task.id, task.name.clone(), phase, start, since)
+                    .interrupted("went idle"),
+            );
+        }
+
+        outcome
+    }
+
+    /// Ends the idle span at `until`, when the user came back, settled at `now`
+    /// as they `choice`d.
+    ///
+    /// The time from `until` to `now`, spent deciding, is not idle.
+    pub fn back(
+        &mut self,
+        task: &Task,
+        choice: Choice,
+        until: DateTime<Local>,
+        now: DateTime<Local>,
+    ) -> Outcome {
+        let Some(since) = self.idle_since.take() else {
+            return Outcome::default();
+        };
+        // the idle span is not an overrun, the user just told what it was
+        self.watched_at = None;
+        let until = until.min(now).max(since);
+
+        match choice {
+            Choice::Discard => {
+                self.session.resume(until);
+                self.advance(task, now)
+            }
+            Choice::Keep => {
+                self.session.unpause();
+                self.session.split_segment(since);
+                self.advance(task, now)
+            }
+            Choice::Split => {
+                // held over like a discarded span, the phase doesn't move on
+                let phase = self.session.phase();
+                self.session.resume(until);
+                let mut outcome = Outcome::default();
+                if until > since {
+                    outcome.intervals.push(
+                        Interval::new(task.id, task.name.clone(), phase, since, until)
+                            .interrupted(IDLE),
+                    );
+                }
+                outcome.merge(self.advance(task, now));
+                outcome
+            }
+        }
+    }
+
+    /// Pauses the session at `now`, logging the stretch that just ended.
+    ///
+    /// Like any other move, pausing settles an idle span as discarded.
+    pub fn pause(&mut self, task: &Task, now: DateTime<Local>) -> Outcome {
+        self.idle_since = None;
+        let mut outcome = self.advance(task, now);
+
+        let (phase, start) = (self.session.phase(), self.session.segment_started_at());
+        if self.session.pause(now) {
+            outcome.intervals.push(
+                Interval::new(task.id, task.name.clone(), phase, start, now).interrupted("paused"),
+            );
+        }
+
+        outcome
+    }
+
+    /// Resumes the session at `now`, returns `false` if it wasn't paused.
+    pub fn resume(&mut self, now: DateTime<Local>) -> bool {
+        self.idle_since = None;
+        self.session.resume(now)
+    }
+
+    /// Jumps to the next phase at `now`, logging the current one as skipped.
+    ///
+    /// The work done in a skipped work block still counts towards the task.
+    pub fn skip(&mut self, task: &Task, now: DateTime<Local>) -> Outcome {
+        self.idle_since = None;
+        let mut outcome = self.advance(task, now);
+
+        // a paused session already logged its last stretch when it was paused
+        let running = self.session.is_running();
+        let worked = match self.session.phase() {
+            Phase::Work => self.session.worked(now),
+            Phase::ShortBreak | Phase::LongBreak => Duration::ZERO,
+        };
+        let Some(transition) = self.session.skip(now) else {
+            return outcome;
+        };
+
+        let mut skipped = Outcome {
+            worked,
+            transitions: vec![transition],
+            finished_at: transition.to.is_none().then_some(transition.at),
+            ..Outcome::default()
+        };
+        if running {
+            skipped.intervals.push(
+                Interval::new(
+                    task.id,
+                    task.name.clone(),
+                    transition.from,
+                    transition.started_at,
+                    transition.at,
+                )
+                .interrupted("skipped"),
+            );
+        }
+        outcome.merge(skipped);
+
+        outcome
+    }
+
+    /// Ends the session at `now`, logging its current phase as cut short by `reason`.
+    ///
+    /// The work done in an unfinished work block still counts towards the task.
+    pub fn stop(&mut self, task: &Task, now: DateTime<Local>, reason: &str) -> Outcome {
+        self.idle_since = None;
+        let mut outcome = self.advance(task, now);
+        if self.session.is_finished() {
+            return outcome;
+        }
+
+        // a paused session already logged its last stretch when it was paused
+        let running = self.session.is_running();
+        let (phase, start) = (self.session.phase(), self.session.segment_started_at());
+        let worked = self.session.stop(now);
+
+        let mut stopped = Outcome {
+            worked,
+            ..Outcome::default()
+        };
+        if running {
+            stopped.intervals.push(
+                Interval::new(task.id, task.name.clone(), phase, start, now).interrupted(reason),
+            );
+        }
+        outcome.merge(stopped);
+
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeDelta, TimeZone};
+
+    use super::*;
+    use crate::Durations;
+
+    /// `minutes` after the session start.
+    fn at(minutes: i64) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap() + TimeDelta::minutes(minutes)
+    }
+
+    fn start() -> (Task, ActiveSession) {
+        let task = Task::new("Write".into(), 4, Durations::default());
+        let active = ActiveSession::new(&task, at(0));
+        (task, active)
+    }
+
+    #[test]
+    fn phases_ending_unwatched_are_an_overrun() {
+        let (task, mut active) = start();
+        active.watch(at(10));
+
+        let outcome = active.advance(&task, at(70));
+
+        let overrun = outcome.overrun.unwrap();
+        assert_eq!((overrun.since, overrun.until), (at(10), at(70)));
+        assert_eq!(
+            overrun.phases,
+            [
+                Phase::Work,
+                Phase::ShortBreak,
+                Phase::Work,
+                Phase::ShortBreak
+            ]
+        );
+        assert_eq!(overrun.work_blocks(), 2);
+        assert_eq!(overrun.duration(), Duration::from_secs(60 * 60));
+        assert_eq!(outcome.intervals.len(), 4);
+        assert_eq!(outcome.worked, Duration::from_secs(50 * 60));
+    }
+
+    #[test]
+    fn an_overrun_is_reported_once() {
+        let (task, mut active) = start();
+        active.watch(at(10));
+        let _ = active.advance(&task, at(70));
+
+        assert_eq!(active.watched_at, None);
+        assert_eq!(active.advance(&task, at(100)).overrun, None);
+    }
+
+    #[test]
+    fn a_watched_session_has_no_overrun() {
+        let (task, mut active) = start();
+        active.watch(at(24));
+
+        let outcome = active.advance(&task, at(26));
+
+        assert_eq!(outcome.intervals.len(), 1);
+        assert_eq!(outcome.overrun, None);
+    }
+
+    #[test]
+    fn a_session_nobody_watched_has_no_overrun() {
+        let (task, mut active) = start();
+
+        let outcome = active.advance(&task, at(70));
+
+        assert_eq!(outcome.transitions.len(), 4);
+        assert_eq!(outcome.overrun, None);
+    }
+
+    /// A session that went idle at 10 minutes, noticed at 15.
+    fn held() -> (Task, ActiveSession, Outcome) {
+        let (task, mut active) = start();
+        let outcome = active.idle(&task, at(10), at(15));
+        (task, active, outcome)
+    }
+
+    #[test]
+    fn going_idle_logs_the_stretch_before_and_holds_the_session() {
+        let (_, active, outcome) = held();
+
+        assert!(active.is_idle());
+        assert!(active.session.is_paused());
+        assert_eq!(outcome.intervals.len(), 1);
+        assert_eq!(
+            (outcome.intervals[0].start, outcome.intervals[0].end),
+            (at(0), at(10))
+        );
+        assert_eq!(
+            outcome.intervals[0].interruption.as_deref(),
+            Some("went idle")
+        );
+    }
+
+    #[test]
+    fn a_kept_idle_span_counts_as_work() {
+        let (task, mut active, _) = held();
+
+        let outcome = active.back(&task, Choice::Keep, at(20), at(22));
+
+        assert!(outcome.is_empty());
+        assert!(!active.is_idle());
+        assert_eq!(
+            active.session.remaining(at(22)),
+            Duration::from_secs(3 * 60)
+        );
+        let ended = active.advance(&task, at(25));
+        assert_eq!(
+            (ended.intervals[0].start, ended.intervals[0].end),
+            (at(10), at(25))
+        );
+    }
+
+    #[test]
+    fn a_discarded_idle_span_resumes_when_the_user_came_back() {
+        let (task, mut active, _) = held();
+
+        let outcome = active.back(&task, Choice::Discard, at(20), at(22));
+
+        assert!(outcome.intervals.is_empty());
+        assert!(active.session.is_running());
+        // paused from 10 to 20, not up to the choice at 22
+        assert_eq!(
+            active.session.remaining(at(22)),
+            Duration::from_secs(13 * 60)
+        );
+    }
+
+    #[test]
+    fn a_split_idle_span_is_logged_apart() {
+        let (task, mut active, _) = held();
+
+        let outcome = active.back(&task, Choice::Split, at(20), at(22));
+
+        assert_eq!(outcome.intervals.len(), 1);
+        let idle = &outcome.intervals[0];
+        assert_eq!((idle.start, idle.end), (at(10), at(20)));
+        assert_eq!(idle.interruption.as_deref(), Some(IDLE));
+        assert!(!idle.is_work());
+        assert_eq!(outcome.worked, Duration::ZERO);
+        // held from 10 to 20, like a discarded span
+        assert_eq!(
+            active.session.remaining(at(22)),
+            Duration::from_secs(13 * 60)
+        );
+        let ended = active.advance(&task, at(35));
+        assert_eq!(
+            (ended.intervals[0].start, ended.intervals[0].end),
+            (at(20), at(35))
+        );
+    }
+
+    #[test]
+    fn a_split_idle_span_longer_than_the_phase_ends_no_phase() {
+        let (task, mut active, _) = held();
+
+        let outcome = active.back(&task, Choice::Split, at(40), at(41));
+
+        let spans: Vec<_> = outcome
+            .intervals
+            .iter()
+            .map(|interval| (interval.phase, interval.start, interval.end))
+            .collect();
+        assert_eq!(spans, [(Phase::Work, at(10), at(40))]);
+        assert_eq!(outcome.intervals[0].interruption.as_deref(), Some(IDLE));
+        assert!(outcome.transitions.is_empty());
+        assert_eq!(outcome.worked, Duration::ZERO);
+        // the work block still has the 15 minutes left when the user went idle
+        assert_eq!(active.session.phase(), Phase::Work);
+        assert_eq!(
+            active.session.remaining(at(41)),
+            Duration::from_secs(14 * 60)
+        );
+    }
+
+    #[test]
+    fn nothing_happens_when_back_without_being_idle() {
+        let (task, mut active) = start();
+
+        assert!(active.back(&task, Choice::Discard, at(5), at(6)).is_empty());
+        assert!(active.session.is_running());
+    }
+
+    #[test]
+    fn a_long_silence_within_a_phase_is_no_overrun() {
+        let (task, mut active) = start();
+        active.watch(at(1));
+
+        assert_eq!(active.advance(&task, at(20)).overrun, None);
+    }
+}
@@ -0,0 +1,402 @@
+//! Exports of the session log, for timesheets and calendars.
+//!
+//! Intervals are written as [`Record`]s in CSV or JSON Lines, or as iCalendar
+//! events, one `VEVENT` per focus block. Each interval is labelled with the
+//! project and tags its task has at the time of the export.
+
+use std::{
+    fmt,
+    io::{self, Write},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{history::phase_to_sql, Interval, Phase, Project, Task, TaskId};
+
+/// The `PRODID` of the exported calendars.
+const PRODID: &str = "-//trackit//trackit//EN";
+/// The longest line allowed by iCalendar, in octets.
+const ICAL_LINE: usize = 75;
+
+/// A file format the log can be exported to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Csv,
+    JsonLines,
+    Ical,
+}
+
+impl Format {
+    pub const ALL: [Format; 3] = [Format::Csv, Format::JsonLines, Format::Ical];
+
+    /// The usual extension of the files of this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::JsonLines => "jsonl",
+            Format::Ical => "ics",
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Format::Csv => "CSV",
+            Format::JsonLines => "JSON Lines",
+            Format::Ical => "iCalendar",
+        })
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    /// Parses a format from its extension.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Format::ALL
+            .into_iter()
+            .find(|format| format.extension().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown format `{s}`, expected csv, jsonl or ics"))
+    }
+}
+
+/// The error type of an export.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Csv(csv::Error),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "i/o error: {err}"),
+            Error::Csv(err) => write!(f, "csv error: {err}"),
+            Error::Json(err) => write!(f, "json error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Csv(err) => Some(err),
+            Error::Json(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(err: csv::Error) -> Self {
+        Error::Csv(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+/// An exported interval, flat so it fits in a CSV row.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Record {
+    pub task_id: TaskId,
+    pub task: String,
+    /// Empty if the task has no project.
+    pub project: String,
+    /// Separated by commas.
+    pub tags: String,
+    /// One of `work`, `short_break` or `long_break`.
+    pub phase: String,
+    /// RFC 3339, in local time.
+    pub start: String,
+    /// RFC 3339, in local time.
+    pub end: String,
+    pub duration_secs: u64,
+    /// Empty if the phase ran to completion.
+    pub interruption: String,
+}
+
+impl Record {
+    pub fn new(interval: &Interval, labels: Labels<'_>) -> Self {
+        let (project, tags) = labels.of(interval.task_id);
+
+        Self {
+            task_id: interval.task_id,
+            task: interval.task.clone(),
+            project: project
+                .map(|project| project.name.clone())
+                .unwrap_or_default(),
+            tags: tags.join(", "),
+            phase: phase_to_sql(interval.phase).to_string(),
+            start: interval.start.to_rfc3339_opts(SecondsFormat::Secs, false),
+            end: interval.end.to_rfc3339_opts(SecondsFormat::Secs, false),
+            duration_secs: interval.duration().as_secs(),
+            interruption: interval.interruption.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// Where the project and tags of the exported intervals are looked up.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Labels<'a> {
+    pub tasks: &'a [Task],
+    pub projects: &'a [Project],
+}
+
+impl<'a> Labels<'a> {
+    /// The project and tags of the task with the given `id`.
+    fn of(&self, id: TaskId) -> (Option<&'a Project>, &'a [String]) {
+        let Some(task) = self.tasks.iter().find(|task| task.id == id) else {
+            return (None, &[]);
+        };
+        let project = task
+            .project
+            .and_then(|id| self.projects.iter().find(|project| project.id == id));
+
+        (project, &task.tags)
+    }
+}
+
+/// Where exports are saved by default: the downloads directory, or the home one.
+pub fn default_dir() -> Option<PathBuf> {
+    dirs::download_dir().or_else(dirs::home_dir)
+}
+
+/// Writes the `intervals` to `writer` in the given `format`, labelled with
+/// their project and tags.
+///
+/// iCalendar only holds the work intervals, the others are skipped.
+pub fn export(
+    intervals: &[Interval],
+    labels: Labels<'_>,
+    format: Format,
+    writer: impl Write,
+) -> Result<(), Error> {
+    match format {
+        Format::Csv => csv(intervals, labels, writer),
+        Format::JsonLines => json_lines(intervals, labels, writer),
+        Format::Ical => ical(intervals, labels, Local::now(), writer),
+    }
+}
+
+fn csv(intervals: &[Interval], labels: Labels<'_>, writer: impl Write) -> Result<(), Error> {
+    let mut writer = csv::Writer::from_writer(writer);
+    for interval in intervals {
+        writer.serialize(Record::new(interval, labels))?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn json_lines(
+    intervals: &[Interval],
+    labels: Labels<'_>,
+    mut writer: impl Write,
+) -> Result<(), Error> {
+    for interval in intervals {
+        serde_json::to_writer(&mut writer, &Record::new(interval, labels))?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn ical(
+    intervals: &[Interval],
+    labels: Labels<'_>,
+    now: DateTime<Local>,
+    mut writer: impl Write,
+) -> Result<(), Error> {
+    let mut line = |content: String| -> io::Result<()> {
+        writer.write_all(fold(&content).as_bytes())?;
+        writer.write_all(b"\r\n")
+    };
+
+    line("BEGIN:VCALENDAR".into())?;
+    line("VERSION:2.0".into())?;
+    line(format!("PRODID:{PRODID}"))?;
+    for interval in intervals.iter().filter(|i| i.phase == Phase::Work) {
+        line("BEGIN:VEVENT".into())?;
+        line(format!(
+            "UID:{}-{}@trackit",
+            interval.task_id,
+            interval.start.timestamp_millis()
+        ))?;
+        line(format!("DTSTAMP:{}", utc(now)))?;
+        line(format!("DTSTART:{}", utc(interval.start)))?;
+        line(format!("DTEND:{}", utc(interval.end)))?;
+        line(format!("SUMMARY:{}", escape(&interval.task)))?;
+        let (project, tags) = labels.of(interval.task_id);
+        let categories: Vec<_> = project
+            .map(|project| escape(&project.name))
+            .into_iter()
+            .chain(tags.iter().map(|tag| escape(tag)))
+            .collect();
+        if !categories.is_empty() {
+            line(format!("CATEGORIES:{}", categories.join(",")))?;
+        }
+        if let Some(reason) = &interval.interruption {
+            line(format!(
+                "DESCRIPTION:{}",
+                escape(&format!("Cut short: {reason}"))
+            ))?;
+        }
+        line("END:VEVENT".into())?;
+    }
+    line("END:VCALENDAR".into())?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// An instant as an iCalendar UTC date-time.
+fn utc(instant: DateTime<Local>) -> String {
+    instant
+        .with_timezone(&Utc)
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+/// Escapes a text value of iCalendar.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(char);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            char => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+/// Folds a content line so no line is longer than [`ICAL_LINE`] octets,
+/// without splitting a character.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut width = 0;
+    for char in line.chars() {
+        if width + char.len_utf8() > ICAL_LINE {
+            folded.push_str("\r\n ");
+            // the leading space counts towards the new line
+            width = 1;
+        }
+        folded.push(char);
+        width += char.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeDelta, TimeZone};
+
+    use super::*;
+    use crate::{project::Color, Durations};
+
+    fn interval(task: &Task, phase: Phase) -> Interval {
+        let start = Local.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap();
+        Interval::new(
+            task.id,
+            task.name.clone(),
+            phase,
+            start,
+            start + TimeDelta::minutes(25),
+        )
+    }
+
+    fn calendar(intervals: &[Interval], labels: Labels<'_>) -> String {
+        let mut output = Vec::new();
+        ical(intervals, labels, Local::now(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn text_values_are_escaped() {
+        assert_eq!(escape(r"a\b;c,d"), r"a\\b\;c\,d");
+        assert_eq!(escape("one\r\ntwo"), r"one\ntwo");
+    }
+
+    #[test]
+    fn short_lines_are_left_alone() {
+        let line = "x".repeat(ICAL_LINE);
+
+        assert_eq!(fold(&line), line);
+    }
+
+    #[test]
+    fn long_lines_are_folded_every_75_octets() {
+        let folded = fold(&"x".repeat(200));
+
+        let lines: Vec<_> = folded.split("\r\n").collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().all(|line| line.len() <= ICAL_LINE));
+        assert!(lines[1..].iter().all(|line| line.starts_with(' ')));
+        assert_eq!(folded.replace("\r\n ", ""), "x".repeat(200));
+    }
+
+    #[test]
+    fn characters_are_never_split() {
+        // 2 octets each, the 38th would end on the 76th octet
+        let line = "é".repeat(40);
+
+        let folded = fold(&line);
+
+        let first = folded.split("\r\n").next().unwrap();
+        assert_eq!(first.len(), 74);
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+
+    #[test]
+    fn only_work_intervals_become_events() {
+        let task = Task::new("Write".into(), 1, Durations::default());
+        let intervals = [
+            interval(&task, Phase::Work),
+            interval(&task, Phase::ShortBreak),
+        ];
+
+        let calendar = calendar(&intervals, Labels::default());
+
+        assert_eq!(calendar.matches("BEGIN:VEVENT").count(), 1);
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn events_are_labelled_with_the_project_and_tags() {
+        let project = Project::new("Docs, v2".into(), Color::PALETTE[0]);
+        let mut task = Task::new("Write; review".into(), 1, Durations::default());
+        task.project = Some(project.id);
+        task.tags = vec!["deep".into()];
+        let labels = Labels {
+            tasks: std::slice::from_ref(&task),
+            projects: std::slice::from_ref(&project),
+        };
+
+        let calendar = calendar(&[interval(&task, Phase::Work)], labels);
+
+        assert!(calendar.contains("\r\nSUMMARY:Write\\; review\r\n"));
+        assert!(calendar.contains("\r\nCATEGORIES:Docs\\, v2,deep\r\n"));
+    }
+}
@@ -0,0 +1,70 @@
+//! The daily goal: how much focus makes a good day.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::stats::Day;
+
+/// A daily target, met once the day reaches it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Goal {
+    /// A number of completed pomodoros.
+    Pomodoros(u32),
+    /// A number of minutes of focus.
+    Minutes(u32),
+}
+
+impl Default for Goal {
+    fn default() -> Self {
+        Goal::Pomodoros(8)
+    }
+}
+
+impl Goal {
+    /// The target, in pomodoros or minutes.
+    pub fn target(&self) -> u32 {
+        match self {
+            Goal::Pomodoros(target) | Goal::Minutes(target) => *target,
+        }
+    }
+
+    /// How far the `day` went, in pomodoros or minutes.
+    pub fn reached(&self, day: &Day) -> u32 {
+        match self {
+            Goal::Pomodoros(_) => day.pomodoros,
+            Goal::Minutes(_) => (day.focus.as_secs() / 60) as u32,
+        }
+    }
+
+    /// The share of the goal reached by the `day`, `1.0` once it's met.
+    pub fn progress(&self, day: &Day) -> f32 {
+        match self.target() {
+            0 => 1.0,
+            target => (self.reached(day) as f32 / target as f32).min(1.0),
+        }
+    }
+
+    /// Whether the `day` met the goal.
+    pub fn is_met(&self, day: &Day) -> bool {
+        self.reached(day) >= self.target()
+    }
+
+    /// The same kind of goal with another `target`.
+    pub fn with_target(&self, target: u32) -> Self {
+        match self {
+            Goal::Pomodoros(_) => Goal::Pomodoros(target),
+            Goal::Minutes(_) => Goal::Minutes(target),
+        }
+    }
+}
+
+impl fmt::Display for Goal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Goal::Pomodoros(target) => write!(f, "{target} pomodoros"),
+            Goal::Minutes(target) => write!(f, "{}h {:02}m of focus", target / 60, target % 60),
+        }
+    }
+}
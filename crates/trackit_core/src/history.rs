@@ -0,0 +1,712 @@
+//! The session log: every work and break interval, backed by SQLite.
+//!
+//! Unlike the [`Task`](crate::Task) itself, which only remembers when it was
+//! last started, the log keeps one row per phase, so history views and reports
+//! can be built from it. The interruptions noted during work blocks are kept
+//! alongside.
+
+use std::{fmt, fs, io, path::Path};
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use rusqlite::{params, types::Type, Connection, OptionalExtension, Row, Transaction};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    interruption::{Interruption, Kind},
+    storage::{self, Data},
+    Phase, TaskId,
+};
+
+const LOG_FILE: &str = "history.sqlite3";
+
+type Migration = fn(&Transaction<'_>) -> rusqlite::Result<()>;
+
+/// The migrations of the database, the `user_version` pragma tracks the ones applied.
+const MIGRATIONS: &[Migration] = &[
+    create_intervals,
+    add_task_ids,
+    create_interruptions,
+    add_split_origins,
+];
+
+fn create_intervals(tx: &Transaction<'_>) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE intervals (
+            id INTEGER PRIMARY KEY,
+            task TEXT NOT NULL,
+            phase TEXT NOT NULL,
+            start INTEGER NOT NULL,
+            end INTEGER NOT NULL,
+            interruption TEXT
+        );
+        CREATE INDEX intervals_start ON intervals (start);",
+    )
+}
+
+/// Intervals were keyed by task name, they get a placeholder
+/// [`TaskId::from_legacy_name`] until [linked](SessionLog::link_migrated) to
+/// their task.
+fn add_task_ids(tx: &Transaction<'_>) -> rusqlite::Result<()> {
+    tx.execute_batch("ALTER TABLE intervals ADD COLUMN task_id TEXT NOT NULL DEFAULT ''")?;
+
+    let names = tx
+        .prepare("SELECT DISTINCT task FROM intervals")?
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    for name in names {
+        tx.execute(
+            "UPDATE intervals SET task_id = ?1 WHERE task = ?2",
+            params![TaskId::from_legacy_name(&name).to_string(), name],
+        )?;
+    }
+
+    tx.execute_batch("CREATE INDEX intervals_task_id ON intervals (task_id)")
+}
+
+fn create_interruptions(tx: &Transaction<'_>) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE interruptions (
+            id INTEGER PRIMARY KEY,
+            task_id TEXT NOT NULL,
+            task TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            note TEXT,
+            at INTEGER NOT NULL
+        );
+        CREATE INDEX interruptions_at ON interruptions (at);",
+    )
+}
+
+/// The parts of a split interval point to the interval they were split off,
+/// so its pomodoro is counted once.
+fn add_split_origins(tx: &Transaction<'_>) -> rusqlite::Result<()> {
+    tx.execute_batch("ALTER TABLE intervals ADD COLUMN split_from INTEGER")
+}
+
+/// The error type of the [`SessionLog`].
+#[derive(Debug)]
+pub enum Error {
+    /// There's no data directory on this platform.
+    NoDataDir,
+    Io(io::Error),
+    Sqlite(rusqlite::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoDataDir => f.write_str("unable to find the data directory"),
+            Error::Io(err) => write!(f, "i/o error: {err}"),
+            Error::Sqlite(err) => write!(f, "database error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::NoDataDir => None,
+            Error::Io(err) => Some(err),
+            Error::Sqlite(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        Error::Sqlite(err)
+    }
+}
+
+/// A recorded phase of a session.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Interval {
+    /// The row id, `0` until the interval is recorded.
+    pub id: i64,
+    pub task_id: TaskId,
+    /// The name the task had when the interval was recorded.
+    pub task: String,
+    pub phase: Phase,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    /// Why the phase was cut short, `None` if it ran to completion.
+    pub interruption: Option<String>,
+    /// The interval this one was split off, which holds the pomodoro of both.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub split_from: Option<i64>,
+}
+
+impl Interval {
+    /// Creates a completed interval, not recorded yet.
+    pub fn new(
+        task_id: TaskId,
+        task: impl Into<String>,
+        phase: Phase,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    ) -> Self {
+        Self {
+            id: 0,
+            task_id,
+            task: task.into(),
+            phase,
+            start,
+            end,
+            interruption: None,
+            split_from: None,
+        }
+    }
+
+    /// Marks the interval as cut short for the given `reason`.
+    pub fn interrupted(mut self, reason: impl Into<String>) -> Self {
+        self.interruption = Some(reason.into());
+        self
+    }
+
+    /// Whether the interval is work time: a work block not split off as idle.
+    pub fn is_work(&self) -> bool {
+        self.phase == Phase::Work && self.interruption.as_deref() != Some(crate::idle::IDLE)
+    }
+
+    /// The length of the interval.
+    pub fn duration(&self) -> std::time::Duration {
+        (self.end - self.start).to_std().unwrap_or_default()
+    }
+
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<Self> {
+        let phase: String = row.get("phase")?;
+        let phase = phase_from_sql(&phase).ok_or_else(|| {
+            rusqlite::Error::FromSqlConversionFailure(
+                3,
+                Type::Text,
+                format!("unknown phase `{phase}`").into(),
+            )
+        })?;
+        let task_id: String = row.get("task_id")?;
+        let task_id = task_id.parse().map_err(|err| {
+            rusqlite::Error::FromSqlConversionFailure(1, Type::Text, Box::new(err))
+        })?;
+
+        Ok(Self {
+            id: row.get("id")?,
+            task_id,
+            task: row.get("task")?,
+            phase,
+            start: from_millis(row.get("start")?),
+            end: from_millis(row.get("end")?),
+            interruption: row.get("interruption")?,
+            split_from: row.get("split_from")?,
+        })
+    }
+}
+
+fn interruption_from_row(row: &Row<'_>) -> rusqlite::Result<Interruption> {
+    let kind: String = row.get("kind")?;
+    let kind = kind
+        .parse::<Kind>()
+        .map_err(|err| rusqlite::Error::FromSqlConversionFailure(3, Type::Text, err.into()))?;
+    let task_id: String = row.get("task_id")?;
+    let task_id = task_id
+        .parse()
+        .map_err(|err| rusqlite::Error::FromSqlConversionFailure(1, Type::Text, Box::new(err)))?;
+
+    Ok(Interruption {
+        id: row.get("id")?,
+        task_id,
+        task: row.get("task")?,
+        kind,
+        note: row.get("note")?,
+        at: from_millis(row.get("at")?),
+    })
+}
+
+/// Restricts the intervals returned by [`SessionLog::intervals`], and the
+/// interruptions returned by [`SessionLog::interruptions`] by when they were
+/// noted.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    /// Only intervals starting at or after this instant.
+    pub from: Option<DateTime<Local>>,
+    /// Only intervals starting before this instant.
+    pub to: Option<DateTime<Local>>,
+    /// Only intervals of this task.
+    pub task: Option<TaskId>,
+}
+
+impl Filter {
+    /// Only the intervals starting between the `from` and `to` days, both included.
+    pub fn days(from: NaiveDate, to: NaiveDate) -> Self {
+        let midnight = |date: NaiveDate| {
+            date.and_hms_opt(0, 0, 0)
+                .and_then(|midnight| Local.from_local_datetime(&midnight).earliest())
+        };
+
+        Self {
+            from: midnight(from),
+            to: to.succ_opt().and_then(midnight),
+            task: None,
+        }
+    }
+}
+
+/// The log of every recorded [`Interval`].
+pub struct SessionLog {
+    conn: Connection,
+}
+
+impl fmt::Debug for SessionLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionLog")
+            .field("path", &self.conn.path())
+            .finish()
+    }
+}
+
+impl SessionLog {
+    /// Opens (or creates) the log at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Opens the default log inside the XDG data directory.
+    pub fn open_default() -> Result<Self, Error> {
+        let dir = storage::data_dir().map_err(|_| Error::NoDataDir)?;
+        Self::open(dir.join(LOG_FILE))
+    }
+
+    /// Opens a throwaway log living in memory.
+    pub fn open_in_memory() -> Result<Self, Error> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(mut conn: Connection) -> Result<Self, Error> {
+        migrate(&mut conn)?;
+        Ok(Self { conn })
+    }
+
+    /// Records the `interval`, setting its id.
+    pub fn record(&self, interval: &mut Interval) -> Result<(), Error> {
+        insert(&self.conn, interval)
+    }
+
+    /// Records all the `intervals` in one transaction, setting their ids.
+    ///
+    /// Either every interval is recorded or none is.
+    pub fn record_all(&mut self, intervals: &mut [Interval]) -> Result<(), Error> {
+        let tx = self.conn.transaction()?;
+        for interval in intervals.iter_mut() {
+            insert(&tx, interval)?;
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// The interval with the given `id`, if it's recorded.
+    pub fn interval(&self, id: i64) -> Result<Option<Interval>, Error> {
+        let interval = self
+            .conn
+            .query_row(
+                "SELECT id, task_id, task, phase, start, end, interruption, split_from
+                 FROM intervals
+                 WHERE id = ?1",
+                params![id],
+                Interval::from_row,
+            )
+            .optional()?;
+
+        Ok(interval)
+    }
+
+    /// The intervals of any task overlapping the span from `start` to `end`,
+    /// oldest first. Intervals merely touching the span don't overlap it.
+    pub fn overlapping(
+        &self,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    ) -> Result<Vec<Interval>, Error> {
+        let mut statement = self.conn.prepare(
+            "SELECT id, task_id, task, phase, start, end, interruption, split_from
+             FROM intervals
+             WHERE start < ?2 AND end > ?1
+             ORDER BY start, id",
+        )?;
+
+        let intervals = statement
+            .query_map(
+                params![start.timestamp_millis(), end.timestamp_millis()],
+                Interval::from_row,
+            )?
+            .collect::<Result<_, _>>()?;
+
+        Ok(intervals)
+    }
+
+    /// Deletes the `removed` intervals and records the `added` ones in one
+    /// transaction.
+    ///
+    /// An added interval keeps its id if it has one, so an interval can be
+    /// replaced by a corrected version of itself. The others get a new id.
+    pub fn rewrite(&mut self, removed: &[Interval], added: &mut [Interval]) -> Result<(), Error> {
+        let tx = self.conn.transaction()?;
+        for interval in removed {
+            tx.execute("DELETE FROM intervals WHERE id = ?1", params![interval.id])?;
+        }
+        for interval in added.iter_mut() {
+            insert(&tx, interval)?;
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Hands the intervals recorded before ids existed to the tasks of the
+    /// `data` that just got an id, clearing [`Data::migrated`].
+    ///
+    /// The intervals were keyed by name, so those of a name several tasks share
+    /// can't be told apart: they're left to no task, and the names are returned.
+    pub fn link_migrated(&mut self, data: &mut Data) -> Result<Vec<String>, Error> {
+        let migrated: Vec<_> = data
+            .tasks
+            .iter()
+            .filter(|task| data.migrated.contains(&task.id))
+            .collect();
+
+        let mut ambiguous = Vec::new();
+        let tx = self.conn.transaction()?;
+        for task in &migrated {
+            if migrated
+                .iter()
+                .filter(|other| other.name == task.name)
+                .count()
+                > 1
+            {
+                if !ambiguous.contains(&task.name) {
+                    ambiguous.push(task.name.clone());
+                }
+                continue;
+            }
+
+            tx.execute(
+                "UPDATE intervals SET task_id = ?1 WHERE task_id = ?2 AND task = ?3",
+                params![
+                    task.id.to_string(),
+                    TaskId::from_legacy_name(&task.name).to_string(),
+                    task.name
+                ],
+            )?;
+        }
+        tx.commit()?;
+
+        data.migrated.clear();
+        Ok(ambiguous)
+    }
+
+    /// The intervals matching the `filter`, oldest first.
+    pub fn intervals(&self, filter: &Filter) -> Result<Vec<Interval>, Error> {
+        let mut statement = self.conn.prepare(
+            "SELECT id, task_id, task, phase, start, end, interruption, split_from
+             FROM intervals
+             WHERE (?1 IS NULL OR start >= ?1)
+               AND (?2 IS NULL OR start < ?2)
+               AND (?3 IS NULL OR task_id = ?3)
+             ORDER BY start, id",
+        )?;
+
+        let intervals = statement
+            .query_map(
+                params![
+                    filter.from.map(|from| from.timestamp_millis()),
+                    filter.to.map(|to| to.timestamp_millis()),
+                    filter.task.map(|task| task.to_string()),
+                ],
+                Interval::from_row,
+            )?
+            .collect::<Result<_, _>>()?;
+
+        Ok(intervals)
+    }
+
+    /// Records the `interruption`, setting its id.
+    pub fn record_interruption(&self, interruption: &mut Interruption) -> Result<(), Error> {
+        self.conn.execute(
+            "INSERT INTO interruptions (task_id, task, kind, note, at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                interruption.task_id.to_string(),
+                interruption.task,
+                interruption.kind.as_str(),
+                interruption.note,
+                interruption.at.timestamp_millis(),
+            ],
+        )?;
+        interruption.id = self.conn.last_insert_rowid();
+
+        Ok(())
+    }
+
+    /// The interruptions matching the `filter`, oldest first.
+    pub fn interruptions(&self, filter: &Filter) -> Result<Vec<Interruption>, Error> {
+        let mut statement = self.conn.prepare(
+            "SELECT id, task_id, task, kind, note, at FROM interruptions
+             WHERE (?1 IS NULL OR at >= ?1)
+               AND (?2 IS NULL OR at < ?2)
+               AND (?3 IS NULL OR task_id = ?3)
+             ORDER BY at, id",
+        )?;
+
+        let interruptions = statement
+            .query_map(
+                params![
+                    filter.from.map(|from| from.timestamp_millis()),
+                    filter.to.map(|to| to.timestamp_millis()),
+                    filter.task.map(|task| task.to_string()),
+                ],
+                interruption_from_row,
+            )?
+            .collect::<Result<_, _>>()?;
+
+        Ok(interruptions)
+    }
+}
+
+/// Inserts the `interval` through `conn`, setting its id if it has none.
+fn insert(conn: &Connection, interval: &mut Interval) -> Result<(), Error> {
+    conn.execute(
+        "INSERT INTO intervals (id, task_id, task, phase, start, end, interruption, split_from)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            (interval.id != 0).then_some(interval.id),
+            interval.task_id.to_string(),
+            interval.task,
+            phase_to_sql(interval.phase),
+            interval.start.timestamp_millis(),
+            interval.end.timestamp_millis(),
+            interval.interruption,
+            interval.split_from,
+        ],
+    )?;
+    interval.id = conn.last_insert_rowid();
+
+    Ok(())
+}
+
+fn migrate(conn: &mut Connection) -> Result<(), Error> {
+    let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+    let tx = conn.transaction()?;
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        migration(&tx)?;
+        tx.pragma_update(None, "user_version", index + 1)?;
+    }
+    tx.commit()?;
+
+    Ok(())
+}
+
+/// The name of the `phase` in the log and in exports.
+pub(crate) fn phase_to_sql(phase: Phase) -> &'static str {
+    match phase {
+        Phase::Work => "work",
+        Phase::ShortBreak => "short_break",
+        Phase::LongBreak => "long_break",
+    }
+}
+
+fn phase_from_sql(phase: &str) -> Option<Phase> {
+    match phase {
+        "work" => Some(Phase::Work),
+        "short_break" => Some(Phase::ShortBreak),
+        "long_break" => Some(Phase::LongBreak),
+        _ => None,
+    }
+}
+
+fn from_millis(millis: i64) -> DateTime<Local> {
+    Local
+        .timestamp_millis_opt(millis)
+        .single()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Datelike, Timelike};
+
+    use super::*;
+    use crate::Task;
+
+    fn task(name: &str) -> Task {
+        Task::new(name.into(), 1, Default::default())
+    }
+
+    /// A work interval of the `task`, from `hour` on the `day` of March 2024.
+    fn interval(task: &Task, day: u32, hour: u32) -> Interval {
+        let start = Local.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap();
+        Interval::new(
+            task.id,
+            task.name.clone(),
+            Phase::Work,
+            start,
+            start + chrono::TimeDelta::minutes(25),
+        )
+    }
+
+    fn march(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    fn starts(log: &SessionLog, filter: &Filter) -> Vec<(u32, u32)> {
+        log.intervals(filter)
+            .unwrap()
+            .iter()
+            .map(|interval| (interval.start.day(), interval.start.hour()))
+            .collect()
+    }
+
+    #[test]
+    fn a_recorded_interval_reads_back() {
+        let log = SessionLog::open_in_memory().unwrap();
+        let mut recorded = interval(&task("Write"), 4, 9).interrupted("stopped");
+
+        log.record(&mut recorded).unwrap();
+
+        assert_ne!(recorded.id, 0);
+        assert_eq!(log.interval(recorded.id).unwrap(), Some(recorded.clone()));
+        assert_eq!(log.interval(recorded.id + 1).unwrap(), None);
+    }
+
+    #[test]
+    fn intervals_are_filtered_by_day_and_task() {
+        let mut log = SessionLog::open_in_memory().unwrap();
+        let (write, read) = (task("Write"), task("Read"));
+        log.record_all(&mut [
+            interval(&read, 5, 10),
+            interval(&write, 4, 9),
+            interval(&write, 5, 9),
+            interval(&write, 6, 9),
+        ])
+        .unwrap();
+
+        // oldest first
+        assert_eq!(
+            starts(&log, &Filter::default()),
+            [(4, 9), (5, 9), (5, 10), (6, 9)]
+        );
+        assert_eq!(
+            starts(&log, &Filter::days(march(5), march(6))),
+            [(5, 9), (5, 10), (6, 9)]
+        );
+        assert_eq!(
+            starts(
+                &log,
+                &Filter {
+                    task: Some(write.id),
+                    ..Filter::days(march(4), march(5))
+                }
+            ),
+            [(4, 9), (5, 9)]
+        );
+        assert!(starts(&log, &Filter::days(march(7), march(8))).is_empty());
+    }
+
+    #[test]
+    fn a_log_of_the_first_version_is_migrated() {
+        let path = std::env::temp_dir().join(format!("trackit-log-{}.sqlite3", TaskId::new()));
+        {
+            let mut conn = Connection::open(&path).unwrap();
+            let tx = conn.transaction().unwrap();
+            create_intervals(&tx).unwrap();
+            tx.pragma_update(None, "user_version", 1).unwrap();
+            tx.execute(
+                "INSERT INTO intervals (task, phase, start, end, interruption)
+                 VALUES ('Write', 'work', ?1, ?2, 'stopped')",
+                params![
+                    Local
+                        .with_ymd_and_hms(2024, 3, 4, 9, 0, 0)
+                        .unwrap()
+                        .timestamp_millis(),
+                    Local
+                        .with_ymd_and_hms(2024, 3, 4, 9, 10, 0)
+                        .unwrap()
+                        .timestamp_millis(),
+                ],
+            )
+            .unwrap();
+            tx.commit().unwrap();
+        }
+
+        let log = SessionLog::open(&path).unwrap();
+        let intervals = log.intervals(&Filter::default()).unwrap();
+        let interruptions = log.interruptions(&Filter::default()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(intervals.len(), 1);
+        assert_eq!(intervals[0].task_id, TaskId::from_legacy_name("Write"));
+        assert_eq!(intervals[0].interruption.as_deref(), Some("stopped"));
+        assert_eq!(intervals[0].split_from, None);
+        assert!(interruptions.is_empty());
+    }
+
+    /// A work interval keyed by the name, as logged before tasks had ids.
+    fn legacy_interval(name: &str, hour: u32) -> Interval {
+        let start = Local.with_ymd_and_hms(2024, 3, 4, hour, 0, 0).unwrap();
+        Interval::new(
+            TaskId::from_legacy_name(name),
+            name,
+            Phase::Work,
+            start,
+            start + chrono::TimeDelta::minutes(25),
+        )
+    }
+
+    #[test]
+    fn migrated_tasks_take_over_the_intervals_of_their_name() {
+        let mut log = SessionLog::open_in_memory().unwrap();
+        log.record(&mut legacy_interval("Write", 9)).unwrap();
+        let write = task("Write");
+        let mut data = Data {
+            migrated: vec![write.id],
+            tasks: vec![write.clone()],
+            ..Data::default()
+        };
+
+        let ambiguous = log.link_migrated(&mut data).unwrap();
+
+        assert!(ambiguous.is_empty());
+        assert!(data.migrated.is_empty());
+        let intervals = log.intervals(&Filter::default()).unwrap();
+        assert_eq!(intervals[0].task_id, write.id);
+    }
+
+    #[test]
+    fn intervals_of_a_shared_name_are_left_unassigned() {
+        let mut log = SessionLog::open_in_memory().unwrap();
+        log.record(&mut legacy_interval("Write", 9)).unwrap();
+        log.record(&mut legacy_interval("Read", 10)).unwrap();
+        let tasks = vec![task("Write"), task("Write"), task("Read")];
+        let mut data = Data {
+            migrated: tasks.iter().map(|task| task.id).collect(),
+            tasks: tasks.clone(),
+            ..Data::default()
+        };
+
+        let ambiguous = log.link_migrated(&mut data).unwrap();
+
+        assert_eq!(ambiguous, ["Write"]);
+        let intervals = log.intervals(&Filter::default()).unwrap();
+        assert_eq!(intervals[0].task_id, TaskId::from_legacy_name("Write"));
+        assert_eq!(intervals[1].task_id, tasks[2].id);
+    }
+}
@@ -0,0 +1,223 @@
+//! Idle detection: noticing when the user walked away from a running session.
+//!
+//! A [`Backend`] tells how long the user has been idle, the [`Detector`] turns
+//! its readings into [`Event`]s once the idle time crosses a threshold. When the
+//! user comes back, they decide what the idle span was with a [`Choice`], see
+//! [`ActiveSession::back`](crate::ActiveSession::back).
+
+use std::{
+    fmt, io,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, Local};
+
+/// Why an idle span [split off](Choice::Split) was cut from the session, in the
+/// log. Such an interval is no work time, whatever its phase.
+pub const IDLE: &str = "idle";
+
+/// Something telling how long the user has been idle.
+pub trait Backend: Send {
+    /// The time since the last input of the user.
+    fn idle_time(&mut self) -> io::Result<Duration>;
+}
+
+/// A backend reporting the idle time it's told, for tests.
+///
+/// Its clones share the same idle time, so a test can keep one and hand the
+/// other to the code under test.
+#[derive(Debug, Clone, Default)]
+pub struct Fake {
+    idle: Arc<Mutex<Duration>>,
+}
+
+impl Fake {
+    /// Sets the idle time reported from now on.
+    pub fn set(&self, idle: Duration) {
+        if let Ok(mut current) = self.idle.lock() {
+            *current = idle;
+        }
+    }
+}
+
+impl Backend for Fake {
+    fn idle_time(&mut self) -> io::Result<Duration> {
+        self.idle
+            .lock()
+            .map(|idle| *idle)
+            .map_err(|_| io::Error::other("the fake idle time is poisoned"))
+    }
+}
+
+/// What the [`Detector`] noticed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// The user has been idle since `since`, for longer than the threshold.
+    Idle { since: DateTime<Local> },
+    /// The user, idle since `since`, came back at `until`.
+    Back {
+        since: DateTime<Local>,
+        until: DateTime<Local>,
+    },
+}
+
+/// Watches the idle time for spans longer than a threshold.
+#[derive(Debug, Clone)]
+pub struct Detector {
+    threshold: Duration,
+    idle_since: Option<DateTime<Local>>,
+}
+
+impl Detector {
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            idle_since: None,
+        }
+    }
+
+    pub fn set_threshold(&mut self, threshold: Duration) {
+        self.threshold = threshold;
+    }
+
+    /// Whether the user is away, as of the last reading.
+    pub fn is_idle(&self) -> bool {
+        self.idle_since.is_some()
+    }
+
+    /// Takes in the `idle` time read at `now`.
+    pub fn update(&mut self, idle: Duration, now: DateTime<Local>) -> Option<Event> {
+        let last_input = now - idle;
+
+        match self.idle_since {
+            None if idle >= self.threshold => {
+                self.idle_since = Some(last_input);
+                Some(Event::Idle { since: last_input })
+            }
+            Some(since) if idle < self.threshold => {
+                self.idle_since = None;
+                Some(Event::Back {
+                    since,
+                    until: last_input.max(since),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// What an idle span turns out to be, once the user is back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Choice {
+    /// It was work after all, the session goes on as if nothing happened.
+    Keep,
+    /// It wasn't work, the session is paused over it.
+    Discard,
+    /// It wasn't work, the session is paused over it, but it's logged apart as
+    /// idle.
+    Split,
+}
+
+impl fmt::Display for Choice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Choice::Keep => "Keep",
+            Choice::Discard => "Discard",
+            Choice::Split => "Split off",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeDelta, TimeZone};
+
+    use super::*;
+
+    const THRESHOLD: Duration = Duration::from_secs(5 * 60);
+
+    /// `minutes` after 9:00.
+    fn at(minutes: i64) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap() + TimeDelta::minutes(minutes)
+    }
+
+    /// An idle time of `minutes`.
+    fn idle(minutes: u64) -> Duration {
+        Duration::from_secs(minutes * 60)
+    }
+
+    /// A detector that noticed the user went idle at 9:00, at 9:06.
+    fn away() -> Detector {
+        let mut detector = Detector::new(THRESHOLD);
+        detector.update(idle(6), at(6));
+        detector
+    }
+
+    #[test]
+    fn short_breaks_go_unnoticed() {
+        let mut detector = Detector::new(THRESHOLD);
+
+        assert_eq!(detector.update(idle(1), at(1)), None);
+        assert_eq!(detector.update(idle(4), at(4)), None);
+        assert!(!detector.is_idle());
+    }
+
+    #[test]
+    fn the_idle_span_starts_at_the_last_input() {
+        let mut detector = Detector::new(THRESHOLD);
+
+        let went = detector.update(idle(6), at(6));
+        let stayed = detector.update(idle(20), at(20));
+
+        assert_eq!(went, Some(Event::Idle { since: at(0) }));
+        assert_eq!(stayed, None);
+        assert!(detector.is_idle());
+    }
+
+    #[test]
+    fn the_user_is_back_at_their_first_input() {
+        let mut detector = away();
+
+        let back = detector.update(idle(2), at(30));
+        let after = detector.update(idle(0), at(31));
+
+        assert_eq!(
+            back,
+            Some(Event::Back {
+                since: at(0),
+                until: at(28),
+            })
+        );
+        assert_eq!(after, None);
+        assert!(!detector.is_idle());
+    }
+
+    #[test]
+    fn going_idle_again_starts_a_new_span() {
+        let mut detector = away();
+        detector.update(idle(0), at(30));
+
+        let went = detector.update(idle(5), at(40));
+
+        assert_eq!(went, Some(Event::Idle { since: at(35) }));
+    }
+
+    #[test]
+    fn a_raised_threshold_ends_no_span_before_it_began() {
+        let mut detector = Detector::new(THRESHOLD);
+        detector.update(idle(10), at(10));
+
+        // still idle, but under the new threshold: back, but not before `since`
+        detector.set_threshold(idle(30));
+        let back = detector.update(idle(20), at(15));
+
+        assert_eq!(
+            back,
+            Some(Event::Back {
+                since: at(0),
+                until: at(0),
+            })
+        );
+    }
+}
@@ -0,0 +1,72 @@
+//! The CSV of a Clockify detailed report.
+//!
+//! Dates and times follow the workspace settings, so the usual formats are tried
+//! in turn, the US ones first as they're the default.
+//!
+//! ```text
+//! Project,Client,Description,Task,User,Email,Tags,Billable,Start Date,Start Time,End Date,End Time,Duration (h)
+//! Website,,Landing page,,Ana,ana@example.com,"design, web",No,01/15/2025,09:00:00 AM,01/15/2025,09:25:00 AM,00:25:00
+//! ```
+
+use std::io::Read;
+
+use super::{csv_entries, Entry, Error};
+
+pub(super) fn parse(reader: impl Read) -> Result<Vec<Entry>, Error> {
+    csv_entries(
+        reader,
+        &["%m/%d/%Y", "%Y-%m-%d", "%d.%m.%Y", "%d-%m-%Y"],
+        &["%I:%M:%S %p", "%I:%M %p", "%H:%M:%S", "%H:%M"],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+
+    use super::*;
+
+    const HEADER: &str = "Project,Client,Description,Task,User,Email,Tags,Billable,Start Date,Start Time,End Date,End Time,Duration (h)\n";
+
+    #[test]
+    fn us_dates_and_times_are_read() {
+        let csv = format!(
+            "{HEADER}Website,,Landing page,,Ana,ana@example.com,\"design, web\",No,01/15/2025,01:00:00 PM,01/15/2025,01:25:00 PM,00:25:00\n"
+        );
+
+        let entries = parse(csv.as_bytes()).unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].project.as_deref(), Some("Website"));
+        assert_eq!(entries[0].tags, ["design", "web"]);
+        assert_eq!(
+            entries[0].start,
+            Local.with_ymd_and_hms(2025, 1, 15, 13, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn european_dates_and_times_are_read() {
+        let csv = format!(
+            "{HEADER},,Landing page,,Ana,,,No,15.01.2025,13:00,15.01.2025,13:25,00:25:00\n"
+        );
+
+        let entries = parse(csv.as_bytes()).unwrap();
+
+        assert_eq!(entries[0].project, None);
+        assert_eq!(
+            entries[0].end,
+            Local.with_ymd_and_hms(2025, 1, 15, 13, 25, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn a_missing_column_is_reported() {
+        let csv = "Project,Description,Start Date,Start Time,End Date\n";
+
+        assert!(matches!(
+            parse(csv.as_bytes()),
+            Err(Error::MissingColumn("End time"))
+        ));
+    }
+}
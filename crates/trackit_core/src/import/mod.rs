@@ -0,0 +1,535 @@
+//! Imports from other time trackers.
+//!
+//! An import goes in three steps, nothing is written before the last one:
+//!
+//! 1. [`parse`] reads the export of a tracker into [`Entry`]s.
+//! 2. [`Plan::new`] decides where each entry goes, following a [`Mapping`] of
+//!    the projects and tags of the other tracker, and leaves out the entries
+//!    already in the log. The plan is the dry-run preview of the import.
+//! 3. [`Plan::apply`] adds the new tasks and records the intervals.
+
+mod clockify;
+mod super_productivity;
+mod toggl;
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fmt,
+    io::{self, Read},
+    str::FromStr,
+};
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use serde::Serialize;
+
+use crate::{
+    history::{self, Filter},
+    Data, Durations, Interval, Phase, SessionLog, Task, TaskId,
+};
+
+/// The name of the task of the entries without description nor project.
+const FALLBACK_TASK: &str = "Imported";
+
+/// A tracker that can be imported from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// The CSV of a Toggl Track detailed report.
+    Toggl,
+    /// The CSV of a Clockify detailed report.
+    Clockify,
+    /// The JSON backup of Super Productivity.
+    SuperProductivity,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Source::Toggl => "Toggl",
+            Source::Clockify => "Clockify",
+            Source::SuperProductivity => "Super Productivity",
+        })
+    }
+}
+
+impl FromStr for Source {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace(['-', '_', ' '], "").as_str() {
+            "toggl" => Ok(Source::Toggl),
+            "clockify" => Ok(Source::Clockify),
+            "superproductivity" | "sp" => Ok(Source::SuperProductivity),
+            _ => Err(format!(
+                "unknown source `{s}`, expected toggl, clockify or super-productivity"
+            )),
+        }
+    }
+}
+
+/// The error type of an import.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Csv(csv::Error),
+    Json(serde_json::Error),
+    /// The export lacks a column this importer needs.
+    MissingColumn(&'static str),
+    /// A date or a time couldn't be read, on the given line of a CSV export.
+    InvalidDate {
+        line: Option<usize>,
+        value: String,
+    },
+    /// The entries couldn't be compared with the log, or recorded in it.
+    Log(history::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "i/o error: {err}"),
+            Error::Csv(err) => write!(f, "csv error: {err}"),
+            Error::Json(err) => write!(f, "json error: {err}"),
+            Error::MissingColumn(column) => write!(f, "the `{column}` column is missing"),
+            Error::InvalidDate {
+                line: Some(line),
+                value,
+            } => write!(f, "line {line}: `{value}` is not a known date or time"),
+            Error::InvalidDate { line: None, value } => {
+                write!(f, "`{value}` is not a known date or time")
+            }
+            Error::Log(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Csv(err) => Some(err),
+            Error::Json(err) => Some(err),
+            Error::Log(err) => Some(err),
+            Error::MissingColumn(_) | Error::InvalidDate { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(err: csv::Error) -> Self {
+        Error::Csv(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+impl From<history::Error> for Error {
+    fn from(err: history::Error) -> Self {
+        Error::Log(err)
+    }
+}
+
+/// A time entry read from another tracker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub description: String,
+    pub project: Option<String>,
+    pub tags: Vec<String>,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+impl Entry {
+    /// The labels the entry can be mapped by: its project, then its tags as `#tag`.
+    pub fn labels(&self) -> impl Iterator<Item = String> + '_ {
+        self.project
+            .iter()
+            .cloned()
+            .chain(self.tags.iter().map(|tag| format!("#{tag}")))
+    }
+
+    /// The task the entry goes to when none of its labels is mapped.
+    fn default_task(&self) -> &str {
+        match (&self.description, &self.project) {
+            (description, _) if !description.is_empty() => description,
+            (_, Some(project)) => project,
+            _ => FALLBACK_TASK,
+        }
+    }
+}
+
+/// Reads the export of the `source` tracker.
+///
+/// Entries without an end, like a running timer, are left out.
+pub fn parse(source: Source, reader: impl Read) -> Result<Vec<Entry>, Error> {
+    let mut entries = match source {
+        Source::Toggl => toggl::parse(reader)?,
+        Source::Clockify => clockify::parse(reader)?,
+        Source::SuperProductivity => super_productivity::parse(reader)?,
+    };
+    entries.sort_by_key(|entry| entry.start);
+
+    Ok(entries)
+}
+
+/// What to do with the entries of a project or tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    /// Imports them into the task with this name, created if needed.
+    Task(String),
+    /// Imports them into a task named after their description.
+    Keep,
+    /// Leaves them out.
+    Skip,
+}
+
+/// How the projects and tags of the other tracker map to tasks.
+///
+/// Projects are given by their name and tags by their name prefixed with `#`.
+/// The project of an entry takes precedence over its tags.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Mapping {
+    pub rules: BTreeMap<String, Rule>,
+}
+
+impl Mapping {
+    /// The rule followed by the `entry`, `None` if none of its labels is mapped.
+    fn rule(&self, entry: &Entry) -> Option<&Rule> {
+        entry.labels().find_map(|label| self.rules.get(&label))
+    }
+}
+
+/// The preview of an import: what would be written to storage.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Plan {
+    /// The tasks to create.
+    pub tasks: Vec<Task>,
+    /// The intervals to record, in new or existing tasks.
+    pub intervals: Vec<Interval>,
+    /// The entries already in the log, or repeated in the export.
+    pub duplicates: usize,
+    /// The entries left out by the mapping.
+    pub skipped: usize,
+    /// The projects and tags found in the export without a rule.
+    pub unmapped: BTreeSet<String>,
+}
+
+impl Plan {
+    /// Plans the import of the `entries` following the `mapping`.
+    ///
+    /// Entries go to the existing task with the same name, if any, and new tasks
+    /// get the default `durations`.
+    pub fn new(
+        entries: &[Entry],
+        mapping: &Mapping,
+        tasks: &[Task],
+        log: &SessionLog,
+        durations: Durations,
+    ) -> Result<Self, Error> {
+        let mut plan = Plan::default();
+        let mut ids: BTreeMap<String, TaskId> = tasks
+            .iter()
+            .map(|task| (task.name.to_lowercase(), task.id))
+            .collect();
+        let mut seen = existing(entries, log)?;
+
+        for entry in entries {
+            plan.unmapped.extend(
+                entry
+                    .labels()
+                    .filter(|label| !mapping.rules.contains_key(label)),
+            );
+
+            let name = match mapping.rule(entry) {
+                Some(Rule::Skip) => {
+                    plan.skipped += 1;
+                    continue;
+                }
+                Some(Rule::Task(name)) => name.as_str(),
+                Some(Rule::Keep) | None => entry.default_task(),
+            };
+            let id = *ids.entry(name.to_lowercase()).or_insert_with(|| {
+                let task = Task::new(name.to_string(), 1, durations);
+                let id = task.id;
+                plan.tasks.push(task);
+                id
+            });
+
+            if !seen.insert((id, entry.start.timestamp(), entry.end.timestamp())) {
+                plan.duplicates += 1;
+                continue;
+            }
+            plan.intervals
+                .push(Interval::new(id, name, Phase::Work, entry.start, entry.end));
+        }
+
+        // the tasks whose every entry was a duplicate are not worth creating
+        plan.tasks
+            .retain(|task| plan.intervals.iter().any(|i| i.task_id == task.id));
+
+        Ok(plan)
+    }
+
+    /// Writes the plan: adds the new tasks to `data`, records the intervals in
+    /// the `log` and adds their time to their tasks.
+    ///
+    /// The intervals are recorded at once, so a failure leaves the log untouched.
+    pub fn apply(mut self, data: &mut Data, log: &mut SessionLog) -> Result<(), Error> {
+        log.record_all(&mut self.intervals)?;
+
+        data.tasks.append(&mut self.tasks);
+        for interval in &self.intervals {
+            if let Some(task) = data.task_mut(interval.task_id) {
+                task.elapsed += interval.duration();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The work intervals of the log overlapping the `entries`, as duplicate keys.
+fn existing(entries: &[Entry], log: &SessionLog) -> Result<HashSet<(TaskId, i64, i64)>, Error> {
+    let (Some(first), Some(last)) = (
+        entries.iter().map(|entry| entry.start).min(),
+        entries.iter().map(|entry| entry.start).max(),
+    ) else {
+        return Ok(HashSet::new());
+    };
+
+    let filter = Filter {
+        from: Some(first),
+        to: Some(last + chrono::Duration::seconds(1)),
+        task: None,
+    };
+    Ok(log
+        .intervals(&filter)?
+        .into_iter()
+        .filter(|interval| interval.phase == Phase::Work)
+        .map(|interval| {
+            (
+                interval.task_id,
+                interval.start.timestamp(),
+                interval.end.timestamp(),
+            )
+        })
+        .collect())
+}
+
+/// The columns of a CSV export, looked up by name.
+struct Columns(BTreeMap<String, usize>);
+
+impl Columns {
+    fn new(headers: &csv::StringRecord) -> Self {
+        Self(
+            headers
+                .iter()
+                .enumerate()
+                .map(|(index, header)| (header.trim().to_lowercase(), index))
+                .collect(),
+        )
+    }
+
+    /// The index of the column `name`, case insensitive.
+    fn index(&self, name: &'static str) -> Result<usize, Error> {
+        self.0
+            .get(&name.to_lowercase())
+            .copied()
+            .ok_or(Error::MissingColumn(name))
+    }
+}
+
+/// Reads the rows of a CSV export with the usual columns of a detailed report.
+///
+/// Dates and times are tried against every given format, in order.
+fn csv_entries(
+    reader: impl Read,
+    date_formats: &[&str],
+    time_formats: &[&str],
+) -> Result<Vec<Entry>, Error> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+    let columns = Columns::new(reader.headers()?);
+    let description = columns.index("Description")?;
+    let project = columns.index("Project")?;
+    let tags = columns.index("Tags").ok();
+    let start_date = columns.index("Start date")?;
+    let start_time = columns.index("Start time")?;
+    let end_date = columns.index("End date")?;
+    let end_time = columns.index("End time")?;
+
+    let mut entries = Vec::new();
+    for (index, row) in reader.records().enumerate() {
+        let row = row?;
+        // the header is the first line
+        let line = index + 2;
+        let field = |column: usize| row.get(column).unwrap_or_default().trim();
+        if field(end_date).is_empty() || field(end_time).is_empty() {
+            continue;
+        }
+
+        let instant = |date: usize, time: usize| {
+            local(field(date), field(time), date_formats, time_formats).ok_or_else(|| {
+                Error::InvalidDate {
+                    line: Some(line),
+                    value: format!("{} {}", field(date), field(time)),
+                }
+            })
+        };
+        let (start, end) = (
+            instant(start_date, start_time)?,
+            instant(end_date, end_time)?,
+        );
+        if end <= start {
+            continue;
+        }
+
+        entries.push(Entry {
+            description: field(description).to_string(),
+            project: Some(field(project))
+                .filter(|project| !project.is_empty())
+                .map(str::to_string),
+            tags: tags
+                .map(|tags| {
+                    field(tags)
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|tag| !tag.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            start,
+            end,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Reads a local date and time, trying every format in order.
+fn local(
+    date: &str,
+    time: &str,
+    date_formats: &[&str],
+    time_formats: &[&str],
+) -> Option<DateTime<Local>> {
+    let date = date_formats
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(date, format).ok())?;
+    let time = time_formats
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(time, format).ok())?;
+
+    Local
+        .from_local_datetime(&NaiveDateTime::new(date, time))
+        .earliest()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use super::*;
+
+    fn entry(description: &str, project: Option<&str>, minutes: i64) -> Entry {
+        let start =
+            Local.with_ymd_and_hms(2025, 1, 15, 9, 0, 0).unwrap() + TimeDelta::minutes(minutes);
+        Entry {
+            description: description.into(),
+            project: project.map(str::to_string),
+            tags: vec![],
+            start,
+            end: start + TimeDelta::minutes(25),
+        }
+    }
+
+    fn plan(entries: &[Entry], mapping: &Mapping, tasks: &[Task], log: &SessionLog) -> Plan {
+        Plan::new(entries, mapping, tasks, log, Durations::default()).unwrap()
+    }
+
+    #[test]
+    fn entries_go_to_the_task_named_after_them() {
+        let log = SessionLog::open_in_memory().unwrap();
+        let landing = Task::new("Landing page".into(), 1, Durations::default());
+        let entries = [
+            entry("landing page", None, 0),
+            entry("", Some("Website"), 30),
+            entry("", None, 60),
+        ];
+
+        let plan = plan(
+            &entries,
+            &Mapping::default(),
+            std::slice::from_ref(&landing),
+            &log,
+        );
+
+        let names: Vec<_> = plan.tasks.iter().map(|task| task.name.as_str()).collect();
+        assert_eq!(names, ["Website", FALLBACK_TASK]);
+        assert_eq!(plan.intervals[0].task_id, landing.id);
+        assert_eq!(plan.unmapped, BTreeSet::from(["Website".to_string()]));
+    }
+
+    #[test]
+    fn the_mapping_moves_or_skips_entries() {
+        let log = SessionLog::open_in_memory().unwrap();
+        let mut tagged = entry("Call", None, 30);
+        tagged.tags = vec!["meeting".into()];
+        let entries = [entry("Landing page", Some("Website"), 0), tagged];
+        let mapping = Mapping {
+            rules: BTreeMap::from([
+                ("Website".to_string(), Rule::Task("Site".into())),
+                ("#meeting".to_string(), Rule::Skip),
+            ]),
+        };
+
+        let plan = plan(&entries, &mapping, &[], &log);
+
+        assert_eq!(plan.intervals.len(), 1);
+        assert_eq!(plan.intervals[0].task, "Site");
+        assert_eq!(plan.skipped, 1);
+        assert!(plan.unmapped.is_empty());
+    }
+
+    #[test]
+    fn entries_repeated_in_the_export_are_duplicates() {
+        let log = SessionLog::open_in_memory().unwrap();
+        let entries = [entry("Review", None, 0), entry("Review", None, 0)];
+
+        let plan = plan(&entries, &Mapping::default(), &[], &log);
+
+        assert_eq!(plan.intervals.len(), 1);
+        assert_eq!(plan.duplicates, 1);
+    }
+
+    #[test]
+    fn importing_twice_finds_only_duplicates() {
+        let mut log = SessionLog::open_in_memory().unwrap();
+        let mut data = Data::default();
+        let entries = [entry("Review", None, 0), entry("Write", None, 30)];
+        plan(&entries, &Mapping::default(), &[], &log)
+            .apply(&mut data, &mut log)
+            .unwrap();
+
+        let again = plan(&entries, &Mapping::default(), &data.tasks, &log);
+
+        assert_eq!(data.tasks.len(), 2);
+        assert_eq!(
+            data.tasks[0].elapsed,
+            std::time::Duration::from_secs(25 * 60)
+        );
+        assert!(again.intervals.is_empty());
+        // the tasks are not created again either
+        assert!(again.tasks.is_empty());
+        assert_eq!(again.duplicates, 2);
+    }
+}
@@ -0,0 +1,244 @@
+//! The JSON backup of Super Productivity.
+//!
+//! Super Productivity only keeps the time spent on each task per day, not when
+//! it was spent. Each day, the tasks are laid one after the other from
+//! [`DAY_START`], in the order of the backup, so importing the same backup twice
+//! gives the same intervals.
+//!
+//! ```json
+//! {
+//!   "task": {
+//!     "ids": ["t1"],
+//!     "entities": {
+//!       "t1": {
+//!         "title": "Landing page",
+//!         "projectId": "p1",
+//!         "tagIds": ["g1"],
+//!         "timeSpentOnDay": { "2025-01-15": 1500000 }
+//!       }
+//!     }
+//!   },
+//!   "project": { "entities": { "p1": { "title": "Website" } } },
+//!   "tag": { "entities": { "g1": { "title": "design" } } }
+//! }
+//! ```
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Read,
+};
+
+use chrono::{Duration, Local, NaiveDate, NaiveTime, TimeZone};
+use serde::Deserialize;
+
+use super::{Entry, Error};
+
+/// When the first task of a day is laid.
+const DAY_START: NaiveTime = match NaiveTime::from_hms_opt(9, 0, 0) {
+    Some(time) => time,
+    None => unreachable!(),
+};
+/// The tag Super Productivity gives to the tasks planned for today.
+const TODAY_TAG: &str = "TODAY";
+
+/// A backup, as saved by the app or wrapped in a `data` field by the sync.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Backup {
+    Wrapped { data: State },
+    Plain(State),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct State {
+    task: Entities<SpTask>,
+    #[serde(default)]
+    task_archive: Option<Entities<SpTask>>,
+    #[serde(default)]
+    project: Option<Entities<Titled>>,
+    #[serde(default)]
+    tag: Option<Entities<Titled>>,
+}
+
+#[derive(Deserialize)]
+struct Entities<T> {
+    /// The order of the entities, missing from some older backups.
+    #[serde(default)]
+    ids: Vec<String>,
+    entities: HashMap<String, T>,
+}
+
+impl<T> Entities<T> {
+    /// The entities, in the order of `ids` then by id for those left out.
+    fn ordered(&self) -> impl Iterator<Item = &T> {
+        let listed = self.ids.iter().filter_map(|id| self.entities.get(id));
+        let unlisted: BTreeMap<_, _> = self
+            .entities
+            .iter()
+            .filter(|(id, _)| !self.ids.contains(id))
+            .collect();
+
+        listed.chain(unlisted.into_values())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SpTask {
+    title: String,
+    #[serde(default)]
+    project_id: Option<String>,
+    #[serde(default)]
+    tag_ids: Vec<String>,
+    /// The milliseconds spent on the task, by `YYYY-MM-DD` day.
+    #[serde(default)]
+    time_spent_on_day: BTreeMap<String, u64>,
+}
+
+#[derive(Deserialize)]
+struct Titled {
+    title: String,
+}
+
+pub(super) fn parse(reader: impl Read) -> Result<Vec<Entry>, Error> {
+    let state = match serde_json::from_reader(reader)? {
+        Backup::Wrapped { data } => data,
+        Backup::Plain(state) => state,
+    };
+    let title = |entities: &Option<Entities<Titled>>, id: &str| {
+        entities
+            .as_ref()
+            .and_then(|entities| entities.entities.get(id))
+            .map(|entity| entity.title.clone())
+    };
+
+    // where the next task of each day is laid
+    let mut cursors = BTreeMap::new();
+    let mut entries = Vec::new();
+    let tasks = state
+        .task
+        .ordered()
+        .chain(state.task_archive.iter().flat_map(Entities::ordered));
+    for task in tasks {
+        let project = task
+            .project_id
+            .as_deref()
+            .and_then(|id| title(&state.project, id));
+        let tags: Vec<_> = task
+            .tag_ids
+            .iter()
+            .filter(|id| *id != TODAY_TAG)
+            .filter_map(|id| title(&state.tag, id))
+            .collect();
+
+        for (day, &millis) in &task.time_spent_on_day {
+            if millis < 1000 {
+                continue;
+            }
+            let date =
+                NaiveDate::parse_from_str(day, "%Y-%m-%d").map_err(|_| Error::InvalidDate {
+                    line: None,
+                    value: day.clone(),
+                })?;
+            let Some(day_start) = Local
+                .from_local_datetime(&date.and_time(DAY_START))
+                .earliest()
+            else {
+                continue;
+            };
+
+            let start = *cursors.entry(date).or_insert(day_start);
+            let end = start + Duration::seconds((millis / 1000) as i64);
+            cursors.insert(date, end);
+
+            entries.push(Entry {
+                description: task.title.trim().to_string(),
+                project: project.clone(),
+                tags: tags.clone(),
+                start,
+                end,
+            });
+        }
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hour: u32, minute: u32) -> chrono::DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2025, 1, 15, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn the_tasks_of_a_day_are_laid_one_after_the_other() {
+        let backup = r#"{
+            "task": {
+                "ids": ["t2", "t1"],
+                "entities": {
+                    "t1": { "title": "Review", "timeSpentOnDay": { "2025-01-15": 600000 } },
+                    "t2": {
+                        "title": " Landing page ",
+                        "projectId": "p1",
+                        "tagIds": ["TODAY", "g1"],
+                        "timeSpentOnDay": { "2025-01-15": 1500000 }
+                    }
+                }
+            },
+            "project": { "entities": { "p1": { "title": "Website" } } },
+            "tag": { "entities": { "g1": { "title": "design" } } }
+        }"#;
+
+        let entries = parse(backup.as_bytes()).unwrap();
+
+        assert_eq!(
+            entries,
+            [
+                Entry {
+                    description: "Landing page".into(),
+                    project: Some("Website".into()),
+                    tags: vec!["design".into()],
+                    start: at(9, 0),
+                    end: at(9, 25),
+                },
+                Entry {
+                    description: "Review".into(),
+                    project: None,
+                    tags: vec![],
+                    start: at(9, 25),
+                    end: at(9, 35),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn synced_backups_are_unwrapped() {
+        let backup = r#"{ "data": { "task": { "entities": {
+            "t1": { "title": "Review", "timeSpentOnDay": { "2025-01-15": 600000, "2025-01-16": 10 } }
+        } } } }"#;
+
+        let entries = parse(backup.as_bytes()).unwrap();
+
+        // less than a second is not worth an interval
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].end, at(9, 10));
+    }
+
+    #[test]
+    fn a_bad_day_is_reported() {
+        let backup = r#"{ "task": { "entities": {
+            "t1": { "title": "Review", "timeSpentOnDay": { "15/01/2025": 600000 } }
+        } } }"#;
+
+        assert!(matches!(
+            parse(backup.as_bytes()),
+            Err(Error::InvalidDate { line: None, .. })
+        ));
+    }
+}
@@ -0,0 +1,66 @@
+//! The CSV of a Toggl Track detailed report.
+//!
+//! ```text
+//! User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags
+//! Ana,ana@example.com,,Website,,Landing page,No,2025-01-15,09:00:00,2025-01-15,09:25:00,00:25:00,"design, web"
+//! ```
+
+use std::io::Read;
+
+use super::{csv_entries, Entry, Error};
+
+pub(super) fn parse(reader: impl Read) -> Result<Vec<Entry>, Error> {
+    csv_entries(reader, &["%Y-%m-%d"], &["%H:%M:%S", "%H:%M"])
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+
+    use super::*;
+
+    const HEADER: &str = "User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags\n";
+
+    #[test]
+    fn rows_become_entries() {
+        let csv = format!(
+            "{HEADER}Ana,ana@example.com,,Website,,Landing page,No,2025-01-15,09:00:00,2025-01-15,09:25:00,00:25:00,\"design, web\"\n"
+        );
+
+        let entries = parse(csv.as_bytes()).unwrap();
+
+        assert_eq!(
+            entries,
+            [Entry {
+                description: "Landing page".into(),
+                project: Some("Website".into()),
+                tags: vec!["design".into(), "web".into()],
+                start: Local.with_ymd_and_hms(2025, 1, 15, 9, 0, 0).unwrap(),
+                end: Local.with_ymd_and_hms(2025, 1, 15, 9, 25, 0).unwrap(),
+            }]
+        );
+    }
+
+    #[test]
+    fn running_and_empty_entries_are_left_out() {
+        let csv = format!(
+            "{HEADER}Ana,,,,,Running,No,2025-01-15,09:00:00,,,,\n\
+             Ana,,,,,Empty,No,2025-01-15,09:00:00,2025-01-15,09:00:00,00:00:00,\n"
+        );
+
+        assert!(parse(csv.as_bytes()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn a_bad_date_tells_its_line() {
+        let csv = format!(
+            "{HEADER}Ana,,,,,Fine,No,2025-01-15,09:00,2025-01-15,09:25,,\n\
+             Ana,,,,,Broken,No,15 Jan,09:00,2025-01-15,09:25,,\n"
+        );
+
+        assert!(matches!(
+            parse(csv.as_bytes()),
+            Err(Error::InvalidDate { line: Some(3), .. })
+        ));
+    }
+}
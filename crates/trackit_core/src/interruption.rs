@@ -0,0 +1,201 @@
+//! Interruptions noted during focus blocks.
+//!
+//! The Pomodoro Technique tells apart the internal interruptions, when one's own
+//! mind wanders off, from the external ones, when someone or something else
+//! calls. They're noted without stopping the work block, unlike the reason an
+//! [`Interval`](crate::Interval) was cut short.
+
+use std::{fmt, ops::AddAssign, str::FromStr};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::TaskId;
+
+/// Where an interruption came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    /// One's own thoughts, an urge to check something.
+    Internal,
+    /// A colleague, a call, a notification.
+    External,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 2] = [Kind::Internal, Kind::External];
+
+    /// The name of the kind in the log and on the wire.
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Kind::Internal => "internal",
+            Kind::External => "external",
+        }
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Kind::Internal => "Internal",
+            Kind::External => "External",
+        })
+    }
+}
+
+impl FromStr for Kind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "internal" | "i" => Ok(Kind::Internal),
+            "external" | "e" => Ok(Kind::External),
+            _ => Err(format!("`{s}` is neither internal nor external")),
+        }
+    }
+}
+
+/// An interruption, noted against the session of a task.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Interruption {
+    /// The row id, `0` until the interruption is recorded.
+    pub id: i64,
+    pub task_id: TaskId,
+    /// The name the task had when the interruption was noted.
+    pub task: String,
+    pub kind: Kind,
+    pub note: Option<String>,
+    pub at: DateTime<Local>,
+}
+
+impl Interruption {
+    /// Creates an interruption, not recorded yet. A blank `note` is dropped.
+    pub fn new(
+        task_id: TaskId,
+        task: impl Into<String>,
+        kind: Kind,
+        note: Option<String>,
+        at: DateTime<Local>,
+    ) -> Self {
+        Self {
+            id: 0,
+            task_id,
+            task: task.into(),
+            kind,
+            note: note
+                .map(|note| note.trim().to_string())
+                .filter(|note| !note.is_empty()),
+            at,
+        }
+    }
+}
+
+/// How many interruptions of each kind were noted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Counts {
+    pub internal: u32,
+    pub external: u32,
+}
+
+impl Counts {
+    /// Counts one more interruption of the given `kind`.
+    pub fn add(&mut self, kind: Kind) {
+        match kind {
+            Kind::Internal => self.internal += 1,
+            Kind::External => self.external += 1,
+        }
+    }
+
+    pub fn get(&self, kind: Kind) -> u32 {
+        match kind {
+            Kind::Internal => self.internal,
+            Kind::External => self.external,
+        }
+    }
+
+    pub fn total(&self) -> u32 {
+        self.internal + self.external
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+}
+
+impl AddAssign for Counts {
+    fn add_assign(&mut self, other: Self) {
+        self.internal += other.internal;
+        self.external += other.external;
+    }
+}
+
+impl fmt::Display for Counts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} internal · {} external", self.internal, self.external)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_kind_is_read_in_full_or_by_its_initial() {
+        assert_eq!("internal".parse(), Ok(Kind::Internal));
+        assert_eq!(" External ".parse(), Ok(Kind::External));
+        assert_eq!("I".parse(), Ok(Kind::Internal));
+        assert_eq!("e".parse(), Ok(Kind::External));
+        for kind in Kind::ALL {
+            assert_eq!(kind.as_str().parse(), Ok(kind));
+            assert_eq!(kind.to_string().parse(), Ok(kind));
+        }
+    }
+
+    #[test]
+    fn an_unknown_kind_is_refused() {
+        assert_eq!(
+            "phone".parse::<Kind>(),
+            Err("`phone` is neither internal nor external".to_string())
+        );
+        assert!("".parse::<Kind>().is_err());
+        assert!("int".parse::<Kind>().is_err());
+    }
+
+    #[test]
+    fn interruptions_are_counted_by_kind() {
+        let mut counts = Counts::default();
+        assert!(counts.is_empty());
+
+        counts.add(Kind::Internal);
+        counts.add(Kind::External);
+        counts.add(Kind::Internal);
+        assert_eq!(counts.get(Kind::Internal), 2);
+        assert_eq!(counts.get(Kind::External), 1);
+        assert_eq!(counts.total(), 3);
+
+        counts += Counts {
+            internal: 1,
+            external: 4,
+        };
+        assert_eq!((counts.internal, counts.external), (3, 5));
+        assert_eq!(counts.to_string(), "3 internal · 5 external");
+    }
+
+    #[test]
+    fn a_blank_note_is_dropped() {
+        let at = Local::now();
+        let note = |note: &str| {
+            Interruption::new(
+                TaskId::new(),
+                "Write",
+                Kind::External,
+                Some(note.into()),
+                at,
+            )
+            .note
+        };
+
+        assert_eq!(note("  call  "), Some("call".to_string()));
+        assert_eq!(note("   "), None);
+    }
+}
@@ -0,0 +1,476 @@
+//! The protocol of the control socket exposed by the GUI.
+//!
+//! Every line written to the socket is a JSON-RPC 2.0 [`Request`], answered by
+//! a line holding its [`Response`]:
+//!
+//! ```text
+//! → {"jsonrpc": "2.0", "id": 1, "method": "start_task", "params": {"task": "docs"}}
+//! ← {"jsonrpc": "2.0", "id": 1, "result": {"task": "…", "name": "Write docs", …}}
+//! ```
+//!
+//! Every method answers with the [`Status`] of the session after the call, or
+//! `null` when there's no session. A request without an `id` is a
+//! notification: it's carried out, but never answered.
+
+use std::{fmt, io, path::PathBuf};
+#[cfg(unix)]
+use std::{
+    io::{BufRead, BufReader, Write},
+    os::unix::net::UnixStream,
+    path::Path,
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    interruption::{Counts, Kind},
+    storage, ActiveSession, Phase, Task, TaskId,
+};
+
+/// The name of the socket, inside the [`socket_dir`].
+pub const SOCKET_FILE: &str = "trackit.sock";
+/// The only protocol version spoken.
+pub const JSONRPC: &str = "2.0";
+
+/// The directory holding the socket, in the runtime directory, or the data
+/// directory on platforms without one.
+///
+/// Only its owner may enter it, so the socket is never reachable by others,
+/// even before its own permissions are set.
+pub fn socket_dir() -> Result<PathBuf, storage::Error> {
+    match dirs::runtime_dir() {
+        Some(dir) => Ok(dir.join("trackit")),
+        None => Ok(storage::data_dir()?.join("run")),
+    }
+}
+
+/// Where the GUI listens.
+pub fn socket_path() -> Result<PathBuf, storage::Error> {
+    Ok(socket_dir()?.join(SOCKET_FILE))
+}
+
+/// A method of the protocol, with its parameters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Call {
+    /// Starts a session for a task, given its id or name.
+    StartTask {
+        task: String,
+    },
+    Pause,
+    Resume,
+    /// Jumps to the next phase.
+    SkipPhase,
+    Stop,
+    Status,
+    /// Notes an interruption of the running work block.
+    Interrupt {
+        kind: Kind,
+        note: Option<String>,
+    },
+}
+
+impl Call {
+    /// The name of the method on the wire.
+    pub fn method(&self) -> &'static str {
+        match self {
+            Call::StartTask { .. } => "start_task",
+            Call::Pause => "pause",
+            Call::Resume => "resume",
+            Call::SkipPhase => "skip_phase",
+            Call::Stop => "stop",
+            Call::Status => "status",
+            Call::Interrupt { .. } => "interrupt",
+        }
+    }
+
+    fn params(&self) -> Value {
+        match self {
+            Call::StartTask { task } => json!({ "task": task }),
+            Call::Interrupt { kind, note } => json!({ "kind": kind, "note": note }),
+            _ => Value::Null,
+        }
+    }
+
+    fn from_parts(method: &str, params: Value) -> Result<Self, RpcError> {
+        #[derive(Deserialize)]
+        struct StartTask {
+            task: String,
+        }
+        #[derive(Deserialize)]
+        struct Interrupt {
+            kind: Kind,
+            #[serde(default)]
+            note: Option<String>,
+        }
+        let invalid =
+            |err: serde_json::Error| RpcError::new(RpcError::INVALID_PARAMS, err.to_string());
+
+        match method {
+            "start_task" => {
+                let StartTask { task } = serde_json::from_value(params).map_err(invalid)?;
+                Ok(Call::StartTask { task })
+            }
+            "interrupt" => {
+                let Interrupt { kind, note } = serde_json::from_value(params).map_err(invalid)?;
+                Ok(Call::Interrupt { kind, note })
+            }
+            "pause" => Ok(Call::Pause),
+            "resume" => Ok(Call::Resume),
+            "skip_phase" => Ok(Call::SkipPhase),
+            "stop" => Ok(Call::Stop),
+            "status" => Ok(Call::Status),
+            method => Err(RpcError::new(
+                RpcError::METHOD_NOT_FOUND,
+                format!("unknown method `{method}`"),
+            )),
+        }
+    }
+}
+
+/// The shape of a request on the wire.
+#[derive(Deserialize, Serialize)]
+struct RawRequest {
+    jsonrpc: String,
+    /// `None` when the member is missing, `Some(Value::Null)` when it's `null`.
+    #[serde(
+        default,
+        deserialize_with = "present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    id: Option<Value>,
+    method: String,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    params: Value,
+}
+
+fn present<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Value>, D::Error> {
+    Value::deserialize(deserializer).map(Some)
+}
+
+/// A call and the id its response is matched with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    /// `None` for a notification, which is never answered.
+    pub id: Option<Value>,
+    pub call: Call,
+}
+
+impl Request {
+    pub fn new(id: impl Into<Value>, call: Call) -> Self {
+        Self {
+            id: Some(id.into()),
+            call,
+        }
+    }
+
+    /// A call that expects no answer.
+    pub fn notification(call: Call) -> Self {
+        Self { id: None, call }
+    }
+
+    /// Parses a line read from the socket.
+    ///
+    /// On failure, the error response to send back is returned instead, or
+    /// `None` if the request was a notification.
+    pub fn parse(line: &str) -> Result<Self, Option<Response>> {
+        let raw: RawRequest = serde_json::from_str(line).map_err(|err| {
+            let code = if err.is_data() {
+                RpcError::INVALID_REQUEST
+            } else {
+                RpcError::PARSE_ERROR
+            };
+            // the id is unknown, so is whether it was a notification
+            Some(Response::error(
+                Value::Null,
+                RpcError::new(code, err.to_string()),
+            ))
+        })?;
+
+        let call = if raw.jsonrpc != JSONRPC {
+            Err(RpcError::new(
+                RpcError::INVALID_REQUEST,
+                "unsupported jsonrpc version",
+            ))
+        } else {
+            Call::from_parts(&raw.method, raw.params)
+        };
+        match call {
+            Ok(call) => Ok(Self { id: raw.id, call }),
+            Err(err) => Err(raw.id.map(|id| Response::error(id, err))),
+        }
+    }
+
+    /// The request as a single line, without the line break.
+    pub fn to_line(&self) -> String {
+        let raw = RawRequest {
+            jsonrpc: JSONRPC.to_string(),
+            id: self.id.clone(),
+            method: self.call.method().to_string(),
+            params: self.call.params(),
+        };
+        serde_json::to_string(&raw).expect("requests are always serializable")
+    }
+}
+
+/// The answer to a [`Request`], either a result or an error.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Response {
+    pub jsonrpc: String,
+    pub id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+impl Response {
+    pub fn success(id: Value, result: Value) -> Self {
+        Self {
+            jsonrpc: JSONRPC.to_string(),
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    pub fn error(id: Value, error: RpcError) -> Self {
+        Self {
+            jsonrpc: JSONRPC.to_string(),
+            id,
+            result: None,
+            error: Some(error),
+        }
+    }
+
+    /// The answer to a call, which is the status of the session on success.
+    pub fn answer(id: Value, result: Result<Option<Status>, RpcError>) -> Self {
+        match result {
+            Ok(status) => Self::success(
+                id,
+                serde_json::to_value(status).expect("statuses are always serializable"),
+            ),
+            Err(err) => Self::error(id, err),
+        }
+    }
+
+    /// The response as a single line, without the line break.
+    pub fn to_line(&self) -> String {
+        serde_json::to_string(self).expect("responses are always serializable")
+    }
+
+    pub fn into_result(self) -> Result<Value, RpcError> {
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok(self.result.unwrap_or(Value::Null)),
+        }
+    }
+}
+
+/// An error of the protocol, as described by JSON-RPC.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub const PARSE_ERROR: i64 = -32700;
+    pub const INVALID_REQUEST: i64 = -32600;
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    pub const INVALID_PARAMS: i64 = -32602;
+    pub const INTERNAL_ERROR: i64 = -32603;
+    /// The call doesn't apply to the session as it is, e.g. resuming a running one.
+    pub const INVALID_STATE: i64 = -32000;
+    /// The task of a call doesn't exist.
+    pub const UNKNOWN_TASK: i64 = -32001;
+
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.message, self.code)
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+/// What every method answers about the session.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Status {
+    pub task: TaskId,
+    pub name: String,
+    pub phase: Phase,
+    pub paused: bool,
+    pub finished: bool,
+    pub remaining_secs: u64,
+    pub completed_cycles: u8,
+    pub cycles: u8,
+    /// The interruptions noted during the session.
+    #[serde(default)]
+    pub interruptions: Counts,
+}
+
+impl Status {
+    /// The status of the `active` session of `task` at `now`.
+    pub fn new(active: &ActiveSession, task: &Task, now: DateTime<Local>) -> Self {
+        Self {
+            task: task.id,
+            name: task.name.clone(),
+            phase: active.session.phase(),
+            paused: active.session.is_paused(),
+            finished: active.session.is_finished(),
+            remaining_secs: active.session.remaining(now).as_secs(),
+            completed_cycles: active.session.completed_cycles(),
+            cycles: active.session.cycles(),
+            interruptions: active.interruptions,
+        }
+    }
+}
+
+/// The error type of the [`Client`].
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// The GUI answered with an error.
+    Rpc(RpcError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "i/o error: {err}"),
+            Error::Json(err) => write!(f, "invalid response: {err}"),
+            Error::Rpc(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Json(err) => Some(err),
+            Error::Rpc(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+/// A connection to the control socket of a running GUI.
+#[cfg(unix)]
+pub struct Client {
+    reader: BufReader<UnixStream>,
+    next_id: u64,
+}
+
+#[cfg(unix)]
+impl Client {
+    /// Connects to the socket at `path`, fails if no GUI is listening.
+    pub fn connect(path: &Path) -> Result<Self, Error> {
+        Ok(Self {
+            reader: BufReader::new(UnixStream::connect(path)?),
+            next_id: 1,
+        })
+    }
+
+    /// Sends the `call` and waits for the status it answers with.
+    pub fn call(&mut self, call: Call) -> Result<Option<Status>, Error> {
+        let request = Request::new(self.next_id, call);
+        self.next_id += 1;
+
+        let stream = self.reader.get_mut();
+        stream.write_all(request.to_line().as_bytes())?;
+        stream.write_all(b"\n")?;
+
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+
+        let response: Response = serde_json::from_str(&line)?;
+        let result = response.into_result().map_err(Error::Rpc)?;
+        Ok(serde_json::from_value(result)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_go_through_the_wire() {
+        let request = Request::new(
+            7,
+            Call::Interrupt {
+                kind: Kind::External,
+                note: Some("phone".into()),
+            },
+        );
+
+        assert_eq!(Request::parse(&request.to_line()), Ok(request));
+    }
+
+    #[test]
+    fn a_request_without_id_is_a_notification() {
+        let request = Request::parse(r#"{"jsonrpc": "2.0", "method": "pause"}"#).unwrap();
+
+        assert_eq!(request, Request::notification(Call::Pause));
+    }
+
+    #[test]
+    fn a_null_id_is_still_answered() {
+        let line = r#"{"jsonrpc": "2.0", "id": null, "method": "pause"}"#;
+
+        assert_eq!(Request::parse(line).unwrap().id, Some(Value::Null));
+    }
+
+    #[test]
+    fn a_failed_notification_is_not_answered() {
+        let line = r#"{"jsonrpc": "2.0", "method": "rewind"}"#;
+
+        assert_eq!(Request::parse(line), Err(None));
+    }
+
+    #[test]
+    fn a_failed_request_is_answered_with_its_error() {
+        let line = r#"{"jsonrpc": "2.0", "id": 3, "method": "rewind"}"#;
+
+        let response = Request::parse(line).unwrap_err().unwrap();
+        assert_eq!(response.id, json!(3));
+        assert_eq!(
+            response.error.map(|err| err.code),
+            Some(RpcError::METHOD_NOT_FOUND)
+        );
+    }
+
+    #[test]
+    fn unreadable_lines_are_answered_without_id() {
+        let response = Request::parse("{").unwrap_err().unwrap();
+
+        assert_eq!(response.id, Value::Null);
+        assert_eq!(
+            response.error.map(|err| err.code),
+            Some(RpcError::PARSE_ERROR)
+        );
+    }
+}
@@ -3,26 +3,406 @@ pub mod chrono {
     pub use chrono::*;
 }
 
-use std::time::Duration;
+pub mod active;
+pub mod export;
+pub mod goal;
+pub mod history;
+pub mod idle;
+pub mod import;
+pub mod interruption;
+pub mod ipc;
+pub mod project;
+pub mod settings;
+pub mod stats;
+pub mod storage;
+pub mod timeline;
+pub mod timer;
+
+use std::{fmt, str::FromStr, time::Duration};
 
 use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub use active::{ActiveSession, Outcome, Overrun};
+pub use history::{Interval, SessionLog};
+pub use interruption::Interruption;
+pub use project::{Project, ProjectId};
+pub use settings::Settings;
+pub use storage::{Data, Storage};
+pub use timer::{Durations, Pause, Phase, Session, Transition};
+
+/// The stable identifier of a [`Task`].
+///
+/// Unlike its position in the list, it never changes once the task is created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct TaskId(Uuid);
+
+impl TaskId {
+    /// Generates a new random id.
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+
+    /// The placeholder id of the log rows recorded for a task named `name`
+    /// before ids existed, until they're linked to the task, see
+    /// [`SessionLog::link_migrated`].
+    pub(crate) fn from_legacy_name(name: &str) -> Self {
+        Self(Uuid::new_v5(&Uuid::NAMESPACE_OID, name.as_bytes()))
+    }
+}
+
+impl Default for TaskId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for TaskId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for TaskId {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Uuid::parse_str(s).map(Self)
+    }
+}
+
+/// Where a task is in its life.
+///
+/// `Todo → InProgress → Completed → Archived`. Starting a completed task puts
+/// it back in progress, and restoring an archived one makes it completed again.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    #[default]
+    Todo,
+    InProgress,
+    Completed,
+    Archived,
+}
+
+impl fmt::Display for TaskState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TaskState::Todo => "To do",
+            TaskState::InProgress => "In progress",
+            TaskState::Completed => "Completed",
+            TaskState::Archived => "Archived",
+        })
+    }
+}
+
+/// A step of a task, ticked once done.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Subtask {
+    pub name: String,
+    #[serde(default)]
+    pub done: bool,
+}
+
+impl Subtask {
+    pub fn new(name: String) -> Self {
+        Self { name, done: false }
+    }
+}
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
 pub struct Task {
+    pub id: TaskId,
     pub name: String,
     pub cycles: u8,
     pub started_at: Option<DateTime<Local>>,
     pub elapsed: Duration,
+    #[serde(default)]
+    pub durations: Durations,
+    /// The project the task belongs to, if any.
+    #[serde(default)]
+    pub project: Option<ProjectId>,
+    /// Free-form labels, without a leading `#`.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub state: TaskState,
+    /// When the task was last completed.
+    #[serde(default)]
+    pub completed_at: Option<DateTime<Local>>,
+    /// What to keep in mind while working on the task.
+    #[serde(default)]
+    pub notes: String,
+    /// The steps of the task, in order.
+    #[serde(default)]
+    pub subtasks: Vec<Subtask>,
 }
 
 impl Task {
-    pub fn new(name: String, cycles: u8) -> Task {
+    pub fn new(name: String, cycles: u8, durations: Durations) -> Task {
         Self {
+            id: TaskId::new(),
             name,
             cycles,
             started_at: None,
             elapsed: Duration::ZERO,
+            durations,
+            project: None,
+            tags: Vec::new(),
+            state: TaskState::Todo,
+            completed_at: None,
+            notes: String::new(),
+            subtasks: Vec::new(),
+        }
+    }
+
+    /// Marks the task as started at `now`, in progress even if it was done.
+    pub fn start(&mut self, now: DateTime<Local>) {
+        self.started_at = Some(now);
+        self.state = TaskState::InProgress;
+    }
+
+    /// Marks the task as done at `now`.
+    pub fn complete(&mut self, now: DateTime<Local>) {
+        self.state = TaskState::Completed;
+        self.completed_at = Some(now);
+    }
+
+    /// Moves the task to the archive.
+    pub fn archive(&mut self) {
+        self.state = TaskState::Archived;
+    }
+
+    /// Takes the task out of the archive, as completed.
+    pub fn restore(&mut self) {
+        if self.state == TaskState::Archived {
+            self.state = TaskState::Completed;
         }
     }
+
+    pub fn is_archived(&self) -> bool {
+        self.state == TaskState::Archived
+    }
+
+    /// The work blocks done, judging by the time spent on the task.
+    pub fn completed_cycles(&self) -> u8 {
+        let work = self.durations.work.as_secs().max(1);
+        (self.elapsed.as_secs() / work).min(u8::MAX as u64) as u8
+    }
+
+    /// The work blocks left before the task is done.
+    pub fn remaining_cycles(&self) -> u8 {
+        self.cycles.saturating_sub(self.completed_cycles())
+    }
+
+    /// Whether the task is done, archived or not.
+    pub fn is_completed(&self) -> bool {
+        matches!(self.state, TaskState::Completed | TaskState::Archived)
+    }
+
+    /// How many subtasks are ticked.
+    pub fn subtasks_done(&self) -> usize {
+        self.subtasks.iter().filter(|subtask| subtask.done).count()
+    }
+
+    /// Whether the task carries the `tag`, case insensitive.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|own| own.eq_ignore_ascii_case(tag))
+    }
+}
+
+/// Formats a duration as `1h 05m` or `05m`.
+pub fn format_duration(duration: Duration) -> String {
+    let total_secs = duration.as_secs();
+    let hours = total_secs / 3600;
+    let minutes = (total_secs % 3600) / 60;
+
+    if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else {
+        format!("{:02}m", minutes)
+    }
+}
+
+/// Formats a duration as a `mm:ss` clock.
+pub fn format_clock(duration: Duration) -> String {
+    let total_secs = duration.as_secs();
+    format!("{:02}:{:02}", total_secs / 60, total_secs % 60)
+}
+
+/// Why a task name was refused by [`check_name`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameError {
+    Empty,
+    /// Another task has the name, case insensitive.
+    Taken(String),
+}
+
+impl fmt::Display for NameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameError::Empty => f.write_str("the task needs a name"),
+            NameError::Taken(name) => write!(f, "a task named `{name}` already exists"),
+        }
+    }
+}
+
+impl std::error::Error for NameError {}
+
+/// Checks the `name` of a new task, or of the task with the id `renamed`, and
+/// returns it trimmed.
+///
+/// Tasks are found by name, so no two of the `tasks` may share one.
+pub fn check_name(
+    tasks: &[Task],
+    name: &str,
+    renamed: Option<TaskId>,
+) -> Result<String, NameError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(NameError::Empty);
+    }
+    if tasks
+        .iter()
+        .any(|task| Some(task.id) != renamed && task.name.eq_ignore_ascii_case(name))
+    {
+        return Err(NameError::Taken(name.to_string()));
+    }
+
+    Ok(name.to_string())
+}
+
+/// Why a task couldn't be found by [`find_task`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FindError {
+    NotFound(String),
+    Ambiguous(String),
+}
+
+impl fmt::Display for FindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FindError::NotFound(query) => write!(f, "no task matches `{query}`"),
+            FindError::Ambiguous(query) => write!(f, "`{query}` matches more than one task"),
+        }
+    }
+}
+
+impl std::error::Error for FindError {}
+
+/// Finds a task by id, exact name or unambiguous name prefix (case insensitive).
+pub fn find_task<'a>(tasks: &'a [Task], query: &str) -> Result<&'a Task, FindError> {
+    if let Ok(id) = query.parse::<TaskId>() {
+        if let Some(task) = tasks.iter().find(|task| task.id == id) {
+            return Ok(task);
+        }
+    }
+
+    let lowercase = query.to_lowercase();
+    let mut exact = tasks
+        .iter()
+        .filter(|task| task.name.to_lowercase() == lowercase);
+    match (exact.next(), exact.next()) {
+        (Some(task), None) => return Ok(task),
+        (Some(_), Some(_)) => return Err(FindError::Ambiguous(query.to_string())),
+        (None, _) => {}
+    }
+
+    let mut matches = tasks
+        .iter()
+        .filter(|task| task.name.to_lowercase().starts_with(&lowercase));
+    match (matches.next(), matches.next()) {
+        (Some(task), None) => Ok(task),
+        (Some(_), Some(_)) => Err(FindError::Ambiguous(query.to_string())),
+        (None, _) => Err(FindError::NotFound(query.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tasks(names: &[&str]) -> Vec<Task> {
+        names
+            .iter()
+            .map(|name| Task::new(name.to_string(), 1, Durations::default()))
+            .collect()
+    }
+
+    #[test]
+    fn tasks_are_found_by_id_name_or_prefix() {
+        let tasks = tasks(&["Write", "Review"]);
+
+        assert_eq!(find_task(&tasks, &tasks[1].id.to_string()), Ok(&tasks[1]));
+        assert_eq!(find_task(&tasks, "write"), Ok(&tasks[0]));
+        assert_eq!(find_task(&tasks, "Rev"), Ok(&tasks[1]));
+        assert_eq!(
+            find_task(&tasks, "Read"),
+            Err(FindError::NotFound("Read".into()))
+        );
+    }
+
+    #[test]
+    fn an_exact_name_beats_a_longer_one() {
+        let tasks = tasks(&["Write", "Write docs"]);
+
+        assert_eq!(find_task(&tasks, "Write"), Ok(&tasks[0]));
+        assert_eq!(
+            find_task(&tasks, "Wr"),
+            Err(FindError::Ambiguous("Wr".into()))
+        );
+    }
+
+    #[test]
+    fn durations_are_formatted_in_hours_and_minutes() {
+        assert_eq!(format_duration(Duration::from_secs(5 * 60 + 59)), "05m");
+        assert_eq!(
+            format_duration(Duration::from_secs(3600 + 5 * 60)),
+            "1h 05m"
+        );
+    }
+
+    #[test]
+    fn clocks_show_minutes_and_seconds() {
+        assert_eq!(format_clock(Duration::from_secs(25 * 60)), "25:00");
+        assert_eq!(format_clock(Duration::from_secs(65)), "01:05");
+    }
+
+    #[test]
+    fn names_are_trimmed_and_unique() {
+        let tasks = tasks(&["Write"]);
+
+        assert_eq!(check_name(&tasks, "  Review ", None), Ok("Review".into()));
+        assert_eq!(check_name(&tasks, " ", None), Err(NameError::Empty));
+        assert_eq!(
+            check_name(&tasks, "write ", None),
+            Err(NameError::Taken("write".into()))
+        );
+    }
+
+    #[test]
+    fn a_task_keeps_its_own_name() {
+        let tasks = tasks(&["Write", "Review"]);
+
+        assert_eq!(
+            check_name(&tasks, "WRITE", Some(tasks[0].id)),
+            Ok("WRITE".into())
+        );
+        assert_eq!(
+            check_name(&tasks, "Review", Some(tasks[0].id)),
+            Err(NameError::Taken("Review".into()))
+        );
+    }
+
+    #[test]
+    fn a_shared_name_is_ambiguous() {
+        let tasks = tasks(&["Write", "Write"]);
+
+        assert_eq!(
+            find_task(&tasks, "Write"),
+            Err(FindError::Ambiguous("Write".into()))
+        );
+    }
 }
@@ -0,0 +1,276 @@
+//! Projects and tags, to group tasks.
+//!
+//! A task belongs to at most one [`Project`], which gives it a colour, and
+//! carries any number of free-form tags.
+
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::Task;
+
+/// The stable identifier of a [`Project`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct ProjectId(Uuid);
+
+impl ProjectId {
+    /// Generates a new random id.
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+impl Default for ProjectId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for ProjectId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for ProjectId {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Uuid::parse_str(s).map(Self)
+    }
+}
+
+/// A colour, saved as `#rrggbb`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    /// The colours given in turn to new projects.
+    pub const PALETTE: [Color; 8] = [
+        Color::rgb(0x89, 0xb4, 0xfa),
+        Color::rgb(0xa6, 0xe3, 0xa1),
+        Color::rgb(0xf9, 0xe2, 0xaf),
+        Color::rgb(0xf3, 0x8b, 0xa8),
+        Color::rgb(0xcb, 0xa6, 0xf7),
+        Color::rgb(0x94, 0xe2, 0xd5),
+        Color::rgb(0xfa, 0xb3, 0x87),
+        Color::rgb(0xf5, 0xc2, 0xe7),
+    ];
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl FromStr for Color {
+    type Err = String;
+
+    /// Parses a `#rrggbb` colour, the `#` being optional.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#').unwrap_or(s);
+        // `from_str_radix` takes a sign too
+        if !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return Err(format!("`{s}` is not a #rrggbb colour"));
+        }
+        let channel = |at: usize| {
+            hex.get(at..at + 2)
+                .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+        };
+
+        match (hex.len(), channel(0), channel(2), channel(4)) {
+            (6, Some(r), Some(g), Some(b)) => Ok(Self::rgb(r, g, b)),
+            _ => Err(format!("`{s}` is not a #rrggbb colour")),
+        }
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Color> for String {
+    fn from(color: Color) -> Self {
+        color.to_string()
+    }
+}
+
+/// A group of tasks, shown in their colour.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Project {
+    pub id: ProjectId,
+    pub name: String,
+    pub color: Color,
+}
+
+impl Project {
+    pub fn new(name: String, color: Color) -> Self {
+        Self {
+            id: ProjectId::new(),
+            name,
+            color,
+        }
+    }
+}
+
+/// The tasks sharing a project or a tag.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Group {
+    Project(ProjectId),
+    Tag(String),
+}
+
+impl Group {
+    /// Whether the `task` belongs to the group.
+    pub fn contains(&self, task: &Task) -> bool {
+        match self {
+            Group::Project(id) => task.project == Some(*id),
+            Group::Tag(tag) => task.has_tag(tag),
+        }
+    }
+}
+
+/// The project named `name` among `projects`, case insensitive.
+pub fn find<'a>(projects: &'a [Project], name: &str) -> Option<&'a Project> {
+    let name = name.trim();
+    projects
+        .iter()
+        .find(|project| project.name.eq_ignore_ascii_case(name))
+}
+
+/// The project named `name` among `projects`, created with the next colour if
+/// it doesn't exist.
+pub fn ensure(projects: &mut Vec<Project>, name: &str) -> ProjectId {
+    if let Some(project) = find(projects, name) {
+        return project.id;
+    }
+
+    let project = Project::new(name.trim().to_string(), next_color(projects));
+    let id = project.id;
+    projects.push(project);
+    id
+}
+
+/// The colour for a new project, the first of the palette used the least by the
+/// `projects`.
+pub fn next_color(projects: &[Project]) -> Color {
+    let uses = |color: &Color| projects.iter().filter(|p| p.color == *color).count();
+    Color::PALETTE
+        .into_iter()
+        .min_by_key(uses)
+        .unwrap_or(Color::PALETTE[0])
+}
+
+/// Cleans a tag up: trims it and drops a leading `#`, `None` if nothing is left.
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim();
+    let tag = tag.strip_prefix('#').unwrap_or(tag).trim();
+    (!tag.is_empty()).then(|| tag.to_string())
+}
+
+/// Reads tags separated by commas, skipping the empty and repeated ones.
+pub fn parse_tags(tags: &str) -> Vec<String> {
+    let mut parsed: Vec<String> = Vec::new();
+    for tag in tags.split(',').filter_map(normalize_tag) {
+        if !parsed.iter().any(|other| other.eq_ignore_ascii_case(&tag)) {
+            parsed.push(tag);
+        }
+    }
+    parsed
+}
+
+/// Every tag of the `tasks`, sorted and without repeats.
+pub fn all_tags(tasks: &[Task]) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in tasks.iter().flat_map(|task| &task.tags) {
+        if !tags.iter().any(|other| other.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.clone());
+        }
+    }
+    tags.sort_by_key(|tag| tag.to_lowercase());
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn projects(colors: &[Color]) -> Vec<Project> {
+        colors
+            .iter()
+            .enumerate()
+            .map(|(index, color)| Project::new(format!("Project {index}"), *color))
+            .collect()
+    }
+
+    #[test]
+    fn a_tag_loses_its_hash_and_blanks() {
+        assert_eq!(normalize_tag("  #draft "), Some("draft".to_string()));
+        assert_eq!(normalize_tag("# Draft"), Some("Draft".to_string()));
+        assert_eq!(normalize_tag("#"), None);
+        assert_eq!(normalize_tag("   "), None);
+    }
+
+    #[test]
+    fn tags_are_read_without_blanks_or_repeats() {
+        assert_eq!(
+            parse_tags("#draft, review,, #, Draft,REVIEW ,later"),
+            ["draft", "review", "later"]
+        );
+        assert!(parse_tags("").is_empty());
+    }
+
+    #[test]
+    fn a_colour_is_read_with_or_without_a_hash() {
+        assert_eq!("#89b4fa".parse(), Ok(Color::rgb(0x89, 0xb4, 0xfa)));
+        assert_eq!("A6E3A1".parse(), Ok(Color::rgb(0xa6, 0xe3, 0xa1)));
+        assert_eq!(Color::rgb(0xa6, 0xe3, 0xa1).to_string(), "#a6e3a1");
+    }
+
+    #[test]
+    fn an_invalid_colour_is_refused() {
+        for invalid in [
+            "", "#", "#89b4f", "#89b4fa0", "#89b4fg", "red", "#89+4fa", "#é9b4f",
+        ] {
+            assert!(invalid.parse::<Color>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn new_projects_cycle_through_the_palette() {
+        assert_eq!(next_color(&[]), Color::PALETTE[0]);
+        assert_eq!(
+            next_color(&projects(&Color::PALETTE[..3])),
+            Color::PALETTE[3]
+        );
+        // once every colour is used, the least used come first, in order
+        let mut colors = Color::PALETTE.to_vec();
+        colors.extend([Color::PALETTE[0], Color::PALETTE[2]]);
+        assert_eq!(next_color(&projects(&colors)), Color::PALETTE[1]);
+        // a colour picked by hand counts as a use too
+        assert_eq!(
+            next_color(&projects(&[Color::PALETTE[1]])),
+            Color::PALETTE[0]
+        );
+        assert_eq!(
+            next_color(&projects(&[Color::PALETTE[0], Color::rgb(0, 0, 0)])),
+            Color::PALETTE[1]
+        );
+    }
+}
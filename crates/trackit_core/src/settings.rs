@@ -0,0 +1,107 @@
+//! Global preferences of the app.
+
+use std::{path::PathBuf, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    goal::Goal,
+    timer::{Durations, Phase},
+};
+
+/// The user preferences shared by every front end.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Settings {
+    /// The default profile used when creating new tasks.
+    #[serde(default)]
+    pub durations: Durations,
+    #[serde(default)]
+    pub alerts: Alerts,
+    /// What a day needs to count towards the streak.
+    #[serde(default)]
+    pub goal: Goal,
+    #[serde(default)]
+    pub idle: IdleDetection,
+}
+
+/// When a running session is held because the user walked away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct IdleDetection {
+    pub enabled: bool,
+    /// How long without input before the user counts as away.
+    pub threshold: Duration,
+}
+
+impl Default for IdleDetection {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+/// How the user is told that a phase ended, for each [`Phase`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Alerts {
+    /// When a work block ends.
+    #[serde(default)]
+    pub work: Alert,
+    /// When a short break ends.
+    #[serde(default)]
+    pub short_break: Alert,
+    /// When a long break ends.
+    #[serde(default)]
+    pub long_break: Alert,
+    /// The sound played by every alert with sound on.
+    #[serde(default)]
+    pub sound: Sound,
+}
+
+impl Alerts {
+    /// The alert raised when the given `phase` ends.
+    pub fn of(&self, phase: Phase) -> Alert {
+        match phase {
+            Phase::Work => self.work,
+            Phase::ShortBreak => self.short_break,
+            Phase::LongBreak => self.long_break,
+        }
+    }
+
+    pub fn of_mut(&mut self, phase: Phase) -> &mut Alert {
+        match phase {
+            Phase::Work => &mut self.work,
+            Phase::ShortBreak => &mut self.short_break,
+            Phase::LongBreak => &mut self.long_break,
+        }
+    }
+}
+
+/// What is done when a phase ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Alert {
+    /// Shows a desktop notification.
+    pub notify: bool,
+    /// Plays the alert [`Sound`].
+    pub sound: bool,
+}
+
+impl Default for Alert {
+    fn default() -> Self {
+        Self {
+            notify: true,
+            sound: true,
+        }
+    }
+}
+
+/// The sound played by the alerts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Sound {
+    /// The bell shipped with the app.
+    #[default]
+    Bell,
+    /// An audio file picked by the user.
+    Custom(PathBuf),
+}
@@ -0,0 +1,356 @@
+//! Aggregates of the session log: focus per day, per week, per task and per
+//! project or tag.
+//!
+//! Only work intervals count, not the idle spans split off them. The focus time is the time spent in them, and a
+//! pomodoro is a work block that ran to completion, the parts of a split block
+//! count as one. An interval counts for the day it started on. Interruptions count for the day and the task they were
+//! noted on.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::Duration,
+};
+
+use chrono::{Datelike, Days, NaiveDate};
+
+use crate::{goal::Goal, interruption::Counts, Interruption, Interval, ProjectId, Task, TaskId};
+
+/// What was done on a single day.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Day {
+    pub focus: Duration,
+    pub pomodoros: u32,
+    pub interruptions: Counts,
+}
+
+impl Day {
+    fn add(&mut self, interval: &Interval, pomodoro: bool) {
+        self.focus += interval.duration();
+        if pomodoro {
+            self.pomodoros += 1;
+        }
+    }
+
+    fn merge(&mut self, other: &Day) {
+        self.focus += other.focus;
+        self.pomodoros += other.pomodoros;
+        self.interruptions += other.interruptions;
+    }
+}
+
+/// What was done on a task, overall.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskTotal {
+    pub task_id: TaskId,
+    /// The name the task had in its latest interval.
+    pub name: String,
+    pub focus: Duration,
+    pub pomodoros: u32,
+    pub interruptions: Counts,
+}
+
+/// The statistics of a set of intervals.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    days: BTreeMap<NaiveDate, Day>,
+    /// Sorted by focus, the most focused first.
+    tasks: Vec<TaskTotal>,
+}
+
+impl Stats {
+    /// Aggregates the `intervals` and the `interruptions`, expected oldest first.
+    pub fn new(intervals: &[Interval], interruptions: &[Interruption]) -> Self {
+        let mut days = BTreeMap::<NaiveDate, Day>::new();
+        let mut tasks = BTreeMap::<TaskId, (String, Day)>::new();
+        // the intervals whose pomodoro is counted, their split parts don't count again
+        let mut counted = BTreeSet::new();
+
+        for interval in intervals.iter().filter(|i| i.is_work()) {
+            let pomodoro = interval.interruption.is_none()
+                && match interval.split_from {
+                    Some(origin) => counted.insert(origin),
+                    None => {
+                        counted.insert(interval.id);
+                        true
+                    }
+                };
+            days.entry(interval.start.date_naive())
+                .or_default()
+                .add(interval, pomodoro);
+
+            let (name, day) = tasks.entry(interval.task_id).or_default();
+            name.clone_from(&interval.task);
+            day.add(interval, pomodoro);
+        }
+        for interruption in interruptions {
+            days.entry(interruption.at.date_naive())
+                .or_default()
+                .interruptions
+                .add(interruption.kind);
+
+            let (name, day) = tasks.entry(interruption.task_id).or_default();
+            if name.is_empty() {
+                name.clone_from(&interruption.task);
+            }
+            day.interruptions.add(interruption.kind);
+        }
+
+        let mut tasks: Vec<_> = tasks
+            .into_iter()
+            .map(|(task_id, (name, day))| TaskTotal {
+                task_id,
+                name,
+                focus: day.focus,
+                pomodoros: day.pomodoros,
+                interruptions: day.interruptions,
+            })
+            .collect();
+        tasks.sort_by(|a, b| b.focus.cmp(&a.focus).then_with(|| a.name.cmp(&b.name)));
+
+        Self { days, tasks }
+    }
+
+    /// What was done on the given `date`.
+    pub fn day(&self, date: NaiveDate) -> Day {
+        self.days.get(&date).copied().unwrap_or_default()
+    }
+
+    /// Every day from `from` to `to`, both included, empty days too.
+    pub fn days(&self, from: NaiveDate, to: NaiveDate) -> Vec<(NaiveDate, Day)> {
+        from.iter_days()
+            .take_while(|date| *date <= to)
+            .map(|date| (date, self.day(date)))
+            .collect()
+    }
+
+    /// The last `count` weeks up to the one of `today`, oldest first.
+    ///
+    /// Each week is given by its monday.
+    pub fn weeks(&self, today: NaiveDate, count: u32) -> Vec<(NaiveDate, Day)> {
+        let monday = week_start(today);
+
+        (0..count)
+            .rev()
+            .filter_map(|weeks_ago| monday.checked_sub_days(Days::new(7 * weeks_ago as u64)))
+            .map(|start| {
+                let end = start + Days::new(6);
+                let week =
+                    self.days
+                        .range(start..=end)
+                        .fold(Day::default(), |mut week, (_, day)| {
+                            week.merge(day);
+                            week
+                        });
+                (start, week)
+            })
+            .collect()
+    }
+
+    /// The totals of every task, the most focused first.
+    pub fn tasks(&self) -> &[TaskTotal] {
+        &self.tasks
+    }
+
+    /// The totals of every project, the most focused first.
+    ///
+    /// A task counts for the project it has now among `tasks`, the tasks without
+    /// project or deleted are gathered under `None`.
+    pub fn projects(&self, tasks: &[Task]) -> Vec<(Option<ProjectId>, Day)> {
+        self.grouped(|id| {
+            let project = tasks
+                .iter()
+                .find(|task| task.id == id)
+                .and_then(|task| task.project);
+            vec![project]
+        })
+    }
+
+    /// The totals of every tag of the `tasks`, the most focused first.
+    ///
+    /// A task counts for each of its tags, so the totals may overlap.
+    pub fn tags(&self, tasks: &[Task]) -> Vec<(String, Day)> {
+        self.grouped(|id| {
+            tasks
+                .iter()
+                .find(|task| task.id == id)
+                .map(|task| task.tags.clone())
+                .unwrap_or_default()
+        })
+    }
+
+    /// Sums the task totals by the groups each task belongs to.
+    fn grouped<K: Ord>(&self, groups_of: impl Fn(TaskId) -> Vec<K>) -> Vec<(K, Day)> {
+        let mut groups = BTreeMap::<K, Day>::new();
+        for total in &self.tasks {
+            for key in groups_of(total.task_id) {
+                groups.entry(key).or_default().merge(&Day {
+                    focus: total.focus,
+                    pomodoros: total.pomodoros,
+                    interruptions: total.interruptions,
+                });
+            }
+        }
+
+        let mut groups: Vec<_> = groups.into_iter().collect();
+        groups.sort_by_key(|(_, day)| std::cmp::Reverse(day.focus));
+        groups
+    }
+
+    /// The number of consecutive focused days up to `today`.
+    ///
+    /// The streak isn't broken until `today` is over, so it may end yesterday.
+    pub fn current_streak(&self, today: NaiveDate) -> u32 {
+        self.streak_until(today, |day| !day.focus.is_zero())
+    }
+
+    /// The longest run of consecutive focused days up to `today`.
+    pub fn longest_streak(&self, today: NaiveDate) -> u32 {
+        self.longest_run(today, |day| !day.focus.is_zero())
+    }
+
+    /// The number of consecutive days that met the `goal` up to `today`.
+    ///
+    /// The streak isn't broken until `today` is over, so it may end yesterday.
+    pub fn goal_streak(&self, goal: &Goal, today: NaiveDate) -> u32 {
+        self.streak_until(today, |day| goal.is_met(day))
+    }
+
+    /// The longest run of consecutive days that met the `goal` up to `today`.
+    pub fn longest_goal_streak(&self, goal: &Goal, today: NaiveDate) -> u32 {
+        self.longest_run(today, |day| goal.is_met(day))
+    }
+
+    /// The number of consecutive days `counts` up to `today`, or yesterday if
+    /// `today` doesn't count yet.
+    ///
+    /// No streak goes back past the first day with something logged, even if
+    /// empty days count.
+    fn streak_until(&self, today: NaiveDate, counts: impl Fn(&Day) -> bool) -> u32 {
+        let Some(&first) = self.days.keys().next() else {
+            return 0;
+        };
+        let counted = |date: &NaiveDate| *date >= first && counts(&self.day(*date));
+        let last = if counted(&today) {
+            Some(today)
+        } else {
+            today.pred_opt()
+        };
+
+        let mut streak = 0;
+        let mut date = last;
+        while let Some(day) = date.filter(counted) {
+            streak += 1;
+            date = day.pred_opt();
+        }
+        streak
+    }
+
+    /// The longest run of consecutive days that `counts`, from the first day
+    /// with something logged up to `today`, empty days included as in
+    /// [`streak_until`](Self::streak_until).
+    fn longest_run(&self, today: NaiveDate, counts: impl Fn(&Day) -> bool) -> u32 {
+        let (Some(&first), Some(&last)) = (self.days.keys().next(), self.days.keys().next_back())
+        else {
+            return 0;
+        };
+
+        let mut longest = 0;
+        let mut streak = 0;
+        for date in first
+            .iter_days()
+            .take_while(|date| *date <= last.max(today))
+        {
+            streak = if counts(&self.day(date)) {
+                streak + 1
+            } else {
+                0
+            };
+            longest = longest.max(streak);
+        }
+
+        longest
+    }
+}
+
+/// The monday of the week of `date`.
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Days::new(date.weekday().num_days_from_monday() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeDelta, TimeZone};
+
+    use super::*;
+    use crate::{idle::IDLE, Phase};
+
+    /// A 25 minutes work block on the `day` of March 2024.
+    fn pomodoro(day: u32) -> Interval {
+        let start = Local.with_ymd_and_hms(2024, 3, day, 9, 0, 0).unwrap();
+        Interval::new(
+            TaskId::new(),
+            "Write",
+            Phase::Work,
+            start,
+            start + TimeDelta::minutes(25),
+        )
+    }
+
+    fn march(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    #[test]
+    fn an_idle_span_split_off_is_no_focus() {
+        let stats = Stats::new(&[pomodoro(4), pomodoro(4).interrupted(IDLE)], &[]);
+
+        assert_eq!(stats.day(march(4)).focus, Duration::from_secs(25 * 60));
+        assert_eq!(stats.day(march(4)).pomodoros, 1);
+    }
+
+    #[test]
+    fn a_streak_may_end_yesterday() {
+        let stats = Stats::new(&[pomodoro(2), pomodoro(4), pomodoro(5)], &[]);
+
+        assert_eq!(stats.current_streak(march(6)), 2);
+        assert_eq!(stats.current_streak(march(5)), 2);
+        assert_eq!(stats.current_streak(march(7)), 0);
+        assert_eq!(stats.longest_streak(march(7)), 2);
+    }
+
+    #[test]
+    fn a_goal_streak_counts_the_days_that_met_it() {
+        let stats = Stats::new(&[pomodoro(3), pomodoro(4), pomodoro(4)], &[]);
+
+        assert_eq!(stats.goal_streak(&Goal::Pomodoros(1), march(4)), 2);
+        assert_eq!(stats.goal_streak(&Goal::Pomodoros(2), march(4)), 1);
+        assert_eq!(stats.goal_streak(&Goal::Minutes(50), march(5)), 1);
+    }
+
+    #[test]
+    fn the_longest_goal_streak_is_never_shorter_than_the_current_one() {
+        let stats = Stats::new(&[pomodoro(1), pomodoro(3), pomodoro(4)], &[]);
+
+        for goal in [Goal::Pomodoros(0), Goal::Pomodoros(1), Goal::Minutes(50)] {
+            for today in (1..=8).map(march) {
+                assert!(
+                    stats.longest_goal_streak(&goal, today) >= stats.goal_streak(&goal, today),
+                    "{goal:?} on {today}"
+                );
+            }
+        }
+        assert_eq!(stats.longest_goal_streak(&Goal::Pomodoros(1), march(8)), 2);
+    }
+
+    #[test]
+    fn a_goal_of_nothing_counts_from_the_first_logged_day() {
+        let stats = Stats::new(&[pomodoro(3)], &[]);
+
+        assert_eq!(stats.goal_streak(&Goal::Pomodoros(0), march(5)), 3);
+        assert_eq!(stats.longest_goal_streak(&Goal::Pomodoros(0), march(5)), 3);
+        assert_eq!(
+            Stats::default().goal_streak(&Goal::Pomodoros(0), march(5)),
+            0
+        );
+    }
+}
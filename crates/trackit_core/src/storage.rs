@@ -0,0 +1,341 @@
+//! On-disk persistence of the app data.
+//!
+//! Everything is kept in a single JSON document inside the XDG data directory
+//! (`$XDG_DATA_HOME/trackit/data.json`). The document carries a schema version so
+//! older files can be migrated when new fields are introduced.
+
+use std::{
+    fmt,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    project::{self, Project, ProjectId},
+    ActiveSession, Settings, Task, TaskId,
+};
+
+/// The current version of the data file.
+pub const SCHEMA_VERSION: u64 = 2;
+
+const APP_DIR: &str = "trackit";
+const DATA_FILE: &str = "data.json";
+
+/// Everything persisted by the app.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Data {
+    /// The tasks, in the order the user left them.
+    #[serde(default)]
+    pub tasks: Vec<Task>,
+    #[serde(default)]
+    pub projects: Vec<Project>,
+    #[serde(default)]
+    pub settings: Settings,
+    /// The session running right now, if any.
+    #[serde(default)]
+    pub active: Option<ActiveSession>,
+    /// The tasks that just got an id, migrating a file from before ids existed.
+    ///
+    /// Their intervals in the log are still keyed by name until
+    /// [linked](crate::SessionLog::link_migrated). It's never saved.
+    #[serde(default, skip_serializing)]
+    pub migrated: Vec<TaskId>,
+}
+
+impl Data {
+    /// The task with the given `id`.
+    pub fn task(&self, id: TaskId) -> Option<&Task> {
+        self.tasks.iter().find(|task| task.id == id)
+    }
+
+    /// The task with the given `id`, mutably.
+    pub fn task_mut(&mut self, id: TaskId) -> Option<&mut Task> {
+        self.tasks.iter_mut().find(|task| task.id == id)
+    }
+
+    /// The project with the given `id`.
+    pub fn project(&self, id: ProjectId) -> Option<&Project> {
+        self.projects.iter().find(|project| project.id == id)
+    }
+
+    /// The project named `name`, case insensitive.
+    pub fn project_named(&self, name: &str) -> Option<&Project> {
+        project::find(&self.projects, name)
+    }
+
+    /// The project named `name`, created with the next colour if it doesn't exist.
+    pub fn ensure_project(&mut self, name: &str) -> ProjectId {
+        project::ensure(&mut self.projects, name)
+    }
+}
+
+/// The error type of the [`Storage`].
+#[derive(Debug)]
+pub enum Error {
+    /// There's no data directory on this platform.
+    NoDataDir,
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// The file was written by a newer version of the app.
+    UnsupportedVersion(u64),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoDataDir => f.write_str("unable to find the data directory"),
+            Error::Io(err) => write!(f, "i/o error: {err}"),
+            Error::Json(err) => write!(f, "malformed data file: {err}"),
+            Error::UnsupportedVersion(version) => {
+                write!(f, "unsupported data file version {version}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Json(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+/// The versioned shape of the file.
+#[derive(Serialize)]
+struct Document<'a> {
+    version: u64,
+    #[serde(flatten)]
+    data: &'a Data,
+}
+
+/// A handle to the data file.
+#[derive(Debug, Clone)]
+pub struct Storage {
+    path: PathBuf,
+}
+
+impl Storage {
+    /// Uses the file at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Uses the default file inside the XDG data directory.
+    pub fn open_default() -> Result<Self, Error> {
+        Ok(Self::new(data_dir()?.join(DATA_FILE)))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the data, migrating it to the [`SCHEMA_VERSION`] if needed.
+    ///
+    /// A missing file is not an error, it yields empty [`Data`].
+    pub fn load(&self) -> Result<Data, Error> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Data::default()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let document: Value = serde_json::from_str(&content)?;
+        Ok(serde_json::from_value(migrate(document)?)?)
+    }
+
+    /// Writes the data atomically.
+    ///
+    /// The content goes to a temporary file next to the real one, which is then
+    /// renamed over it, so a crash never leaves a half written file behind.
+    pub fn save(&self, data: &Data) -> Result<(), Error> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let document = Document {
+            version: SCHEMA_VERSION,
+            data,
+        };
+        let tmp = self.path.with_extension("json.tmp");
+
+        let mut file = File::create(&tmp)?;
+        serde_json::to_writer_pretty(&mut file, &document)?;
+        file.flush()?;
+        file.sync_all()?;
+        fs::rename(&tmp, &self.path)?;
+
+        Ok(())
+    }
+}
+
+/// The directory where trackit keeps its data.
+pub fn data_dir() -> Result<PathBuf, Error> {
+    dirs::data_dir()
+        .map(|dir| dir.join(APP_DIR))
+        .ok_or(Error::NoDataDir)
+}
+
+/// Brings a document of any known version up to the [`SCHEMA_VERSION`].
+fn migrate(mut document: Value) -> Result<Value, Error> {
+    let version = document.get("version").and_then(Value::as_u64).unwrap_or(1);
+    if version > SCHEMA_VERSION {
+        return Err(Error::UnsupportedVersion(version));
+    }
+
+    if version < 2 {
+        add_task_ids(&mut document);
+    }
+
+    Ok(document)
+}
+
+/// v1 → v2: tasks get a [`TaskId`], listed in [`Data::migrated`].
+///
+/// Every task gets its own id, even when several share a name.
+fn add_task_ids(document: &mut Value) {
+    let Some(tasks) = document.get_mut("tasks").and_then(Value::as_array_mut) else {
+        return;
+    };
+
+    let mut migrated = Vec::new();
+    for task in tasks.iter_mut().filter_map(Value::as_object_mut) {
+        if !task.contains_key("id") {
+            let id = TaskId::new();
+            task.insert("id".into(), Value::String(id.to_string()));
+            migrated.push(Value::String(id.to_string()));
+        }
+    }
+
+    if let Some(document) = document.as_object_mut() {
+        document.insert("migrated".into(), Value::Array(migrated));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// A storage in a fresh directory of its own, named after the test.
+    fn storage(test: &str) -> Storage {
+        let dir = std::env::temp_dir().join(format!("trackit-{}-{test}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        Storage::new(dir.join(DATA_FILE))
+    }
+
+    #[test]
+    fn a_missing_file_loads_empty() {
+        let storage = storage("missing");
+
+        assert_eq!(storage.load().unwrap(), Data::default());
+    }
+
+    #[test]
+    fn saved_data_loads_back() {
+        let storage = storage("round-trip");
+        let mut data = Data::default();
+        data.tasks
+            .push(Task::new("Write".into(), 3, Default::default()));
+        data.ensure_project("Book");
+
+        storage.save(&data).unwrap();
+
+        assert_eq!(storage.load().unwrap(), data);
+    }
+
+    #[test]
+    fn saving_leaves_no_temporary_file() {
+        let storage = storage("atomic");
+
+        storage.save(&Data::default()).unwrap();
+        storage.save(&Data::default()).unwrap();
+
+        let files: Vec<_> = fs::read_dir(storage.path().parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(files, [DATA_FILE]);
+    }
+
+    #[test]
+    fn the_saved_file_carries_the_current_version() {
+        let storage = storage("version");
+
+        storage.save(&Data::default()).unwrap();
+
+        let document: Value =
+            serde_json::from_str(&fs::read_to_string(storage.path()).unwrap()).unwrap();
+        assert_eq!(document["version"], SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn an_unversioned_document_gets_task_ids() {
+        let task = json!({
+            "name": "Write", "cycles": 2, "started_at": null, "elapsed": {"secs": 0, "nanos": 0},
+        });
+        let document = json!({ "tasks": [task, task] });
+
+        let data: Data = serde_json::from_value(migrate(document).unwrap()).unwrap();
+
+        // the same name doesn't make the same task
+        assert_eq!(data.tasks.len(), 2);
+        assert_ne!(data.tasks[0].id, data.tasks[1].id);
+        assert_eq!(data.migrated, [data.tasks[0].id, data.tasks[1].id]);
+    }
+
+    #[test]
+    fn the_migrated_tasks_are_not_saved() {
+        let storage = storage("migrated");
+        let data = Data {
+            migrated: vec![TaskId::new()],
+            ..Data::default()
+        };
+
+        storage.save(&data).unwrap();
+
+        assert!(storage.load().unwrap().migrated.is_empty());
+    }
+
+    #[test]
+    fn ids_already_there_are_kept() {
+        let id = TaskId::new();
+        let document = json!({
+            "version": 1,
+            "tasks": [{"id": id.to_string(), "name": "Write"}],
+        });
+
+        let document = migrate(document).unwrap();
+
+        assert_eq!(document["tasks"][0]["id"], id.to_string());
+    }
+
+    #[test]
+    fn a_newer_version_is_refused() {
+        let document = json!({ "version": SCHEMA_VERSION + 1 });
+
+        assert!(matches!(
+            migrate(document),
+            Err(Error::UnsupportedVersion(version)) if version == SCHEMA_VERSION + 1
+        ));
+    }
+}
@@ -0,0 +1,483 @@
+//! Corrections of the past: intervals entered by hand, and edits of the
+//! recorded ones.
+//!
+//! Every [`Change`] is checked before it reaches the [`SessionLog`]: an interval
+//! can't be empty, can't run past the time the log is complete up to, and can't
+//! overlap another interval, whatever its task. The [`Applied`] change tells how
+//! the work time of the tasks moved, so their elapsed time follows.
+
+use std::{fmt, time::Duration};
+
+use chrono::{DateTime, Local, TimeDelta};
+
+use crate::{history, Interval, SessionLog, Task, TaskId};
+
+/// The longest gap between two intervals that still follow each other, for
+/// the instants rounded differently by the front ends.
+pub const MERGE_GAP: TimeDelta = TimeDelta::seconds(1);
+
+/// An edit of the session log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// Records a new interval, entered by hand.
+    Add(Interval),
+    /// Replaces the recorded interval with the same id.
+    Update(Interval),
+    /// Cuts the interval with the given `id` in two, at `at`.
+    Split { id: i64, at: DateTime<Local> },
+    /// Joins two intervals of the same task and phase, one following the other,
+    /// into one spanning both.
+    Merge { first: i64, second: i64 },
+    /// Deletes the interval with the given id.
+    Delete(i64),
+}
+
+/// Why a [`Change`] was refused.
+#[derive(Debug)]
+pub enum Error {
+    Log(history::Error),
+    /// There's no interval with this id.
+    NotFound(i64),
+    /// The interval doesn't end after it starts.
+    Empty,
+    /// The interval runs past the instant the log is complete up to.
+    Unlogged(DateTime<Local>),
+    /// The interval overlaps this recorded one.
+    Overlap(Box<Interval>),
+    /// The instant to split at isn't inside the interval.
+    OutsideInterval,
+    /// The intervals to merge are of different tasks or phases.
+    Unmergeable,
+    /// The intervals to merge don't follow each other.
+    Apart,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Log(err) => err.fmt(f),
+            Error::NotFound(id) => write!(f, "there's no interval #{id}"),
+            Error::Empty => f.write_str("the interval must end after it starts"),
+            Error::Unlogged(until) => write!(
+                f,
+                "the interval can't end after {}, the running session tracks that time",
+                until.format("%H:%M")
+            ),
+            Error::Overlap(other) => write!(
+                f,
+                "the interval overlaps the {} of `{}` from {} to {}",
+                other.phase.to_string().to_lowercase(),
+                other.task,
+                other.start.format("%H:%M"),
+                other.end.format("%H:%M")
+            ),
+            Error::OutsideInterval => f.write_str("the split must fall inside the interval"),
+            Error::Unmergeable => {
+                f.write_str("only intervals of the same task and phase can be merged")
+            }
+            Error::Apart => f.write_str("only intervals following each other can be merged"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Log(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<history::Error> for Error {
+    fn from(err: history::Error) -> Self {
+        Error::Log(err)
+    }
+}
+
+/// What a [`Change`] did to the log.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Applied {
+    /// The intervals taken out of the log, as they were.
+    pub removed: Vec<Interval>,
+    /// The intervals put in the log, with their ids.
+    pub added: Vec<Interval>,
+}
+
+impl Applied {
+    /// The tasks whose intervals changed.
+    pub fn tasks(&self) -> Vec<TaskId> {
+        let mut tasks: Vec<_> = self
+            .removed
+            .iter()
+            .chain(&self.added)
+            .map(|interval| interval.task_id)
+            .collect();
+        tasks.sort();
+        tasks.dedup();
+        tasks
+    }
+
+    /// Moves the elapsed time of the `task` by the work time the change added
+    /// and removed.
+    ///
+    /// The elapsed time is adjusted rather than summed again from the log, the
+    /// work done before the log existed still counts.
+    pub fn adjust(&self, task: &mut Task) {
+        let work = |intervals: &[Interval]| -> Duration {
+            intervals
+                .iter()
+                .filter(|interval| interval.task_id == task.id && interval.is_work())
+                .map(Interval::duration)
+                .sum()
+        };
+
+        task.elapsed = (task.elapsed + work(&self.added)).saturating_sub(work(&self.removed));
+    }
+}
+
+impl Change {
+    /// Checks the change against the `log`, and applies it.
+    ///
+    /// Nothing can be logged after `until`: the log is complete up to there, a
+    /// running session logs the rest.
+    pub fn apply(&self, log: &mut SessionLog, until: DateTime<Local>) -> Result<Applied, Error> {
+        let (removed, mut added) = match self {
+            Change::Add(interval) => {
+                let interval = Interval {
+                    id: 0,
+                    ..interval.clone()
+                };
+                check(log, &interval, until, &[])?;
+                (vec![], vec![interval])
+            }
+            Change::Update(interval) => {
+                let before = find(log, interval.id)?;
+                check(log, interval, until, &[interval.id])?;
+                let interval = Interval {
+                    split_from: before.split_from,
+                    ..interval.clone()
+                };
+                (vec![before], vec![interval])
+            }
+            Change::Split { id, at } => {
+                let before = find(log, *id)?;
+                if *at <= before.start || *at >= before.end {
+                    return Err(Error::OutsideInterval);
+                }
+                // both parts ended the way the interval did, the pomodoro is
+                // counted once for the part split off
+                let first = Interval {
+                    end: *at,
+                    ..before.clone()
+                };
+                let second = Interval {
+                    id: 0,
+                    start: *at,
+                    split_from: Some(before.split_from.unwrap_or(before.id)),
+                    ..before.clone()
+                };
+                (vec![before], vec![first, second])
+            }
+            Change::Merge { first, second } => {
+                let (mut first, mut second) = (find(log, *first)?, find(log, *second)?);
+                if second.start < first.start {
+                    std::mem::swap(&mut first, &mut second);
+                }
+                check_merge(&first, &second)?;
+                let merged = Interval {
+                    start: first.start,
+                    end: first.end.max(second.end),
+                    interruption: second.interruption.clone(),
+                    ..first.clone()
+                };
+                check(log, &merged, until, &[first.id, second.id])?;
+                (vec![first, second], vec![merged])
+            }
+            Change::Delete(id) => (vec![find(log, *id)?], vec![]),
+        };
+
+        log.rewrite(&removed, &mut added)?;
+        // as recorded, to the millisecond, so the elapsed times add up
+        let added = added
+            .iter()
+            .map(|interval| find(log, interval.id))
+            .collect::<Result<_, _>>()?;
+        Ok(Applied { removed, added })
+    }
+}
+
+/// Checks that `first` and `second`, starting later, can be merged: they're of
+/// the same task and phase, and the second one starts when the first one ends.
+pub fn check_merge(first: &Interval, second: &Interval) -> Result<(), Error> {
+    if first.task_id != second.task_id || first.phase != second.phase {
+        return Err(Error::Unmergeable);
+    }
+    if second.start - first.end > MERGE_GAP {
+        return Err(Error::Apart);
+    }
+
+    Ok(())
+}
+
+fn find(log: &SessionLog, id: i64) -> Result<Interval, Error> {
+    log.interval(id)?.ok_or(Error::NotFound(id))
+}
+
+/// Checks that the `interval` is not empty, ends by `until`, and overlaps no
+/// recorded interval but the `replaced` ones.
+fn check(
+    log: &SessionLog,
+    interval: &Interval,
+    until: DateTime<Local>,
+    replaced: &[i64],
+) -> Result<(), Error> {
+    if interval.end <= interval.start {
+        return Err(Error::Empty);
+    }
+    if interval.end > until {
+        return Err(Error::Unlogged(until));
+    }
+
+    match log
+        .overlapping(interval.start, interval.end)?
+        .into_iter()
+        .find(|other| !replaced.contains(&other.id))
+    {
+        Some(other) => Err(Error::Overlap(Box::new(other))),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::{stats::Stats, Durations, Phase};
+
+    /// `minutes` after 9:00.
+    fn at(minutes: i64) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap() + TimeDelta::minutes(minutes)
+    }
+
+    fn task(name: &str) -> Task {
+        Task::new(name.into(), 1, Durations::default())
+    }
+
+    fn work(task: &Task, from: i64, to: i64) -> Interval {
+        Interval::new(task.id, task.name.clone(), Phase::Work, at(from), at(to))
+    }
+
+    /// A log holding the `intervals`, with their ids set.
+    fn log(intervals: &mut [Interval]) -> SessionLog {
+        let mut log = SessionLog::open_in_memory().unwrap();
+        log.record_all(intervals).unwrap();
+        log
+    }
+
+    fn spans(log: &SessionLog) -> Vec<(DateTime<Local>, DateTime<Local>)> {
+        log.intervals(&history::Filter::default())
+            .unwrap()
+            .iter()
+            .map(|interval| (interval.start, interval.end))
+            .collect()
+    }
+
+    #[test]
+    fn an_interval_is_added_in_a_free_slot() {
+        let write = task("Write");
+        let mut log = log(&mut [work(&write, 0, 25)]);
+
+        let applied = Change::Add(work(&write, 30, 55))
+            .apply(&mut log, at(60))
+            .unwrap();
+
+        assert!(applied.removed.is_empty());
+        assert_ne!(applied.added[0].id, 0);
+        assert_eq!(spans(&log), [(at(0), at(25)), (at(30), at(55))]);
+    }
+
+    #[test]
+    fn intervals_never_overlap_whatever_their_task() {
+        let (write, read) = (task("Write"), task("Read"));
+        let mut log = log(&mut [work(&write, 0, 25)]);
+
+        let result = Change::Add(work(&read, 20, 40)).apply(&mut log, at(60));
+
+        assert!(matches!(result, Err(Error::Overlap(other)) if other.task == "Write"));
+        assert_eq!(spans(&log), [(at(0), at(25))]);
+    }
+
+    #[test]
+    fn touching_intervals_do_not_overlap() {
+        let write = task("Write");
+        let mut log = log(&mut [work(&write, 0, 25)]);
+
+        assert!(Change::Add(work(&write, 25, 30))
+            .apply(&mut log, at(60))
+            .is_ok());
+    }
+
+    #[test]
+    fn empty_and_unlogged_intervals_are_refused() {
+        let write = task("Write");
+        let mut log = log(&mut []);
+
+        let empty = Change::Add(work(&write, 10, 10)).apply(&mut log, at(60));
+        let unlogged = Change::Add(work(&write, 50, 70)).apply(&mut log, at(60));
+
+        assert!(matches!(empty, Err(Error::Empty)));
+        assert!(matches!(unlogged, Err(Error::Unlogged(until)) if until == at(60)));
+    }
+
+    #[test]
+    fn an_update_may_overlap_the_interval_it_replaces() {
+        let write = task("Write");
+        let mut intervals = [work(&write, 0, 25)];
+        let mut log = log(&mut intervals);
+
+        let moved = Interval {
+            start: at(5),
+            end: at(35),
+            ..intervals[0].clone()
+        };
+        let applied = Change::Update(moved).apply(&mut log, at(60)).unwrap();
+
+        assert_eq!(applied.removed, intervals);
+        assert_eq!(spans(&log), [(at(5), at(35))]);
+    }
+
+    fn pomodoros(log: &SessionLog) -> u32 {
+        let intervals = log.intervals(&history::Filter::default()).unwrap();
+        Stats::new(&intervals, &[])
+            .day(at(0).date_naive())
+            .pomodoros
+    }
+
+    #[test]
+    fn a_split_cuts_inside_the_interval() {
+        let write = task("Write");
+        let mut intervals = [work(&write, 0, 25).interrupted("stopped")];
+        let mut log = log(&mut intervals);
+        let id = intervals[0].id;
+
+        let outside = Change::Split { id, at: at(25) }.apply(&mut log, at(60));
+        let applied = Change::Split { id, at: at(10) }
+            .apply(&mut log, at(60))
+            .unwrap();
+
+        assert!(matches!(outside, Err(Error::OutsideInterval)));
+        assert_eq!(spans(&log), [(at(0), at(10)), (at(10), at(25))]);
+        // both parts ended the way the interval did
+        assert!(applied
+            .added
+            .iter()
+            .all(|part| part.interruption.as_deref() == Some("stopped")));
+        assert_eq!(pomodoros(&log), 0);
+    }
+
+    #[test]
+    fn the_parts_of_a_split_pomodoro_count_once() {
+        let write = task("Write");
+        let mut intervals = [work(&write, 0, 25)];
+        let mut log = log(&mut intervals);
+        let id = intervals[0].id;
+
+        let applied = Change::Split { id, at: at(10) }
+            .apply(&mut log, at(60))
+            .unwrap();
+        // splitting a part again, or correcting it, still counts once
+        Change::Split {
+            id: applied.added[1].id,
+            at: at(20),
+        }
+        .apply(&mut log, at(60))
+        .unwrap();
+        Change::Update(Interval {
+            id: applied.added[1].id,
+            ..work(&write, 12, 20)
+        })
+        .apply(&mut log, at(60))
+        .unwrap();
+
+        assert_eq!(spans(&log).len(), 3);
+        assert_eq!(pomodoros(&log), 1);
+    }
+
+    #[test]
+    fn merging_joins_intervals_following_each_other() {
+        let write = task("Write");
+        let mut intervals = [work(&write, 0, 10), work(&write, 10, 25)];
+        let mut log = log(&mut intervals);
+
+        // in any order
+        Change::Merge {
+            first: intervals[1].id,
+            second: intervals[0].id,
+        }
+        .apply(&mut log, at(60))
+        .unwrap();
+
+        assert_eq!(spans(&log), [(at(0), at(25))]);
+    }
+
+    #[test]
+    fn merging_needs_the_same_task_and_phase() {
+        let (write, read) = (task("Write"), task("Read"));
+        let mut intervals = [work(&write, 0, 10), work(&read, 10, 25)];
+        let mut log = log(&mut intervals);
+
+        let result = Change::Merge {
+            first: intervals[0].id,
+            second: intervals[1].id,
+        }
+        .apply(&mut log, at(60));
+
+        assert!(matches!(result, Err(Error::Unmergeable)));
+    }
+
+    #[test]
+    fn merging_refuses_intervals_apart() {
+        let write = task("Write");
+        let mut intervals = [work(&write, 0, 10), work(&write, 15, 25)];
+        let mut log = log(&mut intervals);
+
+        let result = Change::Merge {
+            first: intervals[0].id,
+            second: intervals[1].id,
+        }
+        .apply(&mut log, at(60));
+
+        assert!(matches!(result, Err(Error::Apart)));
+        assert_eq!(spans(&log).len(), 2);
+    }
+
+    #[test]
+    fn the_elapsed_time_follows_the_work_changed() {
+        let mut write = task("Write");
+        write.elapsed = Duration::from_secs(40 * 60);
+        let mut intervals = [work(&write, 0, 25)];
+        let mut log = log(&mut intervals);
+
+        let applied = Change::Delete(intervals[0].id)
+            .apply(&mut log, at(60))
+            .unwrap();
+        applied.adjust(&mut write);
+
+        assert_eq!(applied.tasks(), [write.id]);
+        assert!(spans(&log).is_empty());
+        // the work done before the log existed still counts
+        assert_eq!(write.elapsed, Duration::from_secs(15 * 60));
+    }
+
+    #[test]
+    fn a_missing_interval_is_reported() {
+        let mut log = log(&mut []);
+
+        assert!(matches!(
+            Change::Delete(42).apply(&mut log, at(60)),
+            Err(Error::NotFound(42))
+        ));
+    }
+}
@@ -0,0 +1,552 @@
+//! The Pomodoro state machine.
+//!
+//! A [`Session`] walks through the [`Phase`]s of a [`Task`]:
+//! `Work → ShortBreak → Work → … → LongBreak → …` until every cycle is done.
+//! It is advanced from wall-clock instants, so missed ticks never skew it.
+
+use std::{fmt, time::Duration};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::Task;
+
+/// The default length of a work block.
+pub const WORK: Duration = Duration::from_secs(25 * 60);
+/// The default length of a short break.
+pub const SHORT_BREAK: Duration = Duration::from_secs(5 * 60);
+/// The default length of a long break.
+pub const LONG_BREAK: Duration = Duration::from_secs(15 * 60);
+/// How many work blocks are done before taking a long break.
+pub const LONG_BREAK_INTERVAL: u8 = 4;
+
+/// The length of every [`Phase`] of a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Durations {
+    pub work: Duration,
+    pub short_break: Duration,
+    pub long_break: Duration,
+}
+
+impl Durations {
+    /// Creates a profile from lengths expressed in minutes.
+    pub fn from_minutes(work: u64, short_break: u64, long_break: u64) -> Self {
+        Self {
+            work: Duration::from_secs(work * 60),
+            short_break: Duration::from_secs(short_break * 60),
+            long_break: Duration::from_secs(long_break * 60),
+        }
+    }
+
+    /// The length of the given `phase`.
+    pub fn of(&self, phase: Phase) -> Duration {
+        match phase {
+            Phase::Work => self.work,
+            Phase::ShortBreak => self.short_break,
+            Phase::LongBreak => self.long_break,
+        }
+    }
+
+    /// The total work and break time needed to complete `cycles` work blocks.
+    ///
+    /// There's no break after the last work block.
+    pub fn plan(&self, cycles: u8) -> (Duration, Duration) {
+        let work = self.work * cycles as u32;
+        let breaks = (1..cycles)
+            .map(|cycle| {
+                if cycle.is_multiple_of(LONG_BREAK_INTERVAL) {
+                    self.long_break
+                } else {
+                    self.short_break
+                }
+            })
+            .sum();
+
+        (work, breaks)
+    }
+}
+
+impl Default for Durations {
+    fn default() -> Self {
+        Self {
+            work: WORK,
+            short_break: SHORT_BREAK,
+            long_break: LONG_BREAK,
+        }
+    }
+}
+
+/// A phase of a Pomodoro [`Session`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Phase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl Phase {
+    pub fn is_break(self) -> bool {
+        !matches!(self, Phase::Work)
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Phase::Work => "Work",
+            Phase::ShortBreak => "Short break",
+            Phase::LongBreak => "Long break",
+        })
+    }
+}
+
+/// A change of [`Phase`] produced by [`Session::advance`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    /// The phase that just ended.
+    pub from: Phase,
+    /// When the last running stretch of the phase that just ended had started,
+    /// that is the phase start or its last resume.
+    pub started_at: DateTime<Local>,
+    /// The phase that just started, `None` if the session finished.
+    pub to: Option<Phase>,
+    /// When the transition happened.
+    pub at: DateTime<Local>,
+}
+
+/// A stretch of time where a [`Session`] was paused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Pause {
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+impl Pause {
+    pub fn duration(&self) -> Duration {
+        (self.end - self.start).to_std().unwrap_or(Duration::ZERO)
+    }
+}
+
+/// A running Pomodoro session of a [`Task`].
+///
+/// Pausing doesn't eat into the current phase: the phase end is pushed back by
+/// the time spent paused.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Session {
+    cycles: u8,
+    durations: Durations,
+    phase: Phase,
+    phase_started_at: DateTime<Local>,
+    /// The start of the current running stretch of the phase.
+    segment_started_at: DateTime<Local>,
+    /// The time spent paused during the current phase.
+    paused: Duration,
+    paused_at: Option<DateTime<Local>>,
+    pauses: Vec<Pause>,
+    completed_cycles: u8,
+    finished: bool,
+}
+
+impl Session {
+    /// Starts a new session for the `task` at `now`, beginning with a work block.
+    pub fn new(task: &Task, now: DateTime<Local>) -> Self {
+        Self {
+            cycles: task.cycles.max(1),
+            durations: task.durations,
+            phase: Phase::Work,
+            phase_started_at: now,
+            segment_started_at: now,
+            paused: Duration::ZERO,
+            paused_at: None,
+            pauses: Vec::new(),
+            completed_cycles: 0,
+            finished: false,
+        }
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn cycles(&self) -> u8 {
+        self.cycles
+    }
+
+    pub fn durations(&self) -> &Durations {
+        &self.durations
+    }
+
+    /// The length of the current phase.
+    pub fn phase_duration(&self) -> Duration {
+        self.durations.of(self.phase)
+    }
+
+    /// The number of work blocks already finished.
+    pub fn completed_cycles(&self) -> u8 {
+        self.completed_cycles
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Whether the clock is ticking, that is neither paused nor finished.
+    pub fn is_running(&self) -> bool {
+        !self.finished && !self.is_paused()
+    }
+
+    pub fn phase_started_at(&self) -> DateTime<Local> {
+        self.phase_started_at
+    }
+
+    /// The start of the current running stretch of the phase, after the last resume.
+    pub fn segment_started_at(&self) -> DateTime<Local> {
+        self.segment_started_at
+    }
+
+    /// Every pause of the session, the ongoing one excluded.
+    pub fn pauses(&self) -> &[Pause] {
+        &self.pauses
+    }
+
+    /// The instant where the current phase is over, if it's not paused again.
+    pub fn phase_ends_at(&self) -> DateTime<Local> {
+        self.phase_started_at + self.phase_duration() + self.paused
+    }
+
+    /// The time left in the current phase.
+    pub fn remaining(&self, now: DateTime<Local>) -> Duration {
+        if self.finished {
+            return Duration::ZERO;
+        }
+
+        (self.phase_ends_at() - self.clock(now))
+            .to_std()
+            .unwrap_or(Duration::ZERO)
+    }
+
+    /// The time the current phase has been running, pauses excluded.
+    pub fn worked(&self, now: DateTime<Local>) -> Duration {
+        if self.finished {
+            return Duration::ZERO;
+        }
+
+        (self.clock(now) - self.phase_started_at)
+            .to_std()
+            .unwrap_or(Duration::ZERO)
+            .saturating_sub(self.paused)
+            .min(self.phase_duration())
+    }
+
+    /// The progress of the current phase, from `0.0` to `100.0`.
+    pub fn progress(&self, now: DateTime<Local>) -> f32 {
+        let total = self.phase_duration().as_secs_f32();
+        if self.finished || total == 0. {
+            return 100.;
+        }
+
+        let left = self.remaining(now).as_secs_f32();
+        ((total - left) / total * 100.).clamp(0., 100.)
+    }
+
+    /// Freezes the session at `now`.
+    ///
+    /// The session should be [advanced](Self::advance) up to `now` first. Returns
+    /// `false` if it was already paused or finished.
+    pub fn pause(&mut self, now: DateTime<Local>) -> bool {
+        if !self.is_running() {
+            return false;
+        }
+
+        self.paused_at = Some(now);
+        true
+    }
+
+    /// Resumes a paused session at `now`.
+    ///
+    /// Returns `false` if it wasn't paused.
+    pub fn resume(&mut self, now: DateTime<Local>) -> bool {
+        let Some(start) = self.paused_at.take() else {
+            return false;
+        };
+
+        let pause = Pause {
+            start,
+            end: now.max(start),
+        };
+        self.paused += pause.duration();
+        self.pauses.push(pause);
+        self.segment_started_at = pause.end;
+        true
+    }
+
+    /// Drops the ongoing pause as if it never happened: the time spent paused
+    /// counts towards the phase.
+    ///
+    /// Returns `false` if it wasn't paused.
+    pub fn unpause(&mut self) -> bool {
+        self.paused_at.take().is_some()
+    }
+
+    /// Starts a new running stretch of the phase at `at`, the previous one being
+    /// logged up to there.
+    pub(crate) fn split_segment(&mut self, at: DateTime<Local>) {
+        self.segment_started_at = at.max(self.phase_started_at);
+    }
+
+    /// Ends the session at `now`, before all its cycles are done.
+    ///
+    /// Returns the work time of the unfinished work block, if the session was in
+    /// one. The session should be [advanced](Self::advance) up to `now` first.
+    pub fn stop(&mut self, now: DateTime<Local>) -> Duration {
+        let worked = match self.phase {
+            Phase::Work => self.worked(now),
+            Phase::ShortBreak | Phase::LongBreak => Duration::ZERO,
+        };
+
+        if self.is_paused() {
+            self.resume(now);
+        }
+        self.finished = true;
+
+        worked
+    }
+
+    /// Ends the current phase at `now` and moves on to the next one.
+    ///
+    /// A skipped work block counts as done. A paused session is resumed first.
+    /// Returns `None` if the session was already finished. The session should be
+    /// [advanced](Self::advance) up to `now` first.
+    pub fn skip(&mut self, now: DateTime<Local>) -> Option<Transition> {
+        if self.finished {
+            return None;
+        }
+        if self.is_paused() {
+            self.resume(now);
+        }
+
+        let at = now.max(self.segment_started_at);
+        let started_at = self.segment_started_at;
+        let from = self.phase;
+        let to = self.next_phase();
+
+        match to {
+            Some(phase) => self.phase = phase,
+            None => self.finished = true,
+        }
+        self.phase_started_at = at;
+        self.segment_started_at = at;
+        self.paused = Duration::ZERO;
+
+        Some(Transition {
+            from,
+            started_at,
+            to,
+            at,
+        })
+    }
+
+    /// Moves the session forward up to `now`.
+    ///
+    /// Every phase that ended in between is reported, so a session that was left
+    /// unattended catches up at once. Each new phase starts exactly where the
+    /// previous one ended, not at `now`. A paused session doesn't move.
+    pub fn advance(&mut self, now: DateTime<Local>) -> Vec<Transition> {
+        let now = self.clock(now);
+        let mut transitions = Vec::new();
+
+        while !self.finished && now >= self.phase_ends_at() {
+            let at = self.phase_ends_at();
+            let started_at = self.segment_started_at;
+            let from = self.phase;
+            let to = self.next_phase();
+
+            match to {
+                Some(phase) => self.phase = phase,
+                None => self.finished = true,
+            }
+            self.phase_started_at = at;
+            self.segment_started_at = at;
+            self.paused = Duration::ZERO;
+            transitions.push(Transition {
+                from,
+                started_at,
+                to,
+                at,
+            });
+        }
+
+        transitions
+    }
+
+    /// The instant the session is at: `now`, or the pause start if it's paused.
+    fn clock(&self, now: DateTime<Local>) -> DateTime<Local> {
+        self.paused_at.unwrap_or(now)
+    }
+
+    fn next_phase(&mut self) -> Option<Phase> {
+        match self.phase {
+            Phase::Work => {
+                self.completed_cycles += 1;
+                if self.completed_cycles >= self.cycles {
+                    None
+                } else if self.completed_cycles.is_multiple_of(LONG_BREAK_INTERVAL) {
+                    Some(Phase::LongBreak)
+                } else {
+                    Some(Phase::ShortBreak)
+                }
+            }
+            Phase::ShortBreak | Phase::LongBreak => Some(Phase::Work),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeDelta, TimeZone};
+
+    use super::*;
+
+    /// `minutes` after the session start.
+    fn at(minutes: i64) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap() + TimeDelta::minutes(minutes)
+    }
+
+    fn session(cycles: u8) -> Session {
+        let task = Task::new("Write".into(), cycles, Durations::default());
+        Session::new(&task, at(0))
+    }
+
+    fn phases(transitions: &[Transition]) -> Vec<Option<Phase>> {
+        transitions.iter().map(|transition| transition.to).collect()
+    }
+
+    #[test]
+    fn nothing_ends_before_the_phase_end() {
+        let mut session = session(2);
+
+        assert!(session.advance(at(24)).is_empty());
+        assert_eq!(session.phase(), Phase::Work);
+        assert_eq!(session.remaining(at(24)), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn phases_alternate_with_a_long_break_every_fourth_block() {
+        let mut session = session(6);
+
+        // 4 × 25 min of work and 3 short breaks of 5 min
+        let transitions = session.advance(at(115));
+
+        assert_eq!(
+            phases(&transitions),
+            [
+                Some(Phase::ShortBreak),
+                Some(Phase::Work),
+                Some(Phase::ShortBreak),
+                Some(Phase::Work),
+                Some(Phase::ShortBreak),
+                Some(Phase::Work),
+                Some(Phase::LongBreak),
+            ]
+        );
+        assert_eq!(session.completed_cycles(), 4);
+        assert_eq!(session.phase_started_at(), at(115));
+    }
+
+    #[test]
+    fn catching_up_starts_each_phase_where_the_previous_ended() {
+        let mut session = session(4);
+
+        let transitions = session.advance(at(62));
+
+        assert_eq!(transitions.len(), 4);
+        let ends: Vec<_> = transitions.iter().map(|transition| transition.at).collect();
+        assert_eq!(ends, [at(25), at(30), at(55), at(60)]);
+        assert_eq!(session.phase(), Phase::Work);
+        assert_eq!(session.worked(at(62)), Duration::from_secs(120));
+    }
+
+    #[test]
+    fn pausing_pushes_the_phase_end_back() {
+        let mut session = session(2);
+
+        assert!(session.pause(at(10)));
+        assert!(!session.pause(at(11)));
+        assert!(session.advance(at(40)).is_empty());
+        assert_eq!(session.remaining(at(40)), Duration::from_secs(15 * 60));
+
+        assert!(session.resume(at(20)));
+        assert!(!session.resume(at(21)));
+        assert_eq!(session.phase_ends_at(), at(35));
+        assert_eq!(session.segment_started_at(), at(20));
+        assert_eq!(session.pauses().len(), 1);
+        assert!(session.advance(at(34)).is_empty());
+
+        let transitions = session.advance(at(35));
+        assert_eq!(phases(&transitions), [Some(Phase::ShortBreak)]);
+        assert_eq!(transitions[0].started_at, at(20));
+    }
+
+    #[test]
+    fn skipping_a_work_block_counts_it_as_done() {
+        let mut session = session(2);
+
+        let transition = session.skip(at(10)).unwrap();
+
+        assert_eq!(transition.from, Phase::Work);
+        assert_eq!(transition.to, Some(Phase::ShortBreak));
+        assert_eq!(session.completed_cycles(), 1);
+        assert_eq!(session.phase_ends_at(), at(15));
+    }
+
+    #[test]
+    fn skipping_resumes_a_paused_session() {
+        let mut session = session(2);
+        session.pause(at(5));
+
+        session.skip(at(10));
+
+        assert!(session.is_running());
+        assert_eq!(session.phase_started_at(), at(10));
+    }
+
+    #[test]
+    fn the_last_work_block_finishes_the_session() {
+        let mut session = session(2);
+
+        let transitions = session.advance(at(100));
+
+        assert_eq!(
+            phases(&transitions),
+            [Some(Phase::ShortBreak), Some(Phase::Work), None]
+        );
+        assert!(session.is_finished());
+        assert!(!session.is_running());
+        assert_eq!(session.completed_cycles(), 2);
+        assert_eq!(session.progress(at(100)), 100.);
+        assert!(session.advance(at(200)).is_empty());
+        assert!(session.skip(at(200)).is_none());
+    }
+
+    #[test]
+    fn stopping_returns_the_work_of_the_unfinished_block() {
+        let mut session = session(2);
+        session.pause(at(10));
+        session.resume(at(15));
+
+        assert_eq!(session.stop(at(20)), Duration::from_secs(15 * 60));
+        assert!(session.is_finished());
+    }
+
+    #[test]
+    fn stopping_during_a_break_returns_no_work() {
+        let mut session = session(2);
+        session.advance(at(27));
+
+        assert_eq!(session.stop(at(27)), Duration::ZERO);
+    }
+}
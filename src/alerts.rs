@@ -0,0 +1,290 @@
+//! Desktop notifications and sounds raised when a phase ends.
+//!
+//! Notifications go through the freedesktop notification service on the
+//! session bus, so any bus reachable from `DBUS_SESSION_BUS_ADDRESS` works.
+//! Sounds are played by the first audio player found on the system.
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::atomic::{AtomicU32, Ordering},
+    thread,
+};
+
+use trackit_core::{
+    settings::{Alerts, Sound},
+    storage, Durations, Phase, Transition,
+};
+use zbus::{blocking::Connection, proxy, zvariant::Value};
+
+/// The bell shipped with the app, written to the data directory on first use.
+const BELL: &[u8] = include_bytes!("../assets/sounds/bell.wav");
+/// The players tried, in order, to play a sound.
+const PLAYERS: &[&str] = &["pw-play", "paplay", "aplay", "afplay"];
+const APP_NAME: &str = "trackit";
+
+/// The id of the last notification shown, so the next one replaces it.
+static LAST_NOTIFICATION: AtomicU32 = AtomicU32::new(0);
+
+#[proxy(
+    interface = "org.freedesktop.Notifications",
+    default_service = "org.freedesktop.Notifications",
+    default_path = "/org/freedesktop/Notifications"
+)]
+trait Notifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: &[&str],
+        hints: HashMap<&str, Value<'_>>,
+        expire_timeout: i32,
+    ) -> zbus::Result<u32>;
+}
+
+/// Where notifications are shown, the session bus outside of tests.
+trait Notifier {
+    /// Shows a notification replacing the one with the `replaces` id, if still
+    /// shown, and returns its id.
+    fn notify(&self, replaces: u32, summary: &str, body: &str) -> zbus::Result<u32>;
+}
+
+/// The freedesktop notification service of the session bus.
+struct SessionBus;
+
+impl Notifier for SessionBus {
+    fn notify(&self, replaces: u32, summary: &str, body: &str) -> zbus::Result<u32> {
+        let connection = Connection::session()?;
+        let proxy = NotificationsProxyBlocking::new(&connection)?;
+
+        proxy.notify(
+            APP_NAME,
+            replaces,
+            "alarm-symbolic",
+            summary,
+            body,
+            &[],
+            HashMap::new(),
+            -1,
+        )
+    }
+}
+
+/// The transition to alert about among the `transitions` of one tick.
+///
+/// Only the last one alerts: after a suspend several phases may have ended at
+/// once, and only the one running now matters.
+pub fn alerted(transitions: &[Transition]) -> Option<Transition> {
+    transitions.last().copied()
+}
+
+/// Tells the user that `transition` happened to the session of the task named
+/// `task`, as configured in `alerts`.
+///
+/// Nothing blocks the caller, the alert is raised from its own thread.
+pub fn alert(alerts: &Alerts, task: &str, transition: &Transition, durations: &Durations) {
+    let alert = alerts.of(transition.from);
+    if !alert.notify && !alert.sound {
+        return;
+    }
+
+    let notification = alert.notify.then(|| describe(task, transition, durations));
+    let sound = alert.sound.then(|| alerts.sound.clone());
+
+    thread::spawn(move || {
+        if let Some((summary, body)) = notification {
+            if let Err(err) = notify(&SessionBus, &LAST_NOTIFICATION, &summary, &body) {
+                eprintln!("unable to show the notification: {err}");
+            }
+        }
+        if let Some(sound) = sound {
+            if let Err(err) = play(&sound) {
+                eprintln!("unable to play the alert sound: {err}");
+            }
+        }
+    });
+}
+
+/// Plays the `sound` once, to try it out.
+pub fn preview(sound: Sound) {
+    thread::spawn(move || {
+        if let Err(err) = play(&sound) {
+            eprintln!("unable to play the alert sound: {err}");
+        }
+    });
+}
+
+/// The summary and body of the notification of a `transition`.
+fn describe(task: &str, transition: &Transition, durations: &Durations) -> (String, String) {
+    let minutes = |phase: Phase| durations.of(phase).as_secs() / 60;
+
+    match (transition.from, transition.to) {
+        (Phase::Work, None) => ("All cycles done".into(), format!("{task} is complete")),
+        (Phase::Work, Some(next)) => (
+            "Work block done".into(),
+            format!(
+                "Take a {} of {} minutes",
+                next.to_string().to_lowercase(),
+                minutes(next)
+            ),
+        ),
+        (_, _) => (
+            "Break is over".into(),
+            format!("Back to {task} for {} minutes", minutes(Phase::Work)),
+        ),
+    }
+}
+
+/// Shows a notification through the `notifier`, replacing the `last` one shown.
+fn notify(
+    notifier: &impl Notifier,
+    last: &AtomicU32,
+    summary: &str,
+    body: &str,
+) -> zbus::Result<()> {
+    let id = notifier.notify(last.load(Ordering::Relaxed), summary, body)?;
+    last.store(id, Ordering::Relaxed);
+
+    Ok(())
+}
+
+/// Plays the `sound` with the first player available, until it's over.
+fn play(sound: &Sound) -> io::Result<()> {
+    let path = match sound {
+        Sound::Bell => bell()?,
+        Sound::Custom(path) => path.clone(),
+    };
+
+    for player in PLAYERS {
+        let child = Command::new(player)
+            .arg(&path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+
+        match child {
+            Ok(mut child) => return child.wait().map(|_| ()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "no audio player found",
+    ))
+}
+
+/// The path of the bundled bell, written out the first time it's needed.
+fn bell() -> io::Result<PathBuf> {
+    let dir = storage::data_dir().map_err(io::Error::other)?;
+    let path = dir.join("bell.wav");
+
+    if fs::read(&path).ok().as_deref() != Some(BELL) {
+        fs::create_dir_all(&dir)?;
+        fs::write(&path, BELL)?;
+    }
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use trackit_core::chrono::{Local, TimeZone};
+
+    use super::*;
+
+    /// Keeps the notifications shown, numbered from 1.
+    #[derive(Default)]
+    struct Fake {
+        shown: RefCell<Vec<(u32, String, String)>>,
+    }
+
+    impl Notifier for Fake {
+        fn notify(&self, replaces: u32, summary: &str, body: &str) -> zbus::Result<u32> {
+            let mut shown = self.shown.borrow_mut();
+            shown.push((replaces, summary.into(), body.into()));
+            Ok(shown.len() as u32)
+        }
+    }
+
+    fn transition(from: Phase, to: Option<Phase>) -> Transition {
+        let at = Local.with_ymd_and_hms(2024, 3, 4, 9, 25, 0).unwrap();
+        Transition {
+            from,
+            started_at: at,
+            to,
+            at,
+        }
+    }
+
+    fn durations() -> Durations {
+        Durations::from_minutes(25, 5, 15)
+    }
+
+    #[test]
+    fn the_end_of_a_work_block_announces_the_break() {
+        let (summary, body) = describe(
+            "Write",
+            &transition(Phase::Work, Some(Phase::LongBreak)),
+            &durations(),
+        );
+
+        assert_eq!(summary, "Work block done");
+        assert_eq!(body, "Take a long break of 15 minutes");
+    }
+
+    #[test]
+    fn the_end_of_a_break_calls_back_to_work() {
+        let (summary, body) = describe(
+            "Write",
+            &transition(Phase::ShortBreak, Some(Phase::Work)),
+            &durations(),
+        );
+
+        assert_eq!(summary, "Break is over");
+        assert_eq!(body, "Back to Write for 25 minutes");
+    }
+
+    #[test]
+    fn the_end_of_the_last_cycle_completes_the_task() {
+        let (summary, body) = describe("Write", &transition(Phase::Work, None), &durations());
+
+        assert_eq!(summary, "All cycles done");
+        assert_eq!(body, "Write is complete");
+    }
+
+    #[test]
+    fn only_the_last_transition_of_a_tick_alerts() {
+        let transitions = [
+            transition(Phase::Work, Some(Phase::ShortBreak)),
+            transition(Phase::ShortBreak, Some(Phase::Work)),
+        ];
+
+        assert_eq!(alerted(&transitions), Some(transitions[1]));
+        assert_eq!(alerted(&[]), None);
+    }
+
+    #[test]
+    fn a_notification_replaces_the_previous_one() {
+        let fake = Fake::default();
+        let last = AtomicU32::new(0);
+
+        notify(&fake, &last, "Work block done", "Take a break").unwrap();
+        notify(&fake, &last, "Break is over", "Back to work").unwrap();
+
+        let shown = fake.shown.borrow();
+        assert_eq!(shown[0].0, 0);
+        assert_eq!(shown[1], (1, "Break is over".into(), "Back to work".into()));
+        assert_eq!(last.load(Ordering::Relaxed), 2);
+    }
+}
@@ -0,0 +1,129 @@
+//! The idle time of the user, read from the session bus.
+//!
+//! GNOME exposes it through the Mutter idle monitor, KDE and others through the
+//! freedesktop screensaver interface. Both report milliseconds. The first one
+//! that answers is kept.
+
+use std::{io, time::Duration};
+
+use iced::{
+    futures::{SinkExt, Stream},
+    Subscription,
+};
+use trackit_core::idle::Backend;
+use zbus::{blocking::Connection, proxy};
+
+/// How often the idle time is read.
+const POLL: Duration = Duration::from_secs(5);
+
+#[proxy(
+    interface = "org.gnome.Mutter.IdleMonitor",
+    default_service = "org.gnome.Mutter.IdleMonitor",
+    default_path = "/org/gnome/Mutter/IdleMonitor/Core"
+)]
+trait IdleMonitor {
+    fn get_idletime(&self) -> zbus::Result<u64>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.ScreenSaver",
+    default_service = "org.freedesktop.ScreenSaver",
+    default_path = "/org/freedesktop/ScreenSaver"
+)]
+trait ScreenSaver {
+    fn get_session_idle_time(&self) -> zbus::Result<u32>;
+}
+
+/// The interfaces the idle time can be read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Interface {
+    Mutter,
+    ScreenSaver,
+}
+
+/// Reads the idle time on the session bus.
+#[derive(Default)]
+pub struct DBus {
+    connection: Option<Connection>,
+    /// The interface that answered last time.
+    interface: Option<Interface>,
+}
+
+impl DBus {
+    fn read(connection: &Connection, interface: Interface) -> zbus::Result<Duration> {
+        match interface {
+            Interface::Mutter => IdleMonitorProxyBlocking::new(connection)?
+                .get_idletime()
+                .map(Duration::from_millis),
+            Interface::ScreenSaver => ScreenSaverProxyBlocking::new(connection)?
+                .get_session_idle_time()
+                .map(|millis| Duration::from_millis(millis as u64)),
+        }
+    }
+}
+
+impl Backend for DBus {
+    fn idle_time(&mut self) -> io::Result<Duration> {
+        let connection = match &self.connection {
+            Some(connection) => connection.clone(),
+            None => Connection::session().map_err(io::Error::other)?,
+        };
+        self.connection = Some(connection.clone());
+
+        let interfaces = match self.interface {
+            Some(interface) => vec![interface],
+            None => vec![Interface::Mutter, Interface::ScreenSaver],
+        };
+        let mut last_err = None;
+        for interface in interfaces {
+            match Self::read(&connection, interface) {
+                Ok(idle) => {
+                    self.interface = Some(interface);
+                    return Ok(idle);
+                }
+                Err(err) => last_err = Some(err),
+            }
+        }
+
+        Err(io::Error::other(last_err.map_or_else(
+            || "no idle interface".to_string(),
+            |err| err.to_string(),
+        )))
+    }
+}
+
+/// Reads the idle time from the backend `B` every few seconds, until it fails.
+pub fn subscription<B: Backend + Default + 'static>() -> Subscription<Result<Duration, String>> {
+    Subscription::run(poll::<B>)
+}
+
+fn poll<B: Backend + Default + 'static>() -> impl Stream<Item = Result<Duration, String>> {
+    iced::stream::channel(1, |mut output| async move {
+        let mut backend = B::default();
+
+        loop {
+            tokio::time::sleep(POLL).await;
+            // the bus is only reached through blocking calls
+            let read = tokio::task::spawn_blocking(move || {
+                let idle = backend.idle_time();
+                (backend, idle)
+            })
+            .await;
+
+            let (next, idle) = match read {
+                Ok(read) => read,
+                Err(err) => {
+                    let _ = output.send(Err(err.to_string())).await;
+                    return;
+                }
+            };
+            backend = next;
+
+            let failed = idle.is_err();
+            let _ = output.send(idle.map_err(|err| err.to_string())).await;
+            if failed {
+                return;
+            }
+        }
+    })
+}
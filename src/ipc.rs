@@ -0,0 +1,176 @@
+//! The control socket of the app, see [`trackit_core::ipc`] for the protocol.
+//!
+//! Each connection is served on its own task. Every request is handed to the
+//! app as an [`Event`] and its connection waits until the app answers it.
+
+use std::{
+    fs::{self, DirBuilder},
+    io,
+    os::unix::fs::{DirBuilderExt, PermissionsExt},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use iced::{
+    futures::{
+        channel::{mpsc, oneshot},
+        SinkExt, Stream,
+    },
+    Subscription,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+};
+use trackit_core::ipc::{self, Call, Response, RpcError, Status};
+
+type Answer = Result<Option<Status>, RpcError>;
+
+/// How long accepting waits after a first failure, doubled on each failure in a
+/// row up to [`MAX_ACCEPT_BACKOFF`].
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_ACCEPT_BACKOFF: Duration = Duration::from_secs(10);
+
+/// A call received from the socket, waiting for its answer.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub call: Call,
+    responder: Arc<Mutex<Option<oneshot::Sender<Answer>>>>,
+}
+
+impl Request {
+    /// Answers the call, every answer after the first is ignored.
+    pub fn respond(&self, answer: Answer) {
+        let sender = self
+            .responder
+            .lock()
+            .ok()
+            .and_then(|mut sender| sender.take());
+        if let Some(sender) = sender {
+            // the client may have hung up already
+            let _ = sender.send(answer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Request(Request),
+    /// The socket couldn't be opened, the app can't be controlled remotely.
+    Failed(String),
+}
+
+/// Listens on the control socket for as long as the app runs.
+pub fn subscription() -> Subscription<Event> {
+    Subscription::run(listen)
+}
+
+fn listen() -> impl Stream<Item = Event> {
+    iced::stream::channel(100, |mut output| async move {
+        // dropped with the subscription, when the app exits
+        let (listener, _socket) = match bind().await {
+            Ok(bound) => bound,
+            Err(err) => {
+                let _ = output.send(Event::Failed(err.to_string())).await;
+                return;
+            }
+        };
+
+        let mut backoff = ACCEPT_BACKOFF;
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    backoff = ACCEPT_BACKOFF;
+                    tokio::spawn(serve(stream, output.clone()));
+                }
+                // out of file descriptors, say: it may take a while to clear up,
+                // retrying right away would only spin
+                Err(err) => {
+                    eprintln!("unable to accept a control connection: {err}");
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_ACCEPT_BACKOFF);
+                }
+            }
+        }
+    })
+}
+
+/// The socket file of this instance, removed when dropped.
+struct Socket(PathBuf);
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// Binds the socket, replacing the one left behind by a previous run.
+async fn bind() -> io::Result<(UnixListener, Socket)> {
+    let dir = ipc::socket_dir().map_err(io::Error::other)?;
+    let path = dir.join(ipc::SOCKET_FILE);
+
+    // only the owner may drive the timer, the directory keeps the others out
+    // before the socket has its own permissions
+    DirBuilder::new().recursive(true).mode(0o700).create(&dir)?;
+    fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))?;
+
+    if path.exists() {
+        if UnixStream::connect(&path).await.is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                "another instance is already listening",
+            ));
+        }
+        fs::remove_file(&path)?;
+    }
+
+    let listener = UnixListener::bind(&path)?;
+    let socket = Socket(path);
+    fs::set_permissions(&socket.0, fs::Permissions::from_mode(0o600))?;
+    Ok((listener, socket))
+}
+
+/// Answers the requests of a connection, one line at a time.
+async fn serve(stream: UnixStream, mut output: mpsc::Sender<Event>) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match ipc::Request::parse(&line) {
+            Ok(request) => {
+                let (sender, receiver) = oneshot::channel();
+                let event = Event::Request(Request {
+                    call: request.call,
+                    responder: Arc::new(Mutex::new(Some(sender))),
+                });
+                if output.send(event).await.is_err() {
+                    return;
+                }
+
+                let answer = receiver.await.unwrap_or_else(|_| {
+                    Err(RpcError::new(
+                        RpcError::INTERNAL_ERROR,
+                        "the request was dropped",
+                    ))
+                });
+                // a notification is carried out all the same, but not answered
+                match request.id {
+                    Some(id) => Response::answer(id, answer),
+                    None => continue,
+                }
+            }
+            Err(Some(response)) => response,
+            Err(None) => continue,
+        };
+
+        let line = response.to_line() + "\n";
+        if writer.write_all(line.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
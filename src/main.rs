@@ -2,41 +2,243 @@ use std::time::Duration;
 
 use atoms::widgets::radial_progress_bar;
 
+mod alerts;
+mod idle;
+#[cfg(unix)]
+mod ipc;
+mod undo;
 mod widgets;
 
 use iced::{
-    application, time,
+    alignment::Vertical,
+    application,
+    keyboard::{self, Key},
+    time,
     widget::{
-        button, column, container, row,
+        button, column, container, horizontal_space, row,
         rule::{self, Style},
-        text, vertical_rule,
+        scrollable, text, text_input, vertical_rule,
     },
     Length, Renderer, Subscription, Theme,
 };
-use trackit_core::Task;
-use widgets::{modal::Modal, tasks::Cards};
+#[cfg(unix)]
+use trackit_core::ipc::{Call, RpcError, Status};
+use trackit_core::{
+    chrono::{DateTime, Local},
+    format_clock, format_duration,
+    idle::{Choice, Detector, Event},
+    interruption::Kind,
+    project,
+    storage::{Data, Storage},
+    timeline::Change,
+    ActiveSession, NameError, Outcome, Overrun, Phase, Project, Session, SessionLog, Settings,
+    Task, TaskId, Transition,
+};
+use widgets::{
+    export::ExportDialog, goal::Progress, log::LogEditor, modal::Modal, stats::Dashboard,
+    tasks::Cards, toast::Toast,
+};
+
+use undo::{Edit, History};
 
-#[derive(Default)]
 pub struct App {
+    /// Where the data is saved, `None` when it couldn't be loaded.
+    storage: Option<Storage>,
+    /// The log of every interval, `None` when it couldn't be opened.
+    log: Option<SessionLog>,
+    storage_error: Option<String>,
+    /// Why the control socket couldn't be opened.
+    ipc_error: Option<String>,
+    settings: Settings,
     modal: Modal,
     show_modal: bool,
-    progress: f32,
-    should_stop: bool,
-    started_task: Option<Task>,
+    show_settings: bool,
+    screen: Screen,
+    dashboard: Dashboard,
+    /// Today's progress towards the daily goal.
+    goal: Progress,
+    /// The intervals of a day, to correct the log by hand.
+    log_editor: LogEditor,
+    /// The export dialog, `None` when it's closed.
+    export: Option<ExportDialog>,
+    active: Option<ActiveSession>,
+    projects: Vec<Project>,
+    /// The edits of the task list that can be undone.
+    history: History,
+    toast: Option<Toast>,
+    /// The note of the next interruption.
+    interruption_note: String,
+    /// The phases that ended while the app was closed or the machine asleep,
+    /// until dismissed.
+    overrun: Option<Overrun>,
+    /// When the data was last written, the running session leaves a heartbeat.
+    saved_at: DateTime<Local>,
+    /// Watches the idle time of the user while a session runs.
+    idle: Detector,
+    /// The span the user was away, from and to, until they tell what it was.
+    away: Option<(DateTime<Local>, DateTime<Local>)>,
+    /// Why the idle time couldn't be read.
+    idle_error: Option<String>,
+    /// The instant of the last tick, everything time related is rendered from it.
+    now: DateTime<Local>,
     cards: Cards,
 }
 
+impl App {
+    /// Creates the app from the data saved on disk.
+    ///
+    /// If the data can't be read, the app starts empty and never saves, so the
+    /// file is not overwritten.
+    pub fn new() -> (Self, iced::Task<Message>) {
+        let (storage, mut data, storage_error) =
+            match Storage::open_default().and_then(|storage| Ok((storage.load()?, storage))) {
+                Ok((data, storage)) => (Some(storage), data, None),
+                Err(err) => (None, Data::default(), Some(err.to_string())),
+            };
+        let (mut log, mut storage_error) = match SessionLog::open_default() {
+            Ok(log) => (Some(log), storage_error),
+            Err(err) => (None, storage_error.or(Some(err.to_string()))),
+        };
+        // a file from before ids existed, its tasks must be saved with the new ids
+        let migrated = !data.migrated.is_empty();
+        let mut toast = None;
+        if let Some(log) = log.as_mut().filter(|_| migrated) {
+            match log.link_migrated(&mut data) {
+                Ok(ambiguous) if !ambiguous.is_empty() => {
+                    toast = Some(Toast::notice(
+                        format!(
+                            "The history of {} is shared by tasks of the same name, it's left unassigned",
+                            ambiguous.join(", ")
+                        ),
+                        Local::now(),
+                    ))
+                }
+                Ok(_) => {}
+                Err(err) => storage_error = Some(err.to_string()),
+            }
+        }
+
+        let mut app = Self {
+            storage,
+            log,
+            storage_error,
+            ipc_error: None,
+            modal: Modal::new(data.settings.durations),
+            idle: Detector::new(data.settings.idle.threshold),
+            settings: data.settings,
+            show_modal: false,
+            show_settings: false,
+            screen: Screen::Timer,
+            dashboard: Dashboard::default(),
+            goal: Progress::default(),
+            log_editor: LogEditor::new(Local::now().date_naive()),
+            export: None,
+            active: data.active,
+            projects: data.projects,
+            history: History::default(),
+            toast,
+            interruption_note: String::new(),
+            overrun: None,
+            saved_at: Local::now(),
+            away: None,
+            idle_error: None,
+            now: Local::now(),
+            cards: Cards::from_tasks(data.tasks),
+        };
+        if migrated {
+            app.save();
+        }
+        // catch up with the time spent while the app was closed
+        app.advance_session();
+        app.refresh_dashboard();
+        // the user left while idle, and is obviously back
+        app.away = app
+            .active
+            .as_ref()
+            .and_then(|active| active.idle_since)
+            .map(|since| (since, app.now));
+
+        (app, iced::Task::none())
+    }
+}
+
+/// The screens shown next to the task list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Screen {
+    Timer,
+    Stats,
+    Log,
+    Archive,
+}
+
 /// The Message enum for the app
 #[derive(Debug, Clone)]
 pub enum Message {
     Tick,
-    Stop,
+    Pause,
     Resume,
+    Skip,
+    Stop,
     Restart,
+    /// Notes an interruption of the running work block, with the typed note.
+    Interrupt(Kind),
+    InterruptionNoteChanged(String),
+    DismissOverrun,
+    /// The idle time of the user, or why it couldn't be read.
+    IdleTime(Result<Duration, String>),
+    /// The user told what the span they were away was.
+    IdleSettled(Choice),
+    #[cfg(unix)]
+    Ipc(ipc::Event),
     Modal(widgets::modal::Message),
     Card(widgets::tasks::Message),
+    Settings(widgets::settings::Message),
+    Export(widgets::export::Message),
+    Archive(widgets::archive::Message),
+    Log(widgets::log::Message),
+    Undo,
+    Redo,
+    DismissToast,
     OpenModal,
+    OpenExport,
+    OpenSettings,
     CloseModal,
+    ShowScreen(Screen),
+}
+
+/// How often the running session is saved, so a relaunch can tell how long the
+/// app was gone.
+const HEARTBEAT: Duration = Duration::from_secs(30);
+
+/// The size of the timer ring, in logical pixels.
+///
+/// The ring is laid out at this fixed size, so it's also the size it's drawn
+/// at, unless the window is too narrow for it. A ring squeezed smaller moves by
+/// fewer pixels, the ticks derived from this size are then just more than needed.
+const RING_SIZE: f32 = 100.;
+/// The shortest time between two ticks, about a frame.
+const MIN_TICK: Duration = Duration::from_millis(16);
+
+/// Describes the phases that ended while nobody watched the timer.
+fn overrun_message(overrun: &Overrun) -> String {
+    let work = overrun.work_blocks();
+    let breaks = overrun.phases.len() - work;
+    let count = |count: usize, what: &str| match count {
+        0 => None,
+        1 => Some(format!("1 {what}")),
+        count => Some(format!("{count} {what}s")),
+    };
+    let ended = [count(work, "work block"), count(breaks, "break")]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" and ");
+    format!(
+        "Away from {} to {} ({}), {ended} ended meanwhile",
+        overrun.since.format("%H:%M"),
+        overrun.until.format("%H:%M"),
+        format_duration(overrun.duration())
+    )
 }
 
 type Element<'a, Message> = iced::Element<'a, Message, Theme, Renderer>;
@@ -44,8 +246,12 @@ type Element<'a, Message> = iced::Element<'a, Message, Theme, Renderer>;
 impl App {
     const TITLE: &str = "Demo app";
 
-    pub fn view(&self) -> Element<Message> {
-        let task_msg = match &self.started_task {
+    pub fn view(&self) -> Element<'_, Message> {
+        let task_msg = match self
+            .active
+            .as_ref()
+            .and_then(|active| self.cards.get(active.task))
+        {
             Some(task) => format!(
                 "Task {} started at: {}",
                 task.name,
@@ -54,31 +260,165 @@ impl App {
             None => "Not active task".into(),
         };
 
-        let content: Element<_> = column![
+        let (progress, clock, phase_msg) = match self.session() {
+            Some(session) if session.is_finished() => {
+                (100., String::new(), "All cycles done".to_string())
+            }
+            Some(session) => (
+                session.progress(self.now),
+                format_clock(session.remaining(self.now)),
+                format!(
+                    "{} ({}/{} cycles done){}",
+                    session.phase(),
+                    session.completed_cycles(),
+                    session.cycles(),
+                    if session.is_paused() {
+                        " · paused"
+                    } else {
+                        ""
+                    }
+                ),
+            ),
+            None => (0., String::new(), String::new()),
+        };
+
+        let timer: Element<_> = column![
             text(task_msg),
+            text(phase_msg).style(text::secondary),
             container(
-                radial_progress_bar(self.progress, "")
-                    .width(100)
-                    .height(100)
+                row![
+                    radial_progress_bar(progress, clock)
+                        .paused(self.session().is_some_and(Session::is_paused))
+                        .width(RING_SIZE)
+                        .height(RING_SIZE),
+                    self.goal.view(),
+                ]
+                .spacing(32)
+                .align_y(Vertical::Center)
             )
             .width(Length::Fill)
             .center(Length::Fill),
             row![
-                button("Resume").on_press(Message::Resume),
-                button("Stop").on_press(Message::Stop),
-                button("Restart").on_press(Message::Restart),
-                button("Open / Close").on_press(Message::OpenModal)
+                if self.session().is_some_and(Session::is_paused) {
+                    button("Resume").on_press(Message::Resume)
+                } else {
+                    button("Pause").on_press_maybe(
+                        self.session()
+                            .is_some_and(Session::is_running)
+                            .then_some(Message::Pause),
+                    )
+                },
+                button("Skip").on_press_maybe(
+                    self.session()
+                        .is_some_and(|session| !session.is_finished())
+                        .then_some(Message::Skip)
+                ),
+                button("Stop")
+                    .style(button::secondary)
+                    .on_press_maybe(self.active.is_some().then_some(Message::Stop)),
+                button("Restart").on_press_maybe(self.active.is_some().then_some(Message::Restart)),
+                button("Open / Close").on_press(Message::OpenModal),
+                button("Settings")
+                    .style(button::secondary)
+                    .on_press(Message::OpenSettings)
             ]
             .spacing(5.)
             .width(Length::Fill)
         ]
-        .width(Length::FillPortion(4))
+        .push_maybe(self.overrun.as_ref().map(|overrun| {
+            row![
+                text(overrun_message(overrun)).style(text::danger),
+                horizontal_space(),
+                button("Dismiss")
+                    .style(button::text)
+                    .on_press(Message::DismissOverrun),
+            ]
+            .align_y(Vertical::Center)
+        }))
+        .push_maybe(self.is_working().then(|| {
+            row![
+                text_input("What interrupted you?", &self.interruption_note)
+                    .on_input(Message::InterruptionNoteChanged),
+                button("Internal")
+                    .style(button::secondary)
+                    .on_press(Message::Interrupt(Kind::Internal)),
+                button("External")
+                    .style(button::secondary)
+                    .on_press(Message::Interrupt(Kind::External)),
+            ]
+            .spacing(5.)
+        }))
+        .push_maybe(
+            self.active
+                .as_ref()
+                .and_then(|active| self.cards.get(active.task))
+                .filter(|task| !task.notes.trim().is_empty() || !task.subtasks.is_empty())
+                .map(|task| {
+                    container(scrollable(widgets::notes::view(task).map(Message::Card)))
+                        .max_height(240)
+                }),
+        )
+        .push_maybe(
+            self.storage_error
+                .as_ref()
+                .map(|err| text(format!("Data won't be saved: {err}")).style(text::danger)),
+        )
+        .push_maybe(
+            self.ipc_error
+                .as_ref()
+                .map(|err| text(format!("Remote control is off: {err}")).style(text::danger)),
+        )
+        .push_maybe(
+            self.idle_error
+                .as_ref()
+                .filter(|_| self.settings.idle.enabled)
+                .map(|err| text(format!("Idle detection is off: {err}")).style(text::danger)),
+        )
+        .width(Length::Fill)
         .height(Length::Fill)
         .into();
 
+        let tab = |label, screen| {
+            button(label)
+                .style(if self.screen == screen {
+                    button::primary
+                } else {
+                    button::text
+                })
+                .on_press(Message::ShowScreen(screen))
+        };
+        let content = column![
+            row![
+                tab("Timer", Screen::Timer),
+                tab("Stats", Screen::Stats),
+                tab("Log", Screen::Log),
+                tab("Archive", Screen::Archive),
+                horizontal_space(),
+                button("Export")
+                    .style(button::secondary)
+                    .on_press_maybe(self.log.is_some().then_some(Message::OpenExport))
+            ]
+            .spacing(5.),
+            match self.screen {
+                Screen::Timer => timer,
+                Screen::Stats => self.dashboard.view(),
+                Screen::Log => self
+                    .log_editor
+                    .view(&self.cards.tasks(), &self.projects)
+                    .map(Message::Log),
+                Screen::Archive => widgets::archive::view(&self.cards.tasks(), &self.projects)
+                    .map(Message::Archive),
+            }
+        ]
+        .spacing(5.)
+        .width(Length::FillPortion(4))
+        .height(Length::Fill);
+
         let content: Element<_> = row![
             row![
-                self.cards.view().map(Message::Card),
+                self.cards
+                    .view(self.active.as_ref(), &self.projects, self.now)
+                    .map(Message::Card),
                 vertical_rule(1).style(|theme: &Theme| Style {
                     color: theme
                         .extended_palette()
@@ -94,8 +434,19 @@ impl App {
         .into();
         // let content = content.explain(Color::from_rgb(255., 0., 0.));
 
-        if self.show_modal {
-            self.modal.view(content)
+        let content = match &self.toast {
+            Some(toast) => toast.view(content),
+            None => content,
+        };
+
+        if let Some((since, until)) = self.away.filter(|_| self.is_idle()) {
+            widgets::away::view(since, until, content)
+        } else if self.show_modal {
+            self.modal.view(&self.projects, content)
+        } else if self.show_settings {
+            widgets::settings::view(&self.settings, content)
+        } else if let Some(export) = &self.export {
+            export.view(&self.cards.tasks(), &self.projects, content)
         } else {
             content
         }
@@ -103,20 +454,111 @@ impl App {
 
     pub fn update(&mut self, msg: Message) {
         match msg {
-            Message::Tick => self.progress = (self.progress + 0.1).clamp(0.0, 100.),
-            Message::Stop => self.should_stop = true,
-            Message::Resume => self.should_stop = false,
+            Message::Tick => self.tick(),
+            Message::Interrupt(kind) => {
+                let note = self.interruption_note.clone();
+                if self.interrupt(kind, Some(note)) {
+                    self.interruption_note.clear();
+                }
+            }
+            Message::InterruptionNoteChanged(note) => self.interruption_note = note,
+            Message::DismissOverrun => self.overrun = None,
+            Message::IdleTime(Ok(idle)) => {
+                self.now = Local::now();
+                match self.idle.update(idle, self.now) {
+                    Some(Event::Idle { since }) => self.hold_session(since),
+                    Some(Event::Back { until, .. }) => {
+                        // the session may have been held from later on
+                        let since = self.active.as_ref().and_then(|active| active.idle_since);
+                        self.away = since.map(|since| (since, until.max(since)));
+                    }
+                    None => {}
+                }
+            }
+            Message::IdleTime(Err(err)) => self.idle_error = Some(err),
+            Message::IdleSettled(choice) => self.settle_idle(choice),
+            Message::Pause => self.pause_session(),
+            Message::Resume => self.resume_session(),
+            Message::Skip => self.skip_session(),
+            Message::Stop => {
+                self.now = Local::now();
+                self.stop_session("stopped");
+                self.active = None;
+                self.save();
+            }
             Message::Restart => {
-                self.should_stop = false;
-                self.progress = 0.
+                self.now = Local::now();
+                self.stop_session("restarted");
+                self.active = self
+                    .active
+                    .as_ref()
+                    .and_then(|active| self.cards.get(active.task))
+                    .map(|task| ActiveSession::new(task, self.now));
+                self.save();
+            }
+            #[cfg(unix)]
+            Message::Ipc(ipc::Event::Request(request)) => {
+                let answer = self.handle_call(&request.call);
+                request.respond(answer);
             }
+            #[cfg(unix)]
+            Message::Ipc(ipc::Event::Failed(err)) => self.ipc_error = Some(err),
             Message::Modal(widgets::modal::Message::CreateNewTask) => {
-                if self.modal.task_name.is_empty() {
-                    self.modal.set_error("You must provide a text for the task");
+                let name = match self.check_modal(None) {
+                    Ok(name) => name,
+                    Err(err) => {
+                        self.modal.set_error(err);
+                        return;
+                    }
+                };
+                let durations = self.modal.durations();
+                if self.modal.save_as_default {
+                    self.settings.durations = durations;
+                    self.modal.set_defaults(durations);
+                }
+                let mut task = Task::new(name, self.modal.cycles, durations);
+                if !self.modal.project.trim().is_empty() {
+                    task.project = Some(project::ensure(&mut self.projects, &self.modal.project));
+                }
+                task.tags = project::parse_tags(&self.modal.tags);
+                task.notes = self.modal.notes();
+                task.subtasks = self.modal.subtasks.clone();
+                let index = self.cards.order().len();
+                self.edit(Edit::Create { task, index });
+                self.modal.reset();
+                self.show_modal = false;
+            }
+            Message::Modal(widgets::modal::Message::SaveTask) => {
+                let Some(id) = self.modal.editing else {
                     return;
                 };
-                let task = Task::new(self.modal.task_name.clone(), self.modal.cycles);
-                self.cards.add_card(task);
+                let name = match self.check_modal(Some(id)) {
+                    Ok(name) => name,
+                    Err(err) => {
+                        self.modal.set_error(err);
+                        return;
+                    }
+                };
+                let cycles = self.modal.cycles;
+                let durations = self.modal.durations();
+                if self.modal.save_as_default {
+                    self.settings.durations = durations;
+                    self.modal.set_defaults(durations);
+                }
+                let project = (!self.modal.project.trim().is_empty())
+                    .then(|| project::ensure(&mut self.projects, &self.modal.project));
+                let tags = project::parse_tags(&self.modal.tags);
+                let notes = self.modal.notes();
+                let subtasks = self.modal.subtasks.clone();
+                self.update_task(id, |task| {
+                    task.name = name;
+                    task.cycles = cycles;
+                    task.durations = durations;
+                    task.project = project;
+                    task.tags = tags;
+                    task.notes = notes;
+                    task.subtasks = subtasks;
+                });
                 self.modal.reset();
                 self.show_modal = false;
             }
@@ -126,56 +568,608 @@ impl App {
             }
             Message::Modal(msg) => self.modal.update(msg),
             Message::OpenModal => self.show_modal = true,
-            Message::Card(msg @ widgets::tasks::Message::Delete(index)) => {
-                self.remove_task(index);
+            Message::OpenSettings => self.show_settings = true,
+            Message::OpenExport => self.export = Some(ExportDialog::new(self.now.date_naive())),
+            Message::Export(widgets::export::Message::Close) => self.export = None,
+            Message::Export(widgets::export::Message::Export) => {
+                if let (Some(export), Some(log)) = (&mut self.export, &self.log) {
+                    export.export(log, &self.cards.tasks(), &self.projects);
+                }
+            }
+            Message::Export(msg) => {
+                if let Some(export) = &mut self.export {
+                    export.update(msg);
+                }
+            }
+            Message::ShowScreen(screen) => {
+                self.screen = screen;
+                self.refresh_dashboard();
+            }
+            Message::Settings(widgets::settings::Message::Close) => {
+                self.show_settings = false;
+                self.idle.set_threshold(self.settings.idle.threshold);
+                self.refresh_dashboard();
+                self.save();
+            }
+            Message::Settings(widgets::settings::Message::PreviewSound) => {
+                alerts::preview(self.settings.alerts.sound.clone())
+            }
+            Message::Settings(msg) => widgets::settings::update(&mut self.settings, msg),
+            Message::Card(widgets::tasks::Message::Delete(id)) => {
+                if let Some(edit) = self.delete_edit(id) {
+                    self.edit(edit);
+                    self.toast = Some(Toast::new("Task deleted", Local::now()));
+                }
+            }
+            Message::Card(widgets::tasks::Message::Edit(id)) => {
+                if let Some(task) = self.cards.get(id) {
+                    self.modal.edit(task, &self.projects);
+                    self.show_modal = true;
+                }
+            }
+            Message::Card(widgets::tasks::Message::SubtaskToggled(id, index, done)) => self
+                .update_task(id, |task| {
+                    if let Some(subtask) = task.subtasks.get_mut(index) {
+                        subtask.done = done;
+                    }
+                }),
+            Message::Card(widgets::tasks::Message::Start(id))
+                if self.is_active(id) && self.session().is_some_and(Session::is_paused) =>
+            {
+                self.resume_session()
+            }
+            Message::Card(msg @ widgets::tasks::Message::Start(id)) => {
+                // we ensure that the `card.task.started_at` is `Some(...)`
                 self.cards.update(msg);
+                self.start_task(id);
+                self.save();
             }
-            Message::Card(msg @ widgets::tasks::Message::Start(index)) => {
-                // we ensure that the `card.started_task` is `Some(...)`
+            Message::Card(widgets::tasks::Message::Stop(id)) => {
+                if self.is_active(id) {
+                    self.update(Message::Stop);
+                }
+            }
+            Message::Card(widgets::tasks::Message::Complete(id)) => {
+                if self.is_active(id) {
+                    self.now = Local::now();
+                    self.stop_session("completed");
+                    self.active = None;
+                }
+                let now = Local::now();
+                self.update_task(id, |task| task.complete(now));
+            }
+            Message::Card(widgets::tasks::Message::Archive(id)) => {
+                self.update_task(id, Task::archive)
+            }
+            Message::Archive(widgets::archive::Message::Restore(id)) => {
+                self.update_task(id, Task::restore)
+            }
+            Message::Archive(widgets::archive::Message::Purge(id)) => {
+                self.update(Message::Card(widgets::tasks::Message::Delete(id)))
+            }
+            Message::Log(msg) => {
+                if let Some(change) = self.log_editor.update(msg, &self.cards.tasks()) {
+                    self.change_log(change);
+                }
+                self.log_editor.load(self.log.as_ref());
+            }
+            Message::Archive(widgets::archive::Message::PurgeAll) => {
+                // the last first, so the index of every deletion stays right
+                let purged: Vec<_> = self
+                    .cards
+                    .tasks()
+                    .into_iter()
+                    .rev()
+                    .filter(Task::is_archived)
+                    .filter_map(|task| self.delete_edit(task.id))
+                    .collect();
+                if !purged.is_empty() {
+                    let count = purged.len();
+                    self.edit(Edit::Batch(purged));
+                    self.toast = Some(Toast::new(format!("{count} tasks purged"), Local::now()));
+                }
+            }
+            Message::Card(msg @ widgets::tasks::Message::Reorder(_)) => {
+                let before = self.cards.order();
                 self.cards.update(msg);
-                self.start_task(index);
+                let after = self.cards.order();
+                if before != after {
+                    self.history.push(Edit::Reorder { before, after });
+                    self.save();
+                }
+            }
+            Message::Undo => {
+                if let Some(edit) = self.history.undo() {
+                    self.apply_edit(&edit);
+                }
+                self.toast = None;
+            }
+            Message::Redo => {
+                if let Some(edit) = self.history.redo() {
+                    self.apply_edit(&edit);
+                }
+                self.toast = None;
             }
+            Message::DismissToast => self.toast = None,
             Message::Card(msg) => self.cards.update(msg),
         }
     }
 
-    fn remove_task(&mut self, index: u8) {
-        if let Some(task) = &self.started_task {
-            let card = self
-                .cards
-                .elements()
-                .get(index as usize)
-                .expect("Index expected");
-            if *task == card.task {
-                self.started_task = None;
+    /// Applies an `edit` made by the user to the task list, and records it so
+    /// it can be undone.
+    fn edit(&mut self, edit: Edit) {
+        self.apply_edit(&edit);
+        self.history.push(edit);
+    }
+
+    /// Applies an `edit` to the task list, ending the session of a removed task.
+    fn apply_edit(&mut self, edit: &Edit) {
+        for id in edit.removed() {
+            self.remove_task(id);
+        }
+        edit.apply(&mut self.cards);
+        self.save();
+    }
+
+    /// Changes the task with the given `id` through `f`, as an undoable edit.
+    fn update_task(&mut self, id: TaskId, f: impl FnOnce(&mut Task)) {
+        let Some(before) = self.cards.get(id).cloned() else {
+            return;
+        };
+        let mut after = before.clone();
+        f(&mut after);
+        self.edit(Edit::Update {
+            before: Box::new(before),
+            after: Box::new(after),
+        });
+    }
+
+    /// The edit deleting the task with the given `id`, its session stopped
+    /// first so the work time it accounts is kept if the deletion is undone.
+    fn delete_edit(&mut self, id: TaskId) -> Option<Edit> {
+        self.remove_task(id);
+        let index = self.cards.order().iter().position(|other| *other == id)?;
+        let task = self.cards.get(id)?.clone();
+        Some(Edit::Delete { task, index })
+    }
+
+    /// The session running right now.
+    fn session(&self) -> Option<&Session> {
+        self.active.as_ref().map(|active| &active.session)
+    }
+
+    /// Whether the task with the given `id` is the one running.
+    fn is_active(&self, id: TaskId) -> bool {
+        self.active.as_ref().is_some_and(|active| active.task == id)
+    }
+
+    /// Whether a work block is running.
+    fn is_working(&self) -> bool {
+        self.session()
+            .is_some_and(|session| session.is_running() && session.phase() == Phase::Work)
+    }
+
+    /// Notes an interruption of the running work block in the log, returns
+    /// `false` if there's none.
+    fn interrupt(&mut self, kind: Kind, note: Option<String>) -> bool {
+        self.tick();
+        let Some(active) = &mut self.active else {
+            return false;
+        };
+        let Some(task) = self.cards.get(active.task) else {
+            return false;
+        };
+        let Some(mut interruption) = active.interrupt(task, kind, note, self.now) else {
+            return false;
+        };
+
+        if let Some(log) = &self.log {
+            if let Err(err) = log.record_interruption(&mut interruption) {
+                self.storage_error = Some(err.to_string());
+            }
+        }
+        self.refresh_dashboard();
+        self.save();
+        true
+    }
+
+    /// Whether the session is held, the user being away.
+    fn is_idle(&self) -> bool {
+        self.active.as_ref().is_some_and(ActiveSession::is_idle)
+    }
+
+    /// Holds the running session from `since`, when the user went idle.
+    fn hold_session(&mut self, since: DateTime<Local>) {
+        let Some(active) = &mut self.active else {
+            return;
+        };
+        let Some(task) = self.cards.get(active.task) else {
+            return;
+        };
+
+        let outcome = active.idle(task, since, self.now);
+        self.apply(outcome);
+        self.save();
+    }
+
+    /// Ends the span the user was away, as they `choice`d.
+    fn settle_idle(&mut self, choice: Choice) {
+        self.now = Local::now();
+        let until = self.away.take().map_or(self.now, |(_, until)| until);
+        let Some(active) = &mut self.active else {
+            return;
+        };
+        let Some(task) = self.cards.get(active.task) else {
+            return;
+        };
+
+        let outcome = active.back(task, choice, until, self.now);
+        self.apply(outcome);
+        self.save();
+    }
+
+    /// Moves the session up to the current time, alerting the user if a phase ended.
+    fn tick(&mut self) {
+        self.now = Local::now();
+        if self
+            .toast
+            .as_ref()
+            .is_some_and(|toast| toast.is_expired(self.now))
+        {
+            self.toast = None;
+        }
+        if self.goal.is_stale(self.now.date_naive()) {
+            self.refresh_dashboard();
+        }
+        let transition = self.advance_session();
+        if let Some(active) = &mut self.active {
+            active.watch(self.now);
+            let heartbeat = (self.now - self.saved_at).to_std().unwrap_or_default();
+            if active.session.is_running() && heartbeat >= HEARTBEAT {
+                self.save();
+            }
+        }
+        let Some(transition) = transition else {
+            return;
+        };
+
+        let Some(active) = &self.active else {
+            return;
+        };
+        if let Some(task) = self.cards.get(active.task) {
+            alerts::alert(
+                &self.settings.alerts,
+                &task.name,
+                &transition,
+                active.session.durations(),
+            );
+        }
+    }
+
+    /// Moves the session up to `now`, logging and accounting every finished phase.
+    ///
+    /// Returns the transition to alert about, if any.
+    fn advance_session(&mut self) -> Option<Transition> {
+        let active = self.active.as_mut()?;
+        let task = self.cards.get(active.task)?;
+
+        let outcome = active.advance(task, self.now);
+        let last = alerts::alerted(&outcome.transitions);
+        self.apply(outcome);
+
+        last
+    }
+
+    fn pause_session(&mut self) {
+        self.now = Local::now();
+        let Some(active) = &mut self.active else {
+            return;
+        };
+        let Some(task) = self.cards.get(active.task) else {
+            return;
+        };
+
+        let outcome = active.pause(task, self.now);
+        self.apply(outcome);
+        self.save();
+    }
+
+    fn resume_session(&mut self) {
+        self.now = Local::now();
+        if let Some(active) = &mut self.active {
+            active.resume(self.now);
+        }
+        self.save();
+    }
+
+    fn skip_session(&mut self) {
+        self.now = Local::now();
+        let Some(active) = &mut self.active else {
+            return;
+        };
+        let Some(task) = self.cards.get(active.task) else {
+            return;
+        };
+
+        let outcome = active.skip(task, self.now);
+        self.apply(outcome);
+        self.save();
+    }
+
+    /// Ends the running session, logging its current phase as cut short by `reason`.
+    fn stop_session(&mut self, reason: &str) {
+        let Some(active) = &mut self.active else {
+            return;
+        };
+        let Some(task) = self.cards.get(active.task) else {
+            return;
+        };
+
+        let outcome = active.stop(task, self.now, reason);
+        self.apply(outcome);
+    }
+
+    /// Accounts the `outcome` of driving the session to its task and the log.
+    fn apply(&mut self, mut outcome: Outcome) {
+        if outcome.is_empty() {
+            return;
+        }
+        if let Some(overrun) = outcome.overrun.take() {
+            self.overrun = Some(overrun);
+        }
+
+        let task = self
+            .active
+            .as_ref()
+            .and_then(|active| self.cards.get_mut(active.task));
+        if let Some(task) = task {
+            let before = task.clone();
+            if let Err(err) = outcome.apply(task, self.log.as_ref()) {
+                self.storage_error = Some(err.to_string());
+            }
+            // the session completing its task can be undone on its own
+            if task.state != before.state {
+                self.history.push(Edit::Update {
+                    before: Box::new(before),
+                    after: Box::new(task.clone()),
+                });
+            }
+        }
+        self.refresh_dashboard();
+        self.save();
+    }
+
+    /// Corrects the log by hand, the elapsed time of the tasks following.
+    fn change_log(&mut self, change: Change) {
+        self.now = Local::now();
+        let until = self.logged_until();
+        let Some(log) = &mut self.log else {
+            return;
+        };
+
+        match change.apply(log, until) {
+            Ok(applied) => {
+                for id in applied.tasks() {
+                    if let Some(task) = self.cards.get_mut(id) {
+                        applied.adjust(task);
+                    }
+                }
+                self.log_editor.applied();
+                self.refresh_dashboard();
+                self.save();
             }
+            Err(err) => self.log_editor.set_error(err.to_string()),
+        }
+    }
+
+    /// The instant the log is complete up to, the session logs what comes after.
+    fn logged_until(&self) -> DateTime<Local> {
+        match &self.active {
+            Some(active) if active.session.is_running() => active.session.segment_started_at(),
+            // a held session may still log the idle span
+            Some(active) => active.idle_since.unwrap_or(self.now),
+            None => self.now,
+        }
+    }
+
+    /// Rebuilds the progress towards the daily goal from the log, the statistics
+    /// and the day's intervals if they're on screen.
+    fn refresh_dashboard(&mut self) {
+        let today = self.now.date_naive();
+        self.goal = Progress::new(self.log.as_ref(), self.settings.goal, today);
+        if self.screen == Screen::Log {
+            self.log_editor.load(self.log.as_ref());
+        }
+        if self.screen == Screen::Stats {
+            self.dashboard = Dashboard::new(
+                self.log.as_ref(),
+                &self.cards.tasks(),
+                &self.projects,
+                self.settings.goal,
+                today,
+            );
+        }
+    }
+
+    /// Writes the tasks, settings and running session to disk.
+    fn save(&mut self) {
+        let Some(storage) = &self.storage else {
+            return;
+        };
+
+        let data = Data {
+            tasks: self.cards.tasks(),
+            projects: self.projects.clone(),
+            settings: self.settings.clone(),
+            active: self.active.clone(),
+            migrated: Vec::new(),
+        };
+        self.storage_error = storage.save(&data).err().map(|err| err.to_string());
+        self.saved_at = Local::now();
+    }
+
+    fn remove_task(&mut self, id: TaskId) {
+        if self.is_active(id) {
+            self.now = Local::now();
+            self.stop_session("deleted");
+            self.active = None;
         }
     }
 
-    fn start_task(&mut self, index: u8) {
-        let card = self
+    fn start_task(&mut self, id: TaskId) {
+        if self.is_active(id) {
+            return;
+        }
+
+        self.now = Local::now();
+        self.stop_session("switched task");
+        self.active = self
             .cards
-            .mut_elements()
-            .get_mut(index as usize)
-            .expect("Index expected");
+            .get(id)
+            .map(|task| ActiveSession::new(task, self.now));
+    }
 
-        if let Some(started) = &self.started_task {
-            if card.task == *started {
-                return;
-            };
+    /// Checks the task described by the modal, a new one or the `editing` one,
+    /// and returns its trimmed name.
+    fn check_modal(&self, editing: Option<TaskId>) -> Result<String, String> {
+        let name = trackit_core::check_name(&self.cards.tasks(), &self.modal.task_name, editing)
+            .map_err(|err| match err {
+                NameError::Empty => "You must provide a text for the task".to_string(),
+                NameError::Taken(name) => format!("A task named `{name}` already exists"),
+            })?;
+        if editing.is_some_and(|id| self.is_active(id))
+            && self
+                .session()
+                .is_some_and(|session| session.completed_cycles() > self.modal.cycles)
+        {
+            return Err("The task already went through more cycles than that".into());
+        }
+
+        Ok(name)
+    }
+
+    /// Runs a `call` received from the control socket.
+    #[cfg(unix)]
+    fn handle_call(&mut self, call: &Call) -> Result<Option<Status>, RpcError> {
+        self.tick();
+
+        let session = self.session();
+        let invalid = |message: &str| Err(RpcError::new(RpcError::INVALID_STATE, message));
+        match call {
+            Call::StartTask { task } => {
+                let id = trackit_core::find_task(&self.cards.tasks(), task)
+                    .map_err(|err| RpcError::new(RpcError::UNKNOWN_TASK, err.to_string()))?
+                    .id;
+                self.update(Message::Card(widgets::tasks::Message::Start(id)));
+            }
+            Call::Pause if !session.is_some_and(Session::is_running) => {
+                return invalid("there's no running session")
+            }
+            Call::Pause => self.pause_session(),
+            Call::Resume if !session.is_some_and(Session::is_paused) => {
+                return invalid("there's no paused session")
+            }
+            Call::Resume => self.resume_session(),
+            Call::SkipPhase if session.is_none_or(Session::is_finished) => {
+                return invalid("there's no session to skip")
+            }
+            Call::SkipPhase => self.skip_session(),
+            Call::Stop if session.is_none() => return invalid("there's no session to stop"),
+            Call::Stop => self.update(Message::Stop),
+            Call::Interrupt { kind, note } => {
+                if !self.interrupt(*kind, note.clone()) {
+                    return invalid("there's no running work block");
+                }
+            }
+            Call::Status => {}
+        }
+
+        Ok(self.status())
+    }
+
+    /// The status of the running session, as reported on the control socket.
+    #[cfg(unix)]
+    fn status(&self) -> Option<Status> {
+        let active = self.active.as_ref()?;
+        let task = self.cards.get(active.task)?;
+        Some(Status::new(active, task, self.now))
+    }
+
+    /// How often the running session has to be redrawn: every second for the
+    /// clocks, or as soon as the ring moves by a pixel if that's sooner.
+    ///
+    /// `None` when nothing on screen moves.
+    fn tick_rate(&self) -> Option<Duration> {
+        let session = self.session().filter(|session| session.is_running())?;
+        let clock = Duration::from_secs(1);
+        // the ring is only drawn on the timer screen, the others show clocks
+        if self.screen != Screen::Timer {
+            return Some(clock);
         }
 
-        self.started_task = Some(card.task.clone());
+        // the length of the bar, drawn at 90% of the radius of the ring
+        let pixels = std::f32::consts::PI * RING_SIZE * 0.9;
+        let per_pixel = session.phase_duration().div_f32(pixels);
+        Some(per_pixel.clamp(MIN_TICK, clock))
     }
 
     pub fn subscription(&self) -> iced::Subscription<Message> {
-        let time_sub = if !self.should_stop || self.progress >= 100. {
-            time::every(Duration::from_millis(10)).map(|_| Message::Tick)
+        let running = self.session().is_some_and(Session::is_running);
+
+        let time_sub = if let Some(rate) = self.tick_rate() {
+            time::every(rate).map(|_| Message::Tick)
+        } else if self.toast.is_some() {
+            // only to take the toast down in time
+            time::every(Duration::from_millis(250)).map(|_| Message::Tick)
+        } else {
+            Subscription::none()
+        };
+
+        // a failing bus is not asked again, the error stays on screen
+        let watch_idle =
+            self.settings.idle.enabled && self.idle_error.is_none() && (running || self.is_idle());
+        let idle_sub = if watch_idle {
+            idle::subscription::<idle::DBus>().map(Message::IdleTime)
         } else {
             Subscription::none()
         };
 
-        iced::Subscription::batch([self.modal.subscription().map(Message::Modal), time_sub])
+        // the dialogs have their own text fields, and their own undo
+        let dialog = self.show_modal || self.show_settings || self.export.is_some();
+        let shortcuts_sub = if dialog {
+            Subscription::none()
+        } else {
+            keyboard::on_key_press(|key, modifiers| match key.as_ref() {
+                Key::Character(z) if modifiers.command() && z.eq_ignore_ascii_case("z") => {
+                    Some(if modifiers.shift() {
+                        Message::Redo
+                    } else {
+                        Message::Undo
+                    })
+                }
+                Key::Character(i) if modifiers.command() && i.eq_ignore_ascii_case("i") => {
+                    Some(Message::Interrupt(Kind::Internal))
+                }
+                Key::Character(e) if modifiers.command() && e.eq_ignore_ascii_case("e") => {
+                    Some(Message::Interrupt(Kind::External))
+                }
+                _ => None,
+            })
+        };
+
+        // there's no control socket without Unix sockets
+        #[cfg(unix)]
+        let ipc_sub = ipc::subscription().map(Message::Ipc);
+        #[cfg(not(unix))]
+        let ipc_sub = Subscription::none();
+
+        iced::Subscription::batch([
+            self.modal.subscription().map(Message::Modal),
+            ipc_sub,
+            time_sub,
+            idle_sub,
+            shortcuts_sub,
+        ])
     }
 }
 
@@ -184,6 +1178,6 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .theme(|_| Theme::CatppuccinMocha)
         .antialiasing(true)
         .subscription(App::subscription)
-        .run()?;
+        .run_with(App::new)?;
     Ok(())
 }
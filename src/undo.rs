@@ -0,0 +1,276 @@
+//! The undo history of the task list.
+//!
+//! Every change made by the user is recorded as an [`Edit`] that knows how to
+//! revert itself. Undoing applies the inverse edit, so the work time accounted
+//! by sessions in between is never rolled back, and an update only reverts the
+//! fields it changed.
+
+use trackit_core::{Task, TaskId};
+
+use crate::widgets::tasks::Cards;
+
+/// How many edits can be undone.
+const DEPTH: usize = 100;
+
+/// A change to the task list.
+#[derive(Debug, Clone)]
+pub enum Edit {
+    /// A task was added at `index`.
+    Create { task: Task, index: usize },
+    /// A task was removed from `index`.
+    Delete { task: Task, index: usize },
+    /// A task was edited or changed state, only the fields set by the user and
+    /// changed from `before` to `after` count.
+    Update { before: Box<Task>, after: Box<Task> },
+    /// The tasks were reordered, from and to these orders.
+    Reorder {
+        before: Vec<TaskId>,
+        after: Vec<TaskId>,
+    },
+    /// Several edits done at once, in order.
+    Batch(Vec<Edit>),
+}
+
+impl Edit {
+    /// The edit reverting this one.
+    pub fn inverse(&self) -> Edit {
+        match self {
+            Edit::Create { task, index } => Edit::Delete {
+                task: task.clone(),
+                index: *index,
+            },
+            Edit::Delete { task, index } => Edit::Create {
+                task: task.clone(),
+                index: *index,
+            },
+            Edit::Update { before, after } => Edit::Update {
+                before: after.clone(),
+                after: before.clone(),
+            },
+            Edit::Reorder { before, after } => Edit::Reorder {
+                before: after.clone(),
+                after: before.clone(),
+            },
+            Edit::Batch(edits) => Edit::Batch(edits.iter().rev().map(Edit::inverse).collect()),
+        }
+    }
+
+    /// The tasks this edit removes from the list.
+    pub fn removed(&self) -> Vec<TaskId> {
+        match self {
+            Edit::Delete { task, .. } => vec![task.id],
+            Edit::Batch(edits) => edits.iter().flat_map(Edit::removed).collect(),
+            Edit::Create { .. } | Edit::Update { .. } | Edit::Reorder { .. } => Vec::new(),
+        }
+    }
+
+    /// Applies the edit to the `cards`.
+    pub fn apply(&self, cards: &mut Cards) {
+        match self {
+            Edit::Create { task, index } => cards.insert(*index, task.clone()),
+            Edit::Delete { task, .. } => {
+                cards.remove(task.id);
+            }
+            Edit::Update { before, after } => {
+                if let Some(task) = cards.get_mut(after.id) {
+                    copy_changed_fields(task, before, after);
+                }
+            }
+            Edit::Reorder { after, .. } => cards.set_order(after),
+            Edit::Batch(edits) => edits.iter().for_each(|edit| edit.apply(cards)),
+        }
+    }
+}
+
+/// Copies what the user can edit and changed from `before` to `after` to
+/// `task`, leaving what sessions account, like the elapsed time, and what
+/// changed since alone.
+fn copy_changed_fields(task: &mut Task, before: &Task, after: &Task) {
+    copy_changed(&mut task.name, &before.name, &after.name);
+    copy_changed(&mut task.cycles, &before.cycles, &after.cycles);
+    copy_changed(&mut task.durations, &before.durations, &after.durations);
+    copy_changed(&mut task.project, &before.project, &after.project);
+    copy_changed(&mut task.tags, &before.tags, &after.tags);
+    copy_changed(&mut task.state, &before.state, &after.state);
+    copy_changed(
+        &mut task.completed_at,
+        &before.completed_at,
+        &after.completed_at,
+    );
+    copy_changed(&mut task.notes, &before.notes, &after.notes);
+    copy_changed(&mut task.subtasks, &before.subtasks, &after.subtasks);
+}
+
+fn copy_changed<T: PartialEq + Clone>(field: &mut T, before: &T, after: &T) {
+    if before != after {
+        field.clone_from(after);
+    }
+}
+
+/// The edits that can be undone, and the undone ones that can be redone.
+#[derive(Debug, Default)]
+pub struct History {
+    done: Vec<Edit>,
+    undone: Vec<Edit>,
+}
+
+impl History {
+    /// Records a new `edit`, the undone edits can't be redone anymore.
+    pub fn push(&mut self, edit: Edit) {
+        self.undone.clear();
+        self.done.push(edit);
+        if self.done.len() > DEPTH {
+            self.done.remove(0);
+        }
+    }
+
+    /// The edit to apply to undo the last one, if any.
+    pub fn undo(&mut self) -> Option<Edit> {
+        let edit = self.done.pop()?;
+        let inverse = edit.inverse();
+        self.undone.push(edit);
+        Some(inverse)
+    }
+
+    /// The edit to apply to redo the last undone one, if any.
+    pub fn redo(&mut self) -> Option<Edit> {
+        let edit = self.undone.pop()?;
+        self.done.push(edit.clone());
+        Some(edit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use trackit_core::{
+        chrono::{Local, TimeZone},
+        Durations, TaskState,
+    };
+
+    use super::*;
+
+    fn task(name: &str) -> Task {
+        Task::new(name.into(), 1, Durations::default())
+    }
+
+    fn names(cards: &Cards) -> Vec<String> {
+        cards.tasks().into_iter().map(|task| task.name).collect()
+    }
+
+    /// Records the `edit` in the `history` and applies it to the `cards`.
+    fn edit(history: &mut History, cards: &mut Cards, edit: Edit) {
+        edit.apply(cards);
+        history.push(edit);
+    }
+
+    fn rename(task: &Task, name: &str) -> Edit {
+        let mut after = task.clone();
+        after.name = name.into();
+        Edit::Update {
+            before: Box::new(task.clone()),
+            after: Box::new(after),
+        }
+    }
+
+    #[test]
+    fn an_undone_edit_is_redone() {
+        let mut cards = Cards::from_tasks(vec![task("Write")]);
+        let mut history = History::default();
+        let read = task("Read");
+
+        edit(
+            &mut history,
+            &mut cards,
+            Edit::Create {
+                task: read,
+                index: 0,
+            },
+        );
+        history.undo().unwrap().apply(&mut cards);
+        assert_eq!(names(&cards), ["Write"]);
+
+        history.redo().unwrap().apply(&mut cards);
+        assert_eq!(names(&cards), ["Read", "Write"]);
+        assert!(history.redo().is_none());
+    }
+
+    #[test]
+    fn a_new_edit_drops_the_undone_ones() {
+        let write = task("Write");
+        let mut cards = Cards::from_tasks(vec![write.clone()]);
+        let mut history = History::default();
+
+        edit(&mut history, &mut cards, rename(&write, "Draft"));
+        history.undo().unwrap().apply(&mut cards);
+        edit(&mut history, &mut cards, rename(&write, "Edit"));
+
+        assert!(history.redo().is_none());
+        assert_eq!(names(&cards), ["Edit"]);
+    }
+
+    #[test]
+    fn undoing_a_rename_keeps_a_later_completion() {
+        let write = task("Write");
+        let mut cards = Cards::from_tasks(vec![write.clone()]);
+        let mut history = History::default();
+        let done = Local.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap();
+
+        edit(&mut history, &mut cards, rename(&write, "Draft"));
+        // the session completes the task and accounts its work time
+        let task = cards.get_mut(write.id).unwrap();
+        task.complete(done);
+        task.elapsed = std::time::Duration::from_secs(25 * 60);
+        history.undo().unwrap().apply(&mut cards);
+
+        let task = cards.get(write.id).unwrap();
+        assert_eq!(task.name, "Write");
+        assert_eq!(task.state, TaskState::Completed);
+        assert_eq!(task.completed_at, Some(done));
+        assert_eq!(task.elapsed, std::time::Duration::from_secs(25 * 60));
+    }
+
+    #[test]
+    fn a_batch_is_undone_in_reverse_order() {
+        let (write, read, plan) = (task("Write"), task("Read"), task("Plan"));
+        let mut cards = Cards::from_tasks(vec![write.clone(), read.clone(), plan.clone()]);
+        let mut history = History::default();
+
+        // the last first, so the index of every deletion stays right
+        let batch = Edit::Batch(vec![
+            Edit::Delete {
+                task: plan.clone(),
+                index: 2,
+            },
+            Edit::Delete {
+                task: write.clone(),
+                index: 0,
+            },
+        ]);
+        assert_eq!(batch.removed(), [plan.id, write.id]);
+        edit(&mut history, &mut cards, batch);
+        assert_eq!(names(&cards), ["Read"]);
+
+        history.undo().unwrap().apply(&mut cards);
+        assert_eq!(names(&cards), ["Write", "Read", "Plan"]);
+    }
+
+    #[test]
+    fn a_reorder_is_undone() {
+        let (write, read) = (task("Write"), task("Read"));
+        let mut cards = Cards::from_tasks(vec![write.clone(), read.clone()]);
+        let mut history = History::default();
+
+        edit(
+            &mut history,
+            &mut cards,
+            Edit::Reorder {
+                before: vec![write.id, read.id],
+                after: vec![read.id, write.id],
+            },
+        );
+        assert_eq!(names(&cards), ["Read", "Write"]);
+
+        history.undo().unwrap().apply(&mut cards);
+        assert_eq!(names(&cards), ["Write", "Read"]);
+    }
+}
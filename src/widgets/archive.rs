@@ -0,0 +1,77 @@
+use iced::{
+    alignment::Vertical,
+    widget::{button, column, container, horizontal_space, row, scrollable, text},
+    Element, Length,
+};
+use trackit_core::{format_duration, Project, Task, TaskId};
+
+use super::tasks::{chip, project_color};
+
+/// The messages of the archive screen.
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Message variant for when a task is taken out of the archive.
+    Restore(TaskId),
+    /// Message variant for when a task is deleted for good.
+    Purge(TaskId),
+    /// Message variant for when every archived task is deleted for good.
+    PurgeAll,
+}
+
+/// Renders the archived `tasks`, the last completed first.
+pub fn view<'a>(tasks: &[Task], projects: &[Project]) -> Element<'a, Message> {
+    let mut archived: Vec<_> = tasks.iter().filter(|task| task.is_archived()).collect();
+    archived.sort_by_key(|task| std::cmp::Reverse(task.completed_at));
+
+    let header = row![
+        text(format!("{} archived tasks", archived.len())),
+        horizontal_space(),
+        button("Purge all")
+            .style(button::danger)
+            .on_press_maybe((!archived.is_empty()).then_some(Message::PurgeAll)),
+    ]
+    .align_y(Vertical::Center);
+
+    let rows = archived.into_iter().map(|task| {
+        let project = task
+            .project
+            .and_then(|id| projects.iter().find(|project| project.id == id))
+            .map(|project| chip(project.name.clone(), Some(project_color(project))));
+        let completed = task
+            .completed_at
+            .map(|at| format!("done {}", at.format("%d/%m/%Y")))
+            .unwrap_or_default();
+
+        container(
+            row![text(task.name.clone())]
+                .push_maybe(project)
+                .push(horizontal_space())
+                .push(text(completed).style(text::secondary))
+                .push(text(format_duration(task.elapsed)).style(text::secondary))
+                .push(
+                    button("Restore")
+                        .style(button::secondary)
+                        .on_press(Message::Restore(task.id)),
+                )
+                .push(
+                    button("Purge")
+                        .style(button::danger)
+                        .on_press(Message::Purge(task.id)),
+                )
+                .spacing(8)
+                .align_y(Vertical::Center),
+        )
+        .padding(8)
+        .style(container::rounded_box)
+        .into()
+    });
+
+    scrollable(
+        column![header, column(rows).spacing(8)]
+            .spacing(16)
+            .padding(16),
+    )
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .into()
+}
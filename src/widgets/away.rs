@@ -0,0 +1,57 @@
+use crate::Message as AppMessage;
+use atoms::widgets::modal;
+use iced::{
+    widget::{button, column, container, row, text},
+    Element, Length,
+};
+use trackit_core::{
+    chrono::{DateTime, Local},
+    format_duration,
+    idle::Choice,
+};
+
+/// Asks what the span the user was away, from `since` to `until`, was.
+///
+/// Clicking aside keeps the span, as if the user never left.
+pub fn view<'a>(
+    since: DateTime<Local>,
+    until: DateTime<Local>,
+    bg: impl Into<Element<'a, AppMessage>>,
+) -> Element<'a, AppMessage> {
+    let away = (until - since).to_std().unwrap_or_default();
+    let choice = |choice: Choice, style: fn(&iced::Theme, button::Status) -> button::Style| {
+        button(text(choice.to_string()))
+            .style(style)
+            .on_press(AppMessage::IdleSettled(choice))
+    };
+
+    let content: Element<_> = container(
+        column![
+            text("Welcome back"),
+            text(format!(
+                "You were away from {} to {} ({}), the timer was held meanwhile.",
+                since.format("%H:%M"),
+                until.format("%H:%M"),
+                format_duration(away)
+            )),
+            text("Keep the time as work, discard it, or discard it but log it apart as idle?")
+                .style(text::secondary),
+            container(
+                row![
+                    choice(Choice::Discard, button::danger),
+                    choice(Choice::Split, button::secondary),
+                    choice(Choice::Keep, button::primary),
+                ]
+                .spacing(8)
+            )
+            .align_right(Length::Fill)
+        ]
+        .spacing(16),
+    )
+    .width(400)
+    .padding(16)
+    .style(container::rounded_box)
+    .into();
+
+    modal(bg, content, AppMessage::IdleSettled(Choice::Keep))
+}
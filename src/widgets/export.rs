@@ -0,0 +1,249 @@
+use std::{fmt, fs::File, io::BufWriter, path::PathBuf};
+
+use crate::Message as AppMessage;
+use atoms::widgets::modal;
+use iced::{
+    alignment::Vertical,
+    widget::{
+        button, checkbox, column, container, horizontal_space, pick_list, row, text, text_input,
+    },
+    Element, Length,
+};
+use trackit_core::{
+    chrono::{Days, NaiveDate},
+    export::{self, Format, Labels},
+    history::Filter,
+    project::{self, Group},
+    Project, ProjectId, SessionLog, Task, TaskId,
+};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// The messages of the export dialog.
+#[derive(Debug, Clone)]
+pub enum Message {
+    FormatSelected(Format),
+    /// Message variant for when the first day changes, as `YYYY-MM-DD`.
+    FromChanged(String),
+    /// Message variant for when the last day changes, as `YYYY-MM-DD`.
+    ToChanged(String),
+    TaskSelected(TaskChoice),
+    BreaksToggled(bool),
+    PathChanged(String),
+    Export,
+    Close,
+}
+
+/// The tasks whose intervals are exported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskChoice {
+    All,
+    Task(TaskId, String),
+    Project(ProjectId, String),
+    Tag(String),
+}
+
+impl fmt::Display for TaskChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskChoice::All => f.write_str("Every task"),
+            TaskChoice::Task(_, name) => f.write_str(name),
+            TaskChoice::Project(_, name) => write!(f, "Project: {name}"),
+            TaskChoice::Tag(tag) => write!(f, "#{tag}"),
+        }
+    }
+}
+
+/// The state of the export dialog.
+#[derive(Debug)]
+pub struct ExportDialog {
+    format: Format,
+    from: String,
+    to: String,
+    task: TaskChoice,
+    breaks: bool,
+    path: String,
+    /// The outcome of the last export, a message to show.
+    status: Option<Result<String, String>>,
+}
+
+impl ExportDialog {
+    /// Creates a dialog exporting the last 30 days up to `today`.
+    pub fn new(today: NaiveDate) -> Self {
+        let format = Format::default();
+        let path = export::default_dir()
+            .unwrap_or_default()
+            .join("trackit")
+            .with_extension(format.extension());
+
+        Self {
+            format,
+            from: (today - Days::new(30)).format(DATE_FORMAT).to_string(),
+            to: today.format(DATE_FORMAT).to_string(),
+            task: TaskChoice::All,
+            breaks: false,
+            path: path.to_string_lossy().into_owned(),
+            status: None,
+        }
+    }
+
+    pub fn view<'a>(
+        &self,
+        tasks: &[Task],
+        projects: &[Project],
+        bg: impl Into<Element<'a, AppMessage>>,
+    ) -> Element<'a, AppMessage> {
+        let choices: Vec<_> = std::iter::once(TaskChoice::All)
+            .chain(
+                projects
+                    .iter()
+                    .map(|project| TaskChoice::Project(project.id, project.name.clone())),
+            )
+            .chain(project::all_tags(tasks).into_iter().map(TaskChoice::Tag))
+            .chain(
+                tasks
+                    .iter()
+                    .map(|task| TaskChoice::Task(task.id, task.name.clone())),
+            )
+            .collect();
+
+        let field = |label, content: Element<'a, Message>| {
+            row![text(label), horizontal_space(), content]
+                .spacing(8)
+                .align_y(Vertical::Center)
+        };
+
+        let content: Element<_> = container(
+            column!(
+                text("Export the history"),
+                field(
+                    "Format:",
+                    pick_list(Format::ALL, Some(self.format), Message::FormatSelected).into()
+                ),
+                field(
+                    "From:",
+                    text_input("YYYY-MM-DD", &self.from)
+                        .on_input(Message::FromChanged)
+                        .width(120)
+                        .into()
+                ),
+                field(
+                    "To:",
+                    text_input("YYYY-MM-DD", &self.to)
+                        .on_input(Message::ToChanged)
+                        .width(120)
+                        .into()
+                ),
+                field(
+                    "Task:",
+                    pick_list(choices, Some(self.task.clone()), Message::TaskSelected).into()
+                ),
+                checkbox("Include the breaks", self.breaks).on_toggle_maybe(
+                    (self.format != Format::Ical).then_some(Message::BreaksToggled)
+                ),
+                column![
+                    text("File:"),
+                    text_input("trackit.csv", &self.path).on_input(Message::PathChanged)
+                ]
+                .spacing(8),
+            )
+            .push_maybe(self.status.clone().map(|status| match status {
+                Ok(msg) => text(msg).style(text::success),
+                Err(err) => text(err).style(text::danger),
+            }))
+            .push(
+                container(
+                    row![
+                        button("Close")
+                            .style(button::secondary)
+                            .on_press(Message::Close),
+                        button("Export").on_press(Message::Export)
+                    ]
+                    .spacing(8),
+                )
+                .align_right(Length::Fill),
+            )
+            .spacing(16),
+        )
+        .width(400)
+        .height(Length::Shrink)
+        .padding(16)
+        .style(container::rounded_box)
+        .into();
+
+        modal(
+            bg,
+            content.map(AppMessage::Export),
+            AppMessage::Export(Message::Close),
+        )
+    }
+
+    pub fn update(&mut self, msg: Message) {
+        match msg {
+            Message::FormatSelected(format) => {
+                self.format = format;
+                self.path = PathBuf::from(&self.path)
+                    .with_extension(format.extension())
+                    .to_string_lossy()
+                    .into_owned();
+            }
+            Message::FromChanged(from) => self.from = from,
+            Message::ToChanged(to) => self.to = to,
+            Message::TaskSelected(task) => self.task = task,
+            Message::BreaksToggled(breaks) => self.breaks = breaks,
+            Message::PathChanged(path) => self.path = path,
+            // catched at app level
+            Message::Export | Message::Close => {}
+        }
+    }
+
+    /// Writes the intervals picked from the `log` to the chosen file, labelled
+    /// with the project and tags of their task.
+    pub fn export(&mut self, log: &SessionLog, tasks: &[Task], projects: &[Project]) {
+        self.status = Some(self.try_export(log, Labels { tasks, projects }));
+    }
+
+    fn try_export(&self, log: &SessionLog, labels: Labels<'_>) -> Result<String, String> {
+        let date = |date: &str| {
+            NaiveDate::parse_from_str(date, DATE_FORMAT)
+                .map_err(|_| format!("`{date}` is not a YYYY-MM-DD date"))
+        };
+        let (from, to) = (date(&self.from)?, date(&self.to)?);
+        if from > to {
+            return Err("The first day is after the last one".into());
+        }
+        if self.path.is_empty() {
+            return Err("You must provide a file".into());
+        }
+
+        let (task, group) = match &self.task {
+            TaskChoice::All => (None, None),
+            TaskChoice::Task(id, _) => (Some(*id), None),
+            TaskChoice::Project(id, _) => (None, Some(Group::Project(*id))),
+            TaskChoice::Tag(tag) => (None, Some(Group::Tag(tag.clone()))),
+        };
+        let filter = Filter {
+            task,
+            ..Filter::days(from, to)
+        };
+        let mut intervals = log.intervals(&filter).map_err(|err| err.to_string())?;
+        if !self.breaks {
+            intervals.retain(|interval| !interval.phase.is_break());
+        }
+        if let Some(group) = group {
+            intervals.retain(|interval| {
+                labels
+                    .tasks
+                    .iter()
+                    .find(|task| task.id == interval.task_id)
+                    .is_some_and(|task| group.contains(task))
+            });
+        }
+
+        let file = File::create(&self.path).map_err(|err| err.to_string())?;
+        export::export(&intervals, labels, self.format, BufWriter::new(file))
+            .map_err(|err| err.to_string())?;
+
+        Ok(format!("{} intervals exported", intervals.len()))
+    }
+}
@@ -0,0 +1,87 @@
+use atoms::widgets::radial_progress_bar;
+use iced::{
+    alignment::Horizontal,
+    widget::{column, text},
+    Element,
+};
+use trackit_core::{
+    chrono::{Days, NaiveDate},
+    goal::Goal,
+    history::{self, Filter},
+    stats::{Day, Stats},
+    SessionLog,
+};
+
+/// How far today went towards the daily goal, and the streak of days that met it.
+#[derive(Debug, Default)]
+pub struct Progress {
+    goal: Goal,
+    date: NaiveDate,
+    today: Day,
+    streak: u32,
+}
+
+impl Progress {
+    /// Reads the progress of `date` towards the `goal` from the `log`.
+    ///
+    /// Only the last days are read, twice as many each time the streak runs
+    /// back to the first of them.
+    pub fn new(log: Option<&SessionLog>, goal: Goal, date: NaiveDate) -> Self {
+        let mut days = 7;
+        loop {
+            let from = date.checked_sub_days(Days::new(days));
+            let stats = read(log, from.unwrap_or(NaiveDate::MIN), date);
+            let streak = stats.goal_streak(&goal, date);
+
+            if from.is_none() || u64::from(streak) < days {
+                return Self {
+                    goal,
+                    date,
+                    today: stats.day(date),
+                    streak,
+                };
+            }
+            days *= 2;
+        }
+    }
+
+    /// Whether the progress is about another day than `today`.
+    pub fn is_stale(&self, today: NaiveDate) -> bool {
+        self.date != today
+    }
+
+    pub fn view<'a, Message: 'a>(&self) -> Element<'a, Message> {
+        let streak = match self.streak {
+            0 => "No streak yet".to_string(),
+            1 => "1 day streak".to_string(),
+            days => format!("{days} days streak"),
+        };
+
+        column![
+            radial_progress_bar(
+                self.goal.progress(&self.today) * 100.,
+                format!("{}/{}", self.goal.reached(&self.today), self.goal.target())
+            )
+            .width(60)
+            .height(60),
+            text(format!("Goal: {}", self.goal)).size(12),
+            text(streak).size(12).style(text::secondary),
+        ]
+        .spacing(4)
+        .align_x(Horizontal::Center)
+        .into()
+    }
+}
+
+/// The statistics of the days from `from` to `to`, both included.
+fn read(log: Option<&SessionLog>, from: NaiveDate, to: NaiveDate) -> Stats {
+    let history = log.map(|log| {
+        let filter = Filter::days(from, to);
+        Ok::<_, history::Error>((log.intervals(&filter)?, log.interruptions(&filter)?))
+    });
+    match history {
+        Some(Ok((intervals, interruptions))) => Stats::new(&intervals, &interruptions),
+        // the dashboard reports the errors of the log
+        Some(Err(_)) | None => Stats::default(),
+    }
+}
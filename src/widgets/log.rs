@@ -0,0 +1,496 @@
+use std::fmt;
+
+use atoms::widgets::{
+    timeline,
+    timeline::{block, Kind},
+};
+use iced::{
+    alignment::Vertical,
+    widget::{
+        button, column, container, horizontal_space, pick_list, row, scrollable, text, text_input,
+        Column,
+    },
+    Element, Length,
+};
+use trackit_core::{
+    chrono::{
+        DateTime, Days, DurationRound, Local, NaiveDate, NaiveTime, TimeDelta, TimeZone, Timelike,
+    },
+    format_duration,
+    history::Filter,
+    timeline::{check_merge, Change},
+    Interval, Phase, Project, SessionLog, Task, TaskId,
+};
+
+use super::tasks::project_color;
+
+const TIME_FORMAT: &str = "%H:%M";
+/// The formats a time can be entered in, seconds being optional.
+const INPUT_FORMATS: [&str; 2] = ["%H:%M:%S", TIME_FORMAT];
+const MINUTES_PER_DAY: f32 = 24. * 60.;
+const PHASES: [Phase; 3] = [Phase::Work, Phase::ShortBreak, Phase::LongBreak];
+
+/// The messages of the log screen.
+#[derive(Debug, Clone)]
+pub enum Message {
+    PreviousDay,
+    NextDay,
+    Today,
+    /// Message variant for when the user starts entering an interval by hand.
+    Add,
+    /// Message variant for when the user starts editing a recorded interval.
+    Edit(i64),
+    TaskSelected(TaskChoice),
+    PhaseSelected(Phase),
+    /// Message variant for when the start of the edited interval changes, as
+    /// `HH:MM` or `HH:MM:SS`.
+    StartChanged(String),
+    /// Message variant for when the end of the edited interval changes, as
+    /// `HH:MM` or `HH:MM:SS`, on the next day if before the start.
+    EndChanged(String),
+    Save,
+    Cancel,
+    /// Message variant for when an interval is cut in two halves.
+    Split(i64),
+    /// Message variant for when an interval is joined with the next one.
+    MergeWithNext(i64),
+    Delete(i64),
+}
+
+/// A task an interval can be logged for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskChoice {
+    id: TaskId,
+    name: String,
+}
+
+impl fmt::Display for TaskChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// The interval being entered or edited.
+#[derive(Debug, Clone)]
+struct Form {
+    /// The id of the edited interval, `None` for a new one.
+    id: Option<i64>,
+    task: Option<TaskChoice>,
+    phase: Phase,
+    start: String,
+    end: String,
+    /// The start and end of the edited interval, to the millisecond, kept as
+    /// long as their text is left as is.
+    recorded: Option<(DateTime<Local>, DateTime<Local>)>,
+    /// Why the cut short interval ended, kept as it was.
+    interruption: Option<String>,
+}
+
+/// The intervals of a day, and the changes the user makes to them.
+#[derive(Debug)]
+pub struct LogEditor {
+    date: NaiveDate,
+    intervals: Vec<Interval>,
+    form: Option<Form>,
+    error: Option<String>,
+}
+
+impl LogEditor {
+    pub fn new(today: NaiveDate) -> Self {
+        Self {
+            date: today,
+            intervals: Vec::new(),
+            form: None,
+            error: None,
+        }
+    }
+
+    /// Reads the intervals of the day from the `log`.
+    pub fn load(&mut self, log: Option<&SessionLog>) {
+        let Some(log) = log else {
+            self.intervals.clear();
+            return;
+        };
+
+        match log.intervals(&Filter::days(self.date, self.date)) {
+            Ok(intervals) => self.intervals = intervals,
+            Err(err) => self.error = Some(err.to_string()),
+        }
+    }
+
+    /// Closes the form once its change went through.
+    pub fn applied(&mut self) {
+        self.form = None;
+        self.error = None;
+    }
+
+    pub fn set_error(&mut self, error: impl Into<String>) {
+        self.error = Some(error.into());
+    }
+
+    /// Handles the `msg`, returns the change of the log it asks for, if any.
+    pub fn update(&mut self, msg: Message, tasks: &[Task]) -> Option<Change> {
+        match msg {
+            Message::PreviousDay => self.show(self.date.checked_sub_days(Days::new(1))),
+            Message::NextDay => self.show(self.date.checked_add_days(Days::new(1))),
+            Message::Today => self.show(Some(Local::now().date_naive())),
+            Message::Add => {
+                let task = tasks
+                    .iter()
+                    .find(|task| !task.is_archived())
+                    .map(|task| TaskChoice {
+                        id: task.id,
+                        name: task.name.clone(),
+                    });
+                self.open(Form {
+                    id: None,
+                    task,
+                    phase: Phase::Work,
+                    start: String::new(),
+                    end: String::new(),
+                    recorded: None,
+                    interruption: None,
+                })
+            }
+            Message::Edit(id) => {
+                let interval = self.interval(id)?;
+                // the task may have been renamed since
+                let name = tasks
+                    .iter()
+                    .find(|task| task.id == interval.task_id)
+                    .map_or(&interval.task, |task| &task.name);
+                let form = Form {
+                    id: Some(id),
+                    task: Some(TaskChoice {
+                        id: interval.task_id,
+                        name: name.clone(),
+                    }),
+                    phase: interval.phase,
+                    start: interval.start.format(TIME_FORMAT).to_string(),
+                    end: interval.end.format(TIME_FORMAT).to_string(),
+                    recorded: Some((interval.start, interval.end)),
+                    interruption: interval.interruption.clone(),
+                };
+                self.open(form)
+            }
+            Message::TaskSelected(task) => self.form.as_mut()?.task = Some(task),
+            Message::PhaseSelected(phase) => self.form.as_mut()?.phase = phase,
+            Message::StartChanged(start) => self.form.as_mut()?.start = start,
+            Message::EndChanged(end) => self.form.as_mut()?.end = end,
+            Message::Cancel => self.applied(),
+            Message::Save => match self.submit() {
+                Ok(change) => return Some(change),
+                Err(err) => self.set_error(err),
+            },
+            Message::Split(id) => {
+                // halves, rounded to the minute, the user can move the cut after
+                let interval = self.interval(id)?;
+                let middle = interval.start + (interval.end - interval.start) / 2;
+                let at = middle
+                    .duration_round(TimeDelta::minutes(1))
+                    .ok()
+                    .filter(|at| *at > interval.start && *at < interval.end)
+                    .unwrap_or(middle);
+                return Some(Change::Split { id, at });
+            }
+            Message::MergeWithNext(id) => {
+                let index = self.intervals.iter().position(|other| other.id == id)?;
+                let next = self.intervals.get(index + 1)?;
+                return Some(Change::Merge {
+                    first: id,
+                    second: next.id,
+                });
+            }
+            Message::Delete(id) => return Some(Change::Delete(id)),
+        }
+
+        None
+    }
+
+    fn show(&mut self, date: Option<NaiveDate>) {
+        if let Some(date) = date {
+            self.date = date;
+            self.form = None;
+            self.error = None;
+        }
+    }
+
+    fn open(&mut self, form: Form) {
+        self.form = Some(form);
+        self.error = None;
+    }
+
+    fn interval(&self, id: i64) -> Option<&Interval> {
+        self.intervals.iter().find(|interval| interval.id == id)
+    }
+
+    /// The change the form asks for, or why it's not filled right.
+    fn submit(&self) -> Result<Change, String> {
+        let Some(form) = &self.form else {
+            return Err("There's nothing to save".into());
+        };
+        let Some(task) = &form.task else {
+            return Err("Choose the task of the interval".into());
+        };
+        // a time left as shown is the recorded one, not rounded to the minute
+        let recorded = |text: &str, at: Option<DateTime<Local>>| {
+            at.filter(|at| at.format(TIME_FORMAT).to_string() == text.trim())
+        };
+        let start = match recorded(&form.start, form.recorded.map(|(start, _)| start)) {
+            Some(start) => start,
+            None => self.parse(&form.start, self.date)?,
+        };
+        let end = match recorded(&form.end, form.recorded.map(|(_, end)| end)) {
+            Some(end) => end,
+            None => {
+                let end = self.parse(&form.end, start.date_naive())?;
+                // an interval over midnight ends on the next day
+                match start.date_naive().succ_opt().filter(|_| end < start) {
+                    Some(next_day) => self.parse(&form.end, next_day)?,
+                    None => end,
+                }
+            }
+        };
+
+        let mut interval = Interval::new(task.id, task.name.clone(), form.phase, start, end);
+        interval.interruption = form.interruption.clone();
+        Ok(match form.id {
+            Some(id) => Change::Update(Interval { id, ..interval }),
+            None => Change::Add(interval),
+        })
+    }
+
+    /// Reads a `HH:MM` or `HH:MM:SS` time of the `date`.
+    fn parse(&self, time: &str, date: NaiveDate) -> Result<DateTime<Local>, String> {
+        let time = INPUT_FORMATS
+            .iter()
+            .find_map(|format| NaiveTime::parse_from_str(time.trim(), format).ok())
+            .ok_or_else(|| format!("`{time}` is not a time like 09:30"))?;
+        Local
+            .from_local_datetime(&date.and_time(time))
+            .earliest()
+            .ok_or_else(|| format!("{time} doesn't exist on that day"))
+    }
+
+    /// The minutes from the start of the day shown to `at`, within the day.
+    fn minutes(&self, at: DateTime<Local>) -> f32 {
+        match at.date_naive().cmp(&self.date) {
+            std::cmp::Ordering::Less => 0.,
+            std::cmp::Ordering::Equal => at.time().num_seconds_from_midnight() as f32 / 60.,
+            std::cmp::Ordering::Greater => MINUTES_PER_DAY,
+        }
+    }
+
+    /// The intervals of the day laid along the hours they cover, colored by
+    /// project. Clicking one edits it.
+    fn timeline<'a>(&self, tasks: &[Task], projects: &[Project]) -> Element<'a, Message> {
+        let spans: Vec<_> = self
+            .intervals
+            .iter()
+            .map(|interval| {
+                (
+                    interval,
+                    self.minutes(interval.start),
+                    self.minutes(interval.end),
+                )
+            })
+            .collect();
+
+        // the whole hours around the intervals, the working hours of an empty day
+        let (from, to) = match (
+            spans.iter().map(|(_, start, _)| *start).reduce(f32::min),
+            spans.iter().map(|(_, _, end)| *end).reduce(f32::max),
+        ) {
+            (Some(first), Some(last)) => {
+                let from = (first / 60.).floor() * 60.;
+                (from, ((last / 60.).ceil() * 60.).max(from + 60.))
+            }
+            _ => (8. * 60., 18. * 60.),
+        };
+        let step = if to - from > 12. * 60. { 2 } else { 1 };
+        let ticks = (from as u32 / 60..=to as u32 / 60)
+            .step_by(step)
+            .map(|hour| (hour as f32 * 60., format!("{hour}:00")));
+
+        let blocks = spans.into_iter().map(|(interval, start, end)| {
+            let color = tasks
+                .iter()
+                .find(|task| task.id == interval.task_id)
+                .and_then(|task| task.project)
+                .and_then(|id| projects.iter().find(|project| project.id == id))
+                .map(project_color);
+            let kind = match interval.phase {
+                Phase::Work => Kind::Focus,
+                Phase::ShortBreak | Phase::LongBreak => Kind::Rest,
+            };
+
+            block(start, end)
+                .kind(kind)
+                .color(color)
+                .tooltip(format!(
+                    "{} – {} {} ({}, {})",
+                    interval.start.format(TIME_FORMAT),
+                    interval.end.format(TIME_FORMAT),
+                    interval.task,
+                    interval.phase,
+                    format_duration(interval.duration())
+                ))
+                .on_press(Message::Edit(interval.id))
+        });
+
+        timeline(from..to, blocks).ticks(ticks).into()
+    }
+
+    pub fn view<'a>(&'a self, tasks: &[Task], projects: &[Project]) -> Element<'a, Message> {
+        let header = row![
+            button("<")
+                .style(button::text)
+                .on_press(Message::PreviousDay),
+            text(self.date.format("%A %d/%m/%Y").to_string()),
+            button(">").style(button::text).on_press(Message::NextDay),
+            button("Today")
+                .style(button::secondary)
+                .on_press(Message::Today),
+            horizontal_space(),
+            button("Add an interval").on_press(Message::Add),
+        ]
+        .spacing(8)
+        .align_y(Vertical::Center);
+
+        let work: std::time::Duration = self
+            .intervals
+            .iter()
+            .filter(|interval| interval.phase == Phase::Work)
+            .map(Interval::duration)
+            .sum();
+        let summary = text(format!(
+            "{} intervals, {} of work",
+            self.intervals.len(),
+            format_duration(work)
+        ))
+        .style(text::secondary);
+
+        let rows = self.intervals.iter().enumerate().map(|(index, interval)| {
+            let mergeable = self
+                .intervals
+                .get(index + 1)
+                .is_some_and(|next| check_merge(interval, next).is_ok());
+            let editing = self
+                .form
+                .as_ref()
+                .is_some_and(|form| form.id == Some(interval.id));
+
+            container(
+                row![
+                    text(format!(
+                        "{} – {}",
+                        interval.start.format(TIME_FORMAT),
+                        interval.end.format(TIME_FORMAT)
+                    )),
+                    text(interval.phase.to_string()).style(text::secondary),
+                    text(interval.task.clone()),
+                ]
+                .push_maybe(
+                    interval
+                        .interruption
+                        .as_ref()
+                        .map(|reason| text(format!("({reason})")).style(text::secondary)),
+                )
+                .push(horizontal_space())
+                .push(text(format_duration(interval.duration())).style(text::secondary))
+                .push(
+                    button("Edit")
+                        .style(button::secondary)
+                        .on_press(Message::Edit(interval.id)),
+                )
+                .push(
+                    button("Split")
+                        .style(button::secondary)
+                        .on_press(Message::Split(interval.id)),
+                )
+                .push(
+                    button("Merge ↓")
+                        .style(button::secondary)
+                        .on_press_maybe(mergeable.then_some(Message::MergeWithNext(interval.id))),
+                )
+                .push(
+                    button("Delete")
+                        .style(button::danger)
+                        .on_press(Message::Delete(interval.id)),
+                )
+                .spacing(8)
+                .align_y(Vertical::Center),
+            )
+            .padding(8)
+            .style(if editing {
+                container::bordered_box
+            } else {
+                container::rounded_box
+            })
+            .into()
+        });
+
+        scrollable(
+            column![header, summary, self.timeline(tasks, projects)]
+                .push_maybe(self.form.as_ref().map(|form| form_view(form, tasks)))
+                .push_maybe(
+                    self.error
+                        .as_ref()
+                        .map(|err| text(err.clone()).style(text::danger)),
+                )
+                .push(column(rows).spacing(8))
+                .spacing(16)
+                .padding(16),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}
+
+fn form_view<'a>(form: &Form, tasks: &[Task]) -> Column<'a, Message> {
+    // the archived tasks stay out, unless the interval already belongs to one
+    let choices: Vec<_> = tasks
+        .iter()
+        .filter(|task| {
+            !task.is_archived()
+                || form
+                    .task
+                    .as_ref()
+                    .is_some_and(|choice| choice.id == task.id)
+        })
+        .map(|task| TaskChoice {
+            id: task.id,
+            name: task.name.clone(),
+        })
+        .collect();
+
+    column![
+        text(if form.id.is_some() {
+            "Edit the interval"
+        } else {
+            "New interval"
+        }),
+        row![
+            pick_list(choices, form.task.clone(), Message::TaskSelected)
+                .placeholder("Task")
+                .width(Length::Fill),
+            pick_list(PHASES, Some(form.phase), Message::PhaseSelected),
+            text_input("09:00", &form.start)
+                .on_input(Message::StartChanged)
+                .width(70),
+            text("–"),
+            text_input("09:25", &form.end)
+                .on_input(Message::EndChanged)
+                .on_submit(Message::Save)
+                .width(70),
+            button("Cancel")
+                .style(button::secondary)
+                .on_press(Message::Cancel),
+            button("Save").on_press(Message::Save),
+        ]
+        .spacing(8)
+        .align_y(Vertical::Center),
+    ]
+    .spacing(8)
+}
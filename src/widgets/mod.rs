@@ -1,2 +1,12 @@
+pub mod archive;
+pub mod away;
+pub mod export;
+pub mod goal;
+pub mod log;
 pub mod modal;
+pub mod notes;
+pub mod search;
+pub mod settings;
+pub mod stats;
 pub mod tasks;
+pub mod toast;
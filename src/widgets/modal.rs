@@ -3,43 +3,139 @@ use atoms::widgets::modal;
 use iced::{
     alignment::Vertical,
     keyboard::{key::Named, Key},
-    widget::{button, column, container, horizontal_space, row, text, text_input},
+    widget::{
+        button, checkbox, column, container, horizontal_space, row, scrollable, text, text_editor,
+        text_input,
+    },
     Element, Length,
 };
 
 use iced_aw::number_input;
+use trackit_core::{Durations, Project, Subtask, Task, TaskId};
 
 /// The `Message` enum represents the different messages that can be sent within the modal.
 #[derive(Debug, Clone)]
 pub enum Message {
     /// Message variant for when the task name changes.
     TaskNameChanged(String),
+    /// Message variant for when the project name changes.
+    ProjectChanged(String),
+    /// Message variant for when the tags, separated by commas, change.
+    TagsChanged(String),
+    /// Message variant for when the notes are edited.
+    NotesEdited(text_editor::Action),
+    /// Message variant for when the name of the next subtask changes.
+    SubtaskInputChanged(String),
+    /// Message variant for when the typed subtask is added to the checklist.
+    SubtaskAdded,
+    /// Message variant for when a subtask, by index, is moved up the checklist.
+    SubtaskMovedUp(usize),
+    /// Message variant for when a subtask, by index, is removed.
+    SubtaskRemoved(usize),
     /// Message variant for when the cycles changes.
     CyclesChanged(u8),
+    /// Message variant for when the work length (in minutes) changes.
+    WorkChanged(u8),
+    /// Message variant for when the short break length (in minutes) changes.
+    ShortBreakChanged(u8),
+    /// Message variant for when the long break length (in minutes) changes.
+    LongBreakChanged(u8),
+    /// Message variant for when the "save as default" checkbox is toggled.
+    SaveAsDefaultToggled(bool),
     Cancel,
     CreateNewTask,
+    /// Message variant for when the edited task is saved.
+    SaveTask,
 }
 
 /// The `Modal` struct represents the state of the modal
 #[derive(Debug)]
 pub struct Modal {
     pub task_name: String,
+    /// The name of the project, created if it doesn't exist.
+    pub project: String,
+    /// The tags, separated by commas.
+    pub tags: String,
+    pub notes: text_editor::Content,
+    /// The checklist of the task, in order.
+    pub subtasks: Vec<Subtask>,
+    /// The name of the subtask being typed.
+    pub new_subtask: String,
     pub cycles: u8,
+    pub work: u8,
+    pub short_break: u8,
+    pub long_break: u8,
+    pub save_as_default: bool,
     pub error_msg: Option<String>,
+    /// The task being edited, `None` when creating a new one.
+    pub editing: Option<TaskId>,
+    /// The profile the durations are reset to.
+    defaults: Durations,
 }
 
 impl Default for Modal {
     fn default() -> Self {
+        Self::new(Durations::default())
+    }
+}
+
+fn minutes(duration: std::time::Duration) -> u8 {
+    (duration.as_secs() / 60).clamp(1, u8::MAX as u64) as u8
+}
+
+impl<'a> Modal {
+    /// Creates a modal prefilled with the `defaults` profile.
+    pub fn new(defaults: Durations) -> Self {
         Self {
             cycles: 1,
             task_name: String::default(),
+            project: String::default(),
+            tags: String::default(),
+            notes: text_editor::Content::new(),
+            subtasks: Vec::new(),
+            new_subtask: String::new(),
+            work: minutes(defaults.work),
+            short_break: minutes(defaults.short_break),
+            long_break: minutes(defaults.long_break),
+            save_as_default: false,
             error_msg: None,
+            editing: None,
+            defaults,
         }
     }
-}
 
-impl<'a> Modal {
-    pub fn view(&self, bg: impl Into<Element<'a, AppMessage>>) -> Element<'a, AppMessage> {
+    /// Switches the modal to edit the `task`, prefilled with its fields.
+    pub fn edit(&mut self, task: &Task, projects: &[Project]) {
+        *self = Self {
+            task_name: task.name.clone(),
+            project: task
+                .project
+                .and_then(|id| projects.iter().find(|project| project.id == id))
+                .map(|project| project.name.clone())
+                .unwrap_or_default(),
+            tags: task.tags.join(", "),
+            notes: text_editor::Content::with_text(&task.notes),
+            subtasks: task.subtasks.clone(),
+            cycles: task.cycles,
+            work: minutes(task.durations.work),
+            short_break: minutes(task.durations.short_break),
+            long_break: minutes(task.durations.long_break),
+            editing: Some(task.id),
+            ..Self::new(self.defaults)
+        };
+    }
+
+    /// The notes typed in the editor.
+    pub fn notes(&self) -> String {
+        self.notes.text().trim_end().to_string()
+    }
+
+    /// Renders the modal, listing the existing `projects` as a hint.
+    pub fn view(
+        &'a self,
+        projects: &[Project],
+        bg: impl Into<Element<'a, AppMessage>>,
+    ) -> Element<'a, AppMessage> {
         let full_field = column([
             text("Task name:").into(),
             text_input("Do some stuff", &self.task_name)
@@ -48,9 +144,88 @@ impl<'a> Modal {
         ])
         .push_maybe(self.error_msg.clone().map(|t| text(t).style(text::danger)));
 
-        let content: Element<_> = container(
+        let known: Vec<_> = projects
+            .iter()
+            .map(|project| project.name.as_str())
+            .collect();
+        let project_placeholder = if known.is_empty() {
+            "None".to_string()
+        } else {
+            known.join(", ")
+        };
+        let notes = column![
+            text("Notes:"),
+            text_editor(&self.notes)
+                .placeholder("What to keep in mind")
+                .on_action(Message::NotesEdited)
+                .height(80)
+        ]
+        .spacing(8);
+
+        let subtasks = self.subtasks.iter().enumerate().map(|(index, subtask)| {
+            row![
+                text(subtask.name.clone()).style(if subtask.done {
+                    text::secondary
+                } else {
+                    text::default
+                }),
+                horizontal_space(),
+                button("↑")
+                    .style(button::text)
+                    .on_press_maybe((index > 0).then_some(Message::SubtaskMovedUp(index))),
+                button("×")
+                    .style(button::text)
+                    .on_press(Message::SubtaskRemoved(index)),
+            ]
+            .align_y(Vertical::Center)
+            .into()
+        });
+        let checklist = column![
+            text("Subtasks:"),
+            column(subtasks),
+            row![
+                text_input("Next step", &self.new_subtask)
+                    .on_input(Message::SubtaskInputChanged)
+                    .on_submit(Message::SubtaskAdded),
+                button("Add").style(button::secondary).on_press_maybe(
+                    (!self.new_subtask.trim().is_empty()).then_some(Message::SubtaskAdded)
+                ),
+            ]
+            .spacing(8)
+        ]
+        .spacing(8);
+
+        let labels = column![
+            row![
+                text("Project:"),
+                horizontal_space(),
+                text_input(&project_placeholder, &self.project)
+                    .on_input(Message::ProjectChanged)
+                    .width(200)
+            ]
+            .align_y(Vertical::Center),
+            row![
+                text("Tags:"),
+                horizontal_space(),
+                text_input("deep work, reading", &self.tags)
+                    .on_input(Message::TagsChanged)
+                    .width(200)
+            ]
+            .align_y(Vertical::Center),
+        ]
+        .spacing(8);
+
+        let content: Element<_> = container(scrollable(
             column!(
+                text(if self.editing.is_some() {
+                    "Edit the task"
+                } else {
+                    "New task"
+                }),
                 full_field.spacing(8),
+                labels,
+                notes,
+                checklist,
                 row![
                     text("Cycle count:"),
                     horizontal_space(),
@@ -58,20 +233,48 @@ impl<'a> Modal {
                         .style(number_input::number_input::primary)
                 ]
                 .align_y(Vertical::Center),
+                row![
+                    text("Work (min):"),
+                    horizontal_space(),
+                    number_input(self.work, 1..=180u8, Message::WorkChanged)
+                        .style(number_input::number_input::primary)
+                ]
+                .align_y(Vertical::Center),
+                row![
+                    text("Short break (min):"),
+                    horizontal_space(),
+                    number_input(self.short_break, 1..=60u8, Message::ShortBreakChanged)
+                        .style(number_input::number_input::primary)
+                ]
+                .align_y(Vertical::Center),
+                row![
+                    text("Long break (min):"),
+                    horizontal_space(),
+                    number_input(self.long_break, 1..=60u8, Message::LongBreakChanged)
+                        .style(number_input::number_input::primary)
+                ]
+                .align_y(Vertical::Center),
+                checkbox("Use these durations by default", self.save_as_default)
+                    .on_toggle(Message::SaveAsDefaultToggled),
                 container(
                     row![
                         button("Cancel")
                             .style(button::danger)
                             .on_press(Message::Cancel),
-                        button("Create").on_press(Message::CreateNewTask)
+                        match self.editing {
+                            Some(_) => button("Save").on_press(Message::SaveTask),
+                            None => button("Create").on_press(Message::CreateNewTask),
+                        }
                     ]
                     .spacing(8)
                 )
                 .align_right(Length::Fill)
             )
-            .spacing(16),
-        )
+            .spacing(16)
+            .padding(iced::Padding::ZERO.right(12)),
+        ))
         .width(400)
+        .max_height(600)
         .height(Length::Shrink)
         .padding(16)
         .style(container::rounded_box)
@@ -90,26 +293,77 @@ impl<'a> Modal {
                     self.error_msg = None
                 };
             }
+            Message::ProjectChanged(project) => self.project = project,
+            Message::TagsChanged(tags) => self.tags = tags,
+            Message::NotesEdited(action) => self.notes.perform(action),
+            Message::SubtaskInputChanged(name) => self.new_subtask = name,
+            Message::SubtaskAdded => {
+                let name = self.new_subtask.trim();
+                if !name.is_empty() {
+                    self.subtasks.push(Subtask::new(name.to_string()));
+                    self.new_subtask.clear();
+                }
+            }
+            Message::SubtaskMovedUp(index) => {
+                if index > 0 && index < self.subtasks.len() {
+                    self.subtasks.swap(index - 1, index);
+                }
+            }
+            Message::SubtaskRemoved(index) => {
+                if index < self.subtasks.len() {
+                    self.subtasks.remove(index);
+                }
+            }
             Message::CyclesChanged(cycles) => self.cycles = cycles,
+            Message::WorkChanged(minutes) => self.work = minutes,
+            Message::ShortBreakChanged(minutes) => self.short_break = minutes,
+            Message::LongBreakChanged(minutes) => self.long_break = minutes,
+            Message::SaveAsDefaultToggled(save) => self.save_as_default = save,
             // catched at app level
-            Message::Cancel | Message::CreateNewTask => {}
+            Message::Cancel | Message::CreateNewTask | Message::SaveTask => {}
         }
     }
 
+    /// The durations picked by the user.
+    pub fn durations(&self) -> Durations {
+        Durations::from_minutes(
+            self.work as u64,
+            self.short_break as u64,
+            self.long_break as u64,
+        )
+    }
+
     pub fn reset(&mut self) {
-        *self = Self::default()
+        *self = Self::new(self.defaults)
+    }
+
+    /// Changes the profile used to prefill the durations.
+    pub fn set_defaults(&mut self, defaults: Durations) {
+        self.defaults = defaults;
     }
 
     pub fn subscription(&self) -> iced::Subscription<Message> {
-        iced::keyboard::on_key_press(|k, _| {
-            if k == Key::Named(Named::Enter) {
-                Some(Message::CreateNewTask)
-            } else if k == Key::Named(Named::Escape) {
-                Some(Message::Cancel)
-            } else {
-                None
-            }
-        })
+        if self.editing.is_some() {
+            iced::keyboard::on_key_press(|k, _| {
+                if k == Key::Named(Named::Enter) {
+                    Some(Message::SaveTask)
+                } else if k == Key::Named(Named::Escape) {
+                    Some(Message::Cancel)
+                } else {
+                    None
+                }
+            })
+        } else {
+            iced::keyboard::on_key_press(|k, _| {
+                if k == Key::Named(Named::Enter) {
+                    Some(Message::CreateNewTask)
+                } else if k == Key::Named(Named::Escape) {
+                    Some(Message::Cancel)
+                } else {
+                    None
+                }
+            })
+        }
     }
 
     pub fn set_error(&mut self, error: impl Into<String>) {
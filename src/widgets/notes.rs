@@ -0,0 +1,106 @@
+//! The notes and the checklist of a task.
+//!
+//! Notes are plain text with a few markdown habits: `#` starts a heading, `-`
+//! or `*` a bullet, `**bold**` and `` `code` `` are highlighted inline.
+
+use iced::{
+    font::Weight,
+    widget::{checkbox, column, rich_text, row, span, text, text::Span},
+    Element, Font,
+};
+use trackit_core::Task;
+
+use super::tasks::Message;
+
+/// Renders the notes and the subtasks of the `task`, ticking a subtask sends
+/// [`Message::SubtaskToggled`].
+pub fn view<'a>(task: &Task) -> Element<'a, Message> {
+    let id = task.id;
+    let checklist = task.subtasks.iter().enumerate().map(|(index, subtask)| {
+        checkbox(subtask.name.clone(), subtask.done)
+            .on_toggle(move |done| Message::SubtaskToggled(id, index, done))
+            .into()
+    });
+
+    column![notes(&task.notes), column(checklist).spacing(4)]
+        .spacing(8)
+        .into()
+}
+
+/// Renders the `notes` line by line.
+fn notes<'a>(notes: &str) -> Element<'a, Message> {
+    let lines = notes
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let line = line.trim_end();
+            let trimmed = line.trim_start();
+            if let Some(heading) = trimmed.strip_prefix('#') {
+                let level = 1 + heading.chars().take_while(|c| *c == '#').count();
+                let size = match level {
+                    1 => 20,
+                    2 => 18,
+                    _ => 16,
+                };
+                text(heading.trim_start_matches('#').trim().to_string())
+                    .size(size)
+                    .font(Font {
+                        weight: Weight::Bold,
+                        ..Font::DEFAULT
+                    })
+                    .into()
+            } else if let Some(item) = trimmed
+                .strip_prefix("- ")
+                .or_else(|| trimmed.strip_prefix("* "))
+            {
+                let indent = (line.len() - trimmed.len()) as f32 * 4.0;
+                row![text("•"), inline(item)]
+                    .spacing(6)
+                    .padding(iced::Padding::ZERO.left(indent + 4.0))
+                    .into()
+            } else {
+                inline(trimmed)
+            }
+        });
+
+    column(lines).spacing(2).into()
+}
+
+/// Renders a line, its `**bold**` and `` `code` `` parts highlighted.
+fn inline<'a>(line: &str) -> Element<'a, Message> {
+    let mut spans: Vec<Span<'a, Message>> = Vec::new();
+    let mut rest = line;
+    while !rest.is_empty() {
+        let next = [("**", bold as fn(String) -> Span<'a, Message>), ("`", code)]
+            .into_iter()
+            .filter_map(|(mark, style)| {
+                let start = rest.find(mark)?;
+                let end = rest[start + mark.len()..].find(mark)? + start + mark.len();
+                Some((start, end, mark, style))
+            })
+            .min_by_key(|(start, ..)| *start);
+
+        let Some((start, end, mark, style)) = next else {
+            spans.push(span(rest.to_string()));
+            break;
+        };
+        if start > 0 {
+            spans.push(span(rest[..start].to_string()));
+        }
+        spans.push(style(rest[start + mark.len()..end].to_string()));
+        rest = &rest[end + mark.len()..];
+    }
+
+    rich_text(spans).into()
+}
+
+fn bold<'a>(content: String) -> Span<'a, Message> {
+    span(content).font(Font {
+        weight: Weight::Bold,
+        ..Font::DEFAULT
+    })
+}
+
+fn code<'a>(content: String) -> Span<'a, Message> {
+    span(content).font(Font::MONOSPACE)
+}
@@ -0,0 +1,64 @@
+//! Fuzzy matching of the task search.
+
+/// Scores how well `query` matches `text`, case insensitive.
+///
+/// Every character of the query must appear in the text, in order, otherwise
+/// it's `None`. The higher the better: characters following each other or
+/// starting a word count more. Whitespace in the query is ignored.
+pub fn score(query: &str, text: &str) -> Option<u32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut from = 0;
+    let mut previous: Option<usize> = None;
+
+    for char in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = from + text.get(from..)?.iter().position(|&c| c == char)?;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == found) {
+            score += 4;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 2;
+        }
+        previous = Some(found);
+        from = found + 1;
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_query_out_of_order_does_not_match() {
+        assert_eq!(score("xyz", "Write chapter"), None);
+        assert_eq!(score("retirw", "Write"), None);
+        assert_eq!(score("writes", "Write"), None);
+    }
+
+    #[test]
+    fn following_characters_beat_scattered_ones() {
+        let contiguous = score("chap", "Write chapter").unwrap();
+        let scattered = score("chap", "Check the map").unwrap();
+
+        assert!(contiguous > scattered);
+    }
+
+    #[test]
+    fn the_start_of_a_word_counts_more() {
+        let prefix = score("ch", "chapter").unwrap();
+        let inside = score("ch", "each").unwrap();
+
+        assert_eq!(prefix, inside + 2);
+        assert!(score("wc", "Write chapter") > score("wc", "wrecks"));
+    }
+
+    #[test]
+    fn case_and_whitespace_are_ignored() {
+        assert_eq!(score("WRITE", "write"), score("write", "WRITE"));
+        assert_eq!(score("wr ite", "Write"), score("write", "Write"));
+        assert_eq!(score("", "Write"), Some(0));
+    }
+}
@@ -0,0 +1,186 @@
+use crate::Message as AppMessage;
+use atoms::widgets::modal;
+use iced::{
+    alignment::Vertical,
+    widget::{
+        button, checkbox, column, container, horizontal_space, pick_list, row, text, text_input,
+    },
+    Element, Length,
+};
+use iced_aw::number_input;
+use std::{fmt, time::Duration};
+use trackit_core::{
+    goal::Goal,
+    settings::{Settings, Sound},
+    Phase,
+};
+
+/// What the daily goal is counted in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Pomodoros,
+    Minutes,
+}
+
+impl Unit {
+    const ALL: [Unit; 2] = [Unit::Pomodoros, Unit::Minutes];
+
+    fn of(goal: &Goal) -> Self {
+        match goal {
+            Goal::Pomodoros(_) => Unit::Pomodoros,
+            Goal::Minutes(_) => Unit::Minutes,
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Unit::Pomodoros => "pomodoros",
+            Unit::Minutes => "minutes of focus",
+        })
+    }
+}
+
+/// The messages of the settings panel, they edit the alerts, the daily goal and
+/// the idle detection of the app.
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Message variant for when the notification of a phase end is toggled.
+    NotifyToggled(Phase, bool),
+    /// Message variant for when the sound of a phase end is toggled.
+    SoundToggled(Phase, bool),
+    /// Message variant for when the custom sound path changes, empty for the bell.
+    SoundPathChanged(String),
+    /// Message variant for when the daily goal is counted in another unit.
+    GoalUnitSelected(Unit),
+    /// Message variant for when the daily target changes.
+    GoalTargetChanged(u32),
+    /// Message variant for when pausing the timer when the user is away is toggled.
+    IdleToggled(bool),
+    /// Message variant for when the minutes before the user counts as away change.
+    IdleThresholdChanged(u32),
+    PreviewSound,
+    Close,
+}
+
+pub fn update(settings: &mut Settings, msg: Message) {
+    let alerts = &mut settings.alerts;
+    match msg {
+        Message::NotifyToggled(phase, notify) => alerts.of_mut(phase).notify = notify,
+        Message::SoundToggled(phase, sound) => alerts.of_mut(phase).sound = sound,
+        Message::SoundPathChanged(path) => {
+            alerts.sound = if path.is_empty() {
+                Sound::Bell
+            } else {
+                Sound::Custom(path.into())
+            }
+        }
+        Message::GoalUnitSelected(unit) => {
+            // keeps about the same amount of work, a pomodoro being a work block
+            let work = (settings.durations.work.as_secs() / 60).max(1) as u32;
+            settings.goal = match (settings.goal, unit) {
+                (Goal::Minutes(minutes), Unit::Pomodoros) => {
+                    Goal::Pomodoros(minutes.div_ceil(work).max(1))
+                }
+                (Goal::Pomodoros(pomodoros), Unit::Minutes) => Goal::Minutes(pomodoros * work),
+                (goal, _) => goal,
+            }
+        }
+        // a goal of nothing would be met every day
+        Message::GoalTargetChanged(target) => {
+            settings.goal = settings.goal.with_target(target.max(1))
+        }
+        Message::IdleToggled(enabled) => settings.idle.enabled = enabled,
+        Message::IdleThresholdChanged(minutes) => {
+            settings.idle.threshold = Duration::from_secs(minutes as u64 * 60)
+        }
+        // catched at app level
+        Message::PreviewSound | Message::Close => {}
+    }
+}
+
+pub fn view<'a>(
+    settings: &Settings,
+    bg: impl Into<Element<'a, AppMessage>>,
+) -> Element<'a, AppMessage> {
+    let alerts = &settings.alerts;
+    let phases = [Phase::Work, Phase::ShortBreak, Phase::LongBreak].map(|phase| {
+        let alert = alerts.of(phase);
+        row![
+            text(format!("{phase} ends:")),
+            horizontal_space(),
+            checkbox("Notify", alert.notify)
+                .on_toggle(move |notify| Message::NotifyToggled(phase, notify)),
+            checkbox("Sound", alert.sound)
+                .on_toggle(move |sound| Message::SoundToggled(phase, sound)),
+        ]
+        .spacing(8)
+        .align_y(Vertical::Center)
+        .into()
+    });
+
+    let sound_path = match &alerts.sound {
+        Sound::Bell => String::new(),
+        Sound::Custom(path) => path.to_string_lossy().into_owned(),
+    };
+
+    let content: Element<_> = container(
+        column!(
+            text("Alerts"),
+            column(phases).spacing(8),
+            column![
+                text("Sound file:"),
+                row![
+                    text_input("Bundled bell", &sound_path).on_input(Message::SoundPathChanged),
+                    button("Try").on_press(Message::PreviewSound),
+                ]
+                .spacing(8)
+            ]
+            .spacing(8),
+            text("Daily goal"),
+            row![
+                number_input(
+                    settings.goal.target(),
+                    1..=1440u32,
+                    Message::GoalTargetChanged
+                )
+                .style(number_input::number_input::primary),
+                pick_list(
+                    Unit::ALL,
+                    Some(Unit::of(&settings.goal)),
+                    Message::GoalUnitSelected
+                ),
+            ]
+            .spacing(8)
+            .align_y(Vertical::Center),
+            text("Idle detection"),
+            row![
+                checkbox("Pause when away for", settings.idle.enabled)
+                    .on_toggle(Message::IdleToggled),
+                number_input(
+                    (settings.idle.threshold.as_secs() / 60) as u32,
+                    1..=120u32,
+                    Message::IdleThresholdChanged
+                )
+                .style(number_input::number_input::primary),
+                text("minutes"),
+            ]
+            .spacing(8)
+            .align_y(Vertical::Center),
+            container(button("Close").on_press(Message::Close)).align_right(Length::Fill)
+        )
+        .spacing(16),
+    )
+    .width(400)
+    .height(Length::Shrink)
+    .padding(16)
+    .style(container::rounded_box)
+    .into();
+
+    modal(
+        bg,
+        content.map(AppMessage::Settings),
+        AppMessage::Settings(Message::Close),
+    )
+}
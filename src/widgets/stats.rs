@@ -0,0 +1,273 @@
+use atoms::widgets::{bar_chart, calendar_heatmap};
+use iced::{
+    widget::{column, container, horizontal_space, row, scrollable, text},
+    Element, Length,
+};
+use trackit_core::{
+    chrono::{Datelike, Days, NaiveDate},
+    format_duration,
+    goal::Goal,
+    history::{self, Filter},
+    stats::{week_start, Day, Stats},
+    Project, SessionLog, Task,
+};
+
+use super::tasks::{chip, project_color};
+
+/// How many days the focus chart shows.
+const DAYS: u64 = 14;
+/// How many weeks the pomodoros chart shows.
+const WEEKS: u32 = 8;
+/// How many weeks the calendar shows.
+const CALENDAR_WEEKS: u64 = 26;
+/// How many tasks the totals show.
+const TOP_TASKS: usize = 10;
+/// The name of the group of the tasks without project.
+const NO_PROJECT: &str = "No project";
+
+/// The statistics screen, a snapshot of the session log.
+#[derive(Debug, Default)]
+pub struct Dashboard {
+    stats: Stats,
+    /// The totals per project, with its colour.
+    projects: Vec<(String, Option<iced::Color>, Day)>,
+    /// The totals per tag.
+    tags: Vec<(String, Day)>,
+    goal: Goal,
+    today: NaiveDate,
+    error: Option<String>,
+}
+
+impl Dashboard {
+    /// Builds the statistics of every interval in the `log` as of `today`,
+    /// grouped by the projects and tags the `tasks` have now, the streaks
+    /// counting the days that met the `goal`.
+    pub fn new(
+        log: Option<&SessionLog>,
+        tasks: &[Task],
+        projects: &[Project],
+        goal: Goal,
+        today: NaiveDate,
+    ) -> Self {
+        let history = log.map(|log| {
+            let filter = Filter::default();
+            Ok::<_, history::Error>((log.intervals(&filter)?, log.interruptions(&filter)?))
+        });
+
+        match history {
+            Some(Ok((intervals, interruptions))) => {
+                let stats = Stats::new(&intervals, &interruptions);
+                let by_project = stats
+                    .projects(tasks)
+                    .into_iter()
+                    .map(
+                        |(id, day)| match id.and_then(|id| projects.iter().find(|p| p.id == id)) {
+                            Some(project) => {
+                                (project.name.clone(), Some(project_color(project)), day)
+                            }
+                            None => (NO_PROJECT.to_string(), None, day),
+                        },
+                    )
+                    .collect();
+
+                Self {
+                    projects: by_project,
+                    tags: stats.tags(tasks),
+                    stats,
+                    goal,
+                    today,
+                    error: None,
+                }
+            }
+            Some(Err(err)) => Self {
+                goal,
+                today,
+                error: Some(err.to_string()),
+                ..Self::default()
+            },
+            None => Self {
+                goal,
+                today,
+                error: Some("the session log is not available".into()),
+                ..Self::default()
+            },
+        }
+    }
+
+    pub fn view<'a, Message: 'a>(&self) -> Element<'a, Message> {
+        let today = self.stats.day(self.today);
+        let week = self
+            .stats
+            .weeks(self.today, 1)
+            .first()
+            .map(|(_, week)| *week)
+            .unwrap_or_default();
+
+        let summary = row![
+            figure("Today", format_duration(today.focus)),
+            figure("This week", format!("{} pomodoros", week.pomodoros)),
+            figure(
+                "Streak",
+                format!("{} days", self.stats.current_streak(self.today))
+            ),
+            figure(
+                "Longest streak",
+                format!("{} days", self.stats.longest_streak(self.today))
+            ),
+            figure(
+                "Goal streak",
+                format!(
+                    "{} days (best {})",
+                    self.stats.goal_streak(&self.goal, self.today),
+                    self.stats.longest_goal_streak(&self.goal, self.today)
+                )
+            ),
+            figure(
+                "Interrupted this week",
+                format!("{} times", week.interruptions.total())
+            ),
+        ]
+        .spacing(16);
+
+        let days = self
+            .stats
+            .days(self.today - Days::new(DAYS - 1), self.today);
+        let focus_chart = bar_chart(
+            days.iter()
+                .map(|(date, day)| (date.day().to_string(), day.focus.as_secs_f32() / 60.)),
+        )
+        .tooltips(days.iter().map(|(date, day)| {
+            format!(
+                "{} · {}",
+                date.format("%a %d/%m"),
+                format_duration(day.focus)
+            )
+        }))
+        .highlighted(days.len() - 1);
+
+        let weeks = self.stats.weeks(self.today, WEEKS);
+        let pomodoros_chart = bar_chart(
+            weeks
+                .iter()
+                .map(|(monday, week)| (monday.format("%d/%m").to_string(), week.pomodoros as f32)),
+        )
+        .tooltips(weeks.iter().map(|(_, week)| {
+            format!(
+                "{} pomodoros · {}",
+                week.pomodoros,
+                format_duration(week.focus)
+            )
+        }))
+        .highlighted(weeks.len() - 1);
+
+        let interruptions_chart = bar_chart(weeks.iter().map(|(monday, week)| {
+            (
+                monday.format("%d/%m").to_string(),
+                week.interruptions.total() as f32,
+            )
+        }))
+        .tooltips(weeks.iter().map(|(_, week)| week.interruptions.to_string()))
+        .highlighted(weeks.len() - 1);
+
+        let first_day = week_start(self.today) - Days::new(7 * (CALENDAR_WEEKS - 1));
+        let calendar = self.stats.days(first_day, self.today);
+        let heatmap = calendar_heatmap(
+            calendar
+                .iter()
+                .map(|(_, day)| day.focus.as_secs_f32() / 60.),
+        )
+        .tooltips(calendar.iter().map(|(date, day)| {
+            format!(
+                "{} · {} · {} pomodoros",
+                date.format("%a %d/%m/%Y"),
+                format_duration(day.focus),
+                day.pomodoros
+            )
+        }));
+
+        let tasks = column(self.stats.tasks().iter().take(TOP_TASKS).map(|task| {
+            row![
+                text(task.name.clone()),
+                horizontal_space(),
+                text(interrupted(task.interruptions.total())).style(text::secondary),
+                text(format!("{} pomodoros", task.pomodoros)).style(text::secondary),
+                text(format_duration(task.focus)),
+            ]
+            .spacing(16)
+            .into()
+        }))
+        .spacing(4);
+
+        let projects = column(
+            self.projects
+                .iter()
+                .map(|(name, color, day)| total(chip(name.clone(), *color), day)),
+        )
+        .spacing(4);
+        let tags = column(
+            self.tags
+                .iter()
+                .map(|(tag, day)| total(chip(format!("#{tag}"), None), day)),
+        )
+        .spacing(4);
+
+        let content = column![
+            summary,
+            section("Focus per day (minutes)", focus_chart),
+            section("Pomodoros per week", pomodoros_chart),
+            section("Interruptions per week", interruptions_chart),
+            section("Last six months", heatmap),
+            section("Per task", tasks),
+            section("Per project", projects),
+        ]
+        .push_maybe((!self.tags.is_empty()).then(|| section("Per tag", tags)))
+        .push_maybe(
+            self.error
+                .as_ref()
+                .map(|err| text(format!("Unable to read the history: {err}")).style(text::danger)),
+        )
+        .spacing(24)
+        .padding(16);
+
+        scrollable(content)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
+
+/// The totals of a group, after its `label`.
+fn total<'a, Message: 'a>(label: Element<'a, Message>, day: &Day) -> Element<'a, Message> {
+    row![
+        label,
+        horizontal_space(),
+        text(format!("{} pomodoros", day.pomodoros)).style(text::secondary),
+        text(format_duration(day.focus)),
+    ]
+    .spacing(16)
+    .into()
+}
+
+/// How many times a task was interrupted, blank if never.
+fn interrupted(count: u32) -> String {
+    match count {
+        0 => String::new(),
+        1 => "interrupted once".to_string(),
+        count => format!("interrupted {count} times"),
+    }
+}
+
+/// A headline number, with its `caption` above.
+fn figure<'a, Message: 'a>(caption: &'a str, value: String) -> Element<'a, Message> {
+    container(column![text(caption).style(text::secondary), text(value).size(20)].spacing(4))
+        .padding(8)
+        .style(container::rounded_box)
+        .into()
+}
+
+fn section<'a, Message: 'a>(
+    title: &'a str,
+    content: impl Into<Element<'a, Message>>,
+) -> Element<'a, Message> {
+    column![text(title), content.into()].spacing(8).into()
+}
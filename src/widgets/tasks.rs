@@ -1,32 +1,25 @@
-use std::time::Duration;
+use std::{cmp::Reverse, fmt};
 
+use super::{notes, search};
 use dragking::{DragEvent, DropPosition};
 use iced::{
     alignment::Horizontal,
-    widget::{button, column, container, horizontal_rule, mouse_area, row, text},
-    Element, Length,
+    border,
+    widget::{
+        button, checkbox, column, container, horizontal_rule, horizontal_space, mouse_area,
+        pick_list, row, text, text_input,
+    },
+    Element, Length, Theme,
+};
+use trackit_core::{
+    chrono::{DateTime, Local},
+    format_clock, format_duration,
+    project::{self, Group},
+    ActiveSession, Durations, Project, ProjectId, Session, Task, TaskId, TaskState,
 };
-use trackit_core::{chrono::Local, Task};
-
-fn format_duration(duration: Duration) -> String {
-    let total_secs = duration.as_secs();
-    let hours = total_secs / 3600;
-    let minutes = (total_secs % 3600) / 60;
-
-    if hours > 0 {
-        format!("{}h {:02}m", hours, minutes)
-    } else {
-        format!("{:02}m", minutes)
-    }
-}
 
-fn calculate_cycles(cycles: u8) -> String {
-    let time = Duration::from_secs(60 * 25 * cycles as u64);
-    let breaks = if cycles > 1 {
-        Duration::from_secs((cycles as u64 - 1) * 5 * 60)
-    } else {
-        Duration::ZERO
-    };
+fn calculate_cycles(cycles: u8, durations: &Durations) -> String {
+    let (time, breaks) = durations.plan(cycles);
 
     if breaks.is_zero() {
         format_duration(time)
@@ -39,36 +32,140 @@ fn calculate_cycles(cycles: u8) -> String {
     }
 }
 
+/// The colour of a project, for iced.
+pub fn project_color(project: &Project) -> iced::Color {
+    iced::Color::from_rgb8(project.color.r, project.color.g, project.color.b)
+}
+
+/// A small rounded label, filled with `color` or with the weak background.
+pub fn chip<'a, Message: 'a>(label: String, color: Option<iced::Color>) -> Element<'a, Message> {
+    container(text(label).size(12))
+        .padding([2, 8])
+        .style(move |theme: &Theme| {
+            let palette = theme.extended_palette();
+            let (background, text_color) = match color {
+                Some(color) => (color, palette.background.base.color),
+                None => (palette.background.weak.color, palette.background.weak.text),
+            };
+            container::Style {
+                background: Some(background.into()),
+                text_color: Some(text_color),
+                border: border::rounded(8),
+                ..container::Style::default()
+            }
+        })
+        .into()
+}
+
+/// The tasks shown in the sidebar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    All,
+    Project(ProjectId, String),
+    Tag(String),
+}
+
+impl Filter {
+    /// The group of the shown tasks, `None` if every task is shown.
+    fn group(&self) -> Option<Group> {
+        match self {
+            Filter::All => None,
+            Filter::Project(id, _) => Some(Group::Project(*id)),
+            Filter::Tag(tag) => Some(Group::Tag(tag.clone())),
+        }
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Filter::All => f.write_str("Every task"),
+            Filter::Project(_, name) => f.write_str(name),
+            Filter::Tag(tag) => write!(f, "#{tag}"),
+        }
+    }
+}
+
+/// The order of the cards in the sidebar.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Sort {
+    /// The order the user dragged the cards in, the newest on top.
+    #[default]
+    Manual,
+    /// The last started first, the never started last.
+    Recent,
+    /// The closest to completion first.
+    RemainingCycles,
+}
+
+impl Sort {
+    pub const ALL: [Sort; 3] = [Sort::Manual, Sort::Recent, Sort::RemainingCycles];
+}
+
+impl fmt::Display for Sort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Sort::Manual => "Manual order",
+            Sort::Recent => "Most recent",
+            Sort::RemainingCycles => "Fewest cycles left",
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Message {
-    Start(u8),
-    Delete(u8),
-    Stop,
-    StartHover(u8),
-    EndHover(u8),
+    Start(TaskId),
+    Delete(TaskId),
+    /// Message variant for when the task is opened in the modal to be edited.
+    Edit(TaskId),
+    Stop(TaskId),
+    /// Message variant for when a task is marked as done by hand.
+    Complete(TaskId),
+    Archive(TaskId),
+    /// Message variant for when the notes and subtasks of a card are shown or hidden.
+    DetailsToggled(TaskId),
+    /// Message variant for when a subtask, by index, is ticked or unticked.
+    SubtaskToggled(TaskId, usize, bool),
+    StartHover(TaskId),
+    EndHover(TaskId),
     Reorder(DragEvent),
+    FilterSelected(Filter),
+    SearchChanged(String),
+    /// Message variant for when the tasks not done yet are shown or hidden.
+    ActiveToggled(bool),
+    /// Message variant for when the completed tasks are shown or hidden.
+    CompletedToggled(bool),
+    SortSelected(Sort),
 }
 
 pub struct Card {
     pub task: Task,
-    pub index: u8,
     pub hovered: bool,
+    /// Whether the notes and subtasks are shown.
+    pub expanded: bool,
 }
 
 impl<'a> Card {
-    pub fn new(index: u8, task: Task) -> Self {
+    pub fn new(task: Task) -> Self {
         Self {
             task,
-            index,
             hovered: false,
+            expanded: false,
         }
     }
 
-    pub fn placeholder() -> Element<'a, Message> {
+    /// Shown in place of the cards, `filtered` if there are cards but none matches.
+    pub fn placeholder(filtered: bool) -> Element<'a, Message> {
+        let (title, hint) = if filtered {
+            ("No matching task", "try another search")
+        } else {
+            ("Empty task list", "try adding one!")
+        };
+
         container(
             column![
-                text("Empty task list").style(text::secondary),
-                text("try adding one!").style(text::secondary),
+                text(title).style(text::secondary),
+                text(hint).style(text::secondary),
             ]
             .align_x(Horizontal::Center)
             .spacing(4)
@@ -79,125 +176,399 @@ impl<'a> Card {
         .style(container::transparent)
         .into()
     }
-    pub fn view(&self) -> Element<'a, Message> {
+    pub fn view(
+        &self,
+        active: Option<&ActiveSession>,
+        project: Option<&Project>,
+        now: DateTime<Local>,
+    ) -> Element<'a, Message> {
+        let session = active.map(|active| &active.session);
+        let done = if self.task.is_completed() {
+            button("Archive")
+                .style(button::secondary)
+                .on_press(Message::Archive(self.task.id))
+        } else {
+            button("Done")
+                .style(button::success)
+                .on_press(Message::Complete(self.task.id))
+        };
         let actions = row![
             button("Delete")
                 .style(button::danger)
-                .on_press(Message::Delete(self.index)),
-            button("Start").on_press(Message::Start(self.index)),
+                .on_press(Message::Delete(self.task.id)),
+            button("Edit")
+                .style(button::secondary)
+                .on_press(Message::Edit(self.task.id)),
+            button(if session.is_some_and(Session::is_paused) {
+                "Resume"
+            } else {
+                "Start"
+            })
+            .on_press(Message::Start(self.task.id)),
             button("Stop")
                 .style(button::secondary)
-                .on_press(Message::Stop)
+                .on_press(Message::Stop(self.task.id)),
+            done
         ]
-        .spacing(8);
+        .spacing(8)
+        .wrap();
 
-        let card_content = column![
-            text(self.task.name.clone()),
-            horizontal_rule(1),
-            column![
-                text(format!("{} cycles", self.task.cycles)).style(text::secondary),
-                text(calculate_cycles(self.task.cycles)).style(text::secondary)
-            ],
-        ]
-        .push_maybe(self.hovered.then_some(actions))
+        let chips = row(project
+            .map(|project| chip(project.name.clone(), Some(project_color(project))))
+            .into_iter()
+            .chain(
+                self.task
+                    .tags
+                    .iter()
+                    .map(|tag| chip(format!("#{tag}"), None)),
+            ))
         .spacing(4)
-        .padding(8);
+        .wrap();
+
+        let title = row![text(self.task.name.clone()), horizontal_space()].push_maybe(
+            (self.task.state != TaskState::Todo).then(|| {
+                text(self.task.state.to_string())
+                    .size(12)
+                    .style(text::secondary)
+            }),
+        );
+
+        let has_details = !self.task.notes.trim().is_empty() || !self.task.subtasks.is_empty();
+        let details = has_details.then(|| {
+            let label = match self.task.subtasks.len() {
+                0 => "Notes".to_string(),
+                total => format!("{}/{total} steps", self.task.subtasks_done()),
+            };
+            column![button(text(format!(
+                "{} {label}",
+                if self.expanded { "▾" } else { "▸" }
+            )))
+            .style(button::text)
+            .padding(0)
+            .on_press(Message::DetailsToggled(self.task.id))]
+            .push_maybe(self.expanded.then(|| notes::view(&self.task)))
+            .spacing(4)
+        });
+
+        let card_content = column![title]
+            .push_maybe((project.is_some() || !self.task.tags.is_empty()).then_some(chips))
+            .push(horizontal_rule(1))
+            .push(column![
+                text(format!("{} cycles", self.task.cycles)).style(text::secondary),
+                text(format!(
+                    "{}m work / {}m break",
+                    self.task.durations.work.as_secs() / 60,
+                    self.task.durations.short_break.as_secs() / 60
+                ))
+                .style(text::secondary),
+                text(calculate_cycles(self.task.cycles, &self.task.durations))
+                    .style(text::secondary)
+            ])
+            .push_maybe(session.map(|session| {
+                let status = if session.is_finished() {
+                    "Finished".to_string()
+                } else {
+                    format!(
+                        "{}{} · {}/{} cycles · {} left",
+                        if session.is_paused() {
+                            "Paused · "
+                        } else {
+                            ""
+                        },
+                        session.phase(),
+                        session.completed_cycles(),
+                        session.cycles(),
+                        format_clock(session.remaining(now))
+                    )
+                };
+                let status = match active.map(|active| active.interruptions.total()) {
+                    Some(0) | None => status,
+                    Some(1) => format!("{status} · 1 interruption"),
+                    Some(count) => format!("{status} · {count} interruptions"),
+                };
+                text(status).style(text::primary)
+            }))
+            .push_maybe(details)
+            .push_maybe(self.hovered.then_some(actions))
+            .spacing(4)
+            .padding(8);
 
         let all = mouse_area(container(card_content).style(container::rounded_box))
-            .on_enter(Message::StartHover(self.index))
-            .on_exit(Message::EndHover(self.index));
+            .on_enter(Message::StartHover(self.task.id))
+            .on_exit(Message::EndHover(self.task.id));
 
         all.into()
     }
 
+    /// How well the task name or one of its tags matches the search `query`.
+    fn score(&self, query: &str) -> Option<u32> {
+        std::iter::once(search::score(query, &self.task.name))
+            .chain(
+                self.task
+                    .tags
+                    .iter()
+                    .map(|tag| search::score(query, &format!("#{tag}"))),
+            )
+            .flatten()
+            .max()
+    }
+
     fn update(&mut self, msg: Message) {
         match msg {
             Message::StartHover(_) => self.hovered = true,
             Message::EndHover(_) => self.hovered = false,
+            Message::DetailsToggled(_) => self.expanded = !self.expanded,
             _ => {}
         }
     }
-
-    fn set_index(&mut self, new_index: u8) {
-        self.index = new_index
-    }
 }
 
-#[derive(Default)]
 pub struct Cards {
     elements: Vec<Card>,
+    filter: Filter,
+    search: String,
+    /// Whether the tasks not done yet are shown.
+    show_active: bool,
+    /// Whether the completed tasks are shown.
+    show_completed: bool,
+    sort: Sort,
+}
+
+impl Default for Cards {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
 }
 
 impl<'a> Cards {
-    #[expect(dead_code)]
     pub fn new(cards: Vec<Card>) -> Cards {
-        Cards { elements: cards }
+        Cards {
+            elements: cards,
+            filter: Filter::All,
+            search: String::new(),
+            show_active: true,
+            show_completed: true,
+            sort: Sort::Manual,
+        }
     }
 
-    pub fn view(&self) -> Element<'a, Message> {
-        let elements = self
+    /// Creates the cards of the given `tasks`, keeping their order.
+    pub fn from_tasks(tasks: Vec<Task>) -> Cards {
+        Cards::new(tasks.into_iter().map(Card::new).collect())
+    }
+
+    /// The tasks of every card, in order.
+    pub fn tasks(&self) -> Vec<Task> {
+        self.elements.iter().map(|card| card.task.clone()).collect()
+    }
+
+    /// Renders the cards, `active` being the running session.
+    ///
+    /// The cards can only be reordered while they're all shown in manual order.
+    pub fn view(
+        &self,
+        active: Option<&ActiveSession>,
+        projects: &[Project],
+        now: DateTime<Local>,
+    ) -> Element<'a, Message> {
+        let shown = self.shown();
+        let placeholder = shown
+            .is_empty()
+            .then(|| Card::placeholder(!self.elements.is_empty()));
+        let elements = shown
+            .into_iter()
+            .map(move |card| {
+                let active = active.filter(|active| active.task == card.task.id);
+                let project = card
+                    .task
+                    .project
+                    .and_then(|id| projects.iter().find(|project| project.id == id));
+                card.view(active, project, now)
+            })
+            .chain(placeholder);
+
+        let list: Element<_> = if self.is_reorderable() {
+            dragking::column(elements)
+                .on_drag(Message::Reorder)
+                .deadband_zone(0.0)
+                .spacing(8)
+                .into()
+        } else {
+            column(elements).spacing(8).into()
+        };
+
+        let controls = column![
+            text_input("Search tasks or #tags", &self.search).on_input(Message::SearchChanged),
+            row![
+                self.filter_list(projects),
+                pick_list(Sort::ALL, Some(self.sort), Message::SortSelected).width(Length::Fill)
+            ]
+            .spacing(8),
+            row![
+                checkbox("Active", self.show_active).on_toggle(Message::ActiveToggled),
+                checkbox("Completed", self.show_completed).on_toggle(Message::CompletedToggled)
+            ]
+            .spacing(16),
+        ]
+        .spacing(8);
+
+        column![controls, list]
+            .width(Length::FillPortion(2))
+            .padding(8)
+            .spacing(8)
+            .into()
+    }
+
+    /// The cards matching the search and filters, in the chosen order.
+    ///
+    /// While searching, the best matches come first.
+    fn shown(&self) -> Vec<&Card> {
+        let group = self.filter.group();
+        let query = self.search.trim();
+
+        let mut shown: Vec<_> = self
             .elements
             .iter()
             .rev()
-            .map(Card::view)
-            .chain(self.elements.is_empty().then(Card::placeholder));
+            .filter(|card| !card.task.is_archived())
+            .filter(|card| {
+                group
+                    .as_ref()
+                    .is_none_or(|group| group.contains(&card.task))
+            })
+            .filter(|card| {
+                if card.task.is_completed() {
+                    self.show_completed
+                } else {
+                    self.show_active
+                }
+            })
+            .filter_map(|card| match query {
+                "" => Some((card, 0)),
+                query => card.score(query).map(|score| (card, score)),
+            })
+            .collect();
 
-        let view = dragking::column(elements)
-            .on_drag(Message::Reorder)
-            .width(Length::FillPortion(2))
-            .deadband_zone(0.0)
-            .padding(8)
-            .spacing(8);
+        match self.sort {
+            Sort::Manual => {}
+            Sort::Recent => shown.sort_by_key(|(card, _)| Reverse(card.task.started_at)),
+            Sort::RemainingCycles => shown.sort_by_key(|(card, _)| card.task.remaining_cycles()),
+        }
+        if !query.is_empty() {
+            shown.sort_by_key(|(_, score)| Reverse(*score));
+        }
+
+        shown.into_iter().map(|(card, _)| card).collect()
+    }
+
+    /// Whether the cards are shown in manual order, none hidden, so that
+    /// dragging them maps to their real position.
+    fn is_reorderable(&self) -> bool {
+        self.sort == Sort::Manual
+            && self.filter == Filter::All
+            && self.search.trim().is_empty()
+            && self.show_active
+            && self.show_completed
+    }
+
+    /// The list picking the shown tasks, by project or tag.
+    fn filter_list(&self, projects: &[Project]) -> Element<'a, Message> {
+        let choices: Vec<_> = std::iter::once(Filter::All)
+            .chain(
+                projects
+                    .iter()
+                    .map(|project| Filter::Project(project.id, project.name.clone())),
+            )
+            .chain(
+                project::all_tags(&self.tasks())
+                    .into_iter()
+                    .map(Filter::Tag),
+            )
+            .collect();
+
+        pick_list(choices, Some(self.filter.clone()), Message::FilterSelected)
+            .width(Length::Fill)
+            .into()
+    }
+
+    /// The task with the given `id`.
+    pub fn get(&self, id: TaskId) -> Option<&Task> {
+        self.elements
+            .iter()
+            .find(|card| card.task.id == id)
+            .map(|card| &card.task)
+    }
+
+    /// The task with the given `id`, mutably.
+    pub fn get_mut(&mut self, id: TaskId) -> Option<&mut Task> {
+        self.elements
+            .iter_mut()
+            .find(|card| card.task.id == id)
+            .map(|card| &mut card.task)
+    }
+
+    fn card_mut(&mut self, id: TaskId) -> Option<&mut Card> {
+        self.elements.iter_mut().find(|card| card.task.id == id)
+    }
 
-        view.into()
+    /// Inserts a card for the `task` at `index`, or last if it's past the end.
+    pub fn insert(&mut self, index: usize, task: Task) {
+        let index = index.min(self.elements.len());
+        self.elements.insert(index, Card::new(task));
     }
 
-    pub fn elements(&self) -> &Vec<Card> {
-        &self.elements
+    /// Removes the card of the task with the given `id`, returns where it was
+    /// and its task.
+    pub fn remove(&mut self, id: TaskId) -> Option<(usize, Task)> {
+        let index = self.elements.iter().position(|card| card.task.id == id)?;
+        Some((index, self.elements.remove(index).task))
     }
 
-    pub fn mut_elements(&mut self) -> &mut Vec<Card> {
-        &mut self.elements
+    /// The ids of the tasks, in order.
+    pub fn order(&self) -> Vec<TaskId> {
+        self.elements.iter().map(|card| card.task.id).collect()
     }
 
-    pub fn add_card(&mut self, task: Task) {
-        let index = self.elements.len();
-        self.elements.push(Card::new(index as u8, task));
+    /// Puts the cards in the given `order`, the ones left out keep their place
+    /// at the end.
+    pub fn set_order(&mut self, order: &[TaskId]) {
+        self.elements.sort_by_key(|card| {
+            order
+                .iter()
+                .position(|id| *id == card.task.id)
+                .unwrap_or(usize::MAX)
+        });
     }
 
     pub fn update(&mut self, msg: Message) {
         match msg {
-            Message::Start(index) => {
-                let element = self
-                    .elements
-                    .get_mut(index as usize)
-                    .expect("Unable to update");
-
-                element.task.started_at = Some(Local::now());
-            }
-            Message::Delete(index) => {
-                self.elements.remove(index as usize);
-                self.elements = self
-                    .elements
-                    .iter()
-                    .enumerate()
-                    .map(|(index, card)| Card {
-                        index: index as u8,
-                        task: card.task.clone(),
-                        hovered: card.hovered,
-                    })
-                    .collect::<Vec<_>>();
+            Message::Start(id) => {
+                if let Some(task) = self.get_mut(id) {
+                    task.start(Local::now());
+                }
             }
-            Message::Stop => todo!(),
-            msg @ Message::StartHover(index) => {
-                let card = self.elements.get_mut(index as usize).unwrap();
-                card.update(msg)
+            Message::Delete(id) => {
+                self.remove(id);
             }
-            msg @ Message::EndHover(index) => {
-                let card = self.elements.get_mut(index as usize).unwrap();
-                card.update(msg)
+            // catched at app level
+            Message::Edit(_)
+            | Message::Stop(_)
+            | Message::Complete(_)
+            | Message::Archive(_)
+            | Message::SubtaskToggled(..) => {}
+            msg @ (Message::StartHover(id)
+            | Message::EndHover(id)
+            | Message::DetailsToggled(id)) => {
+                if let Some(card) = self.card_mut(id) {
+                    card.update(msg)
+                }
             }
             Message::Reorder(event) => self.handle_reorder(event),
+            Message::FilterSelected(filter) => self.filter = filter,
+            Message::SearchChanged(search) => self.search = search,
+            Message::ActiveToggled(show) => self.show_active = show,
+            Message::CompletedToggled(show) => self.show_completed = show,
+            Message::SortSelected(sort) => self.sort = sort,
         }
     }
 
@@ -208,7 +579,12 @@ impl<'a> Cards {
             drop_position,
         } = event
         {
-            let len = self.elements.len();
+            // the archived cards are hidden, the indices are among the others
+            let visible: Vec<_> = (0..self.elements.len())
+                .rev()
+                .filter(|&index| !self.elements[index].task.is_archived())
+                .collect();
+            let len = visible.len();
             if len == 1 {
                 return;
             };
@@ -217,29 +593,25 @@ impl<'a> Cards {
                 return;
             }
 
-            let index = len.saturating_sub(index + 1);
-            let target_index = len.saturating_sub(target_index + 1);
+            let index = visible[index];
+            let target_index = visible[target_index];
 
             match drop_position {
                 DropPosition::Before | DropPosition::After => {
                     if target_index != index && target_index != index + 1 {
-                        let mut item = self.elements.remove(index);
+                        let item = self.elements.remove(index);
                         let insert_index = if index < target_index {
                             target_index - 1
                         } else {
                             target_index
                         };
 
-                        item.set_index(insert_index as u8);
-
                         self.elements.insert(insert_index, item);
                     }
                 }
                 DropPosition::Swap => {
                     if target_index != index {
                         self.elements.swap(index, target_index);
-                        self.elements[index].set_index(index as u8);
-                        self.elements[target_index].set_index(target_index as u8);
                     }
                 }
             }
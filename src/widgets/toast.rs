@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use crate::Message as AppMessage;
+use iced::{
+    alignment::{Horizontal, Vertical},
+    widget::{button, container, row, stack, text},
+    Element, Length,
+};
+use trackit_core::chrono::{DateTime, Local};
+
+/// How long a toast stays on screen.
+const LIFETIME: Duration = Duration::from_secs(5);
+
+/// A short-lived message at the bottom of the window, offering to undo what
+/// just happened, unless it's a mere notice.
+#[derive(Debug, Clone)]
+pub struct Toast {
+    message: String,
+    until: DateTime<Local>,
+    undoable: bool,
+}
+
+impl Toast {
+    /// Creates a toast shown from `now` on, offering to undo the last edit.
+    pub fn new(message: impl Into<String>, now: DateTime<Local>) -> Self {
+        Self {
+            message: message.into(),
+            until: now + LIFETIME,
+            undoable: true,
+        }
+    }
+
+    /// Creates a toast shown from `now` on, with nothing to undo.
+    pub fn notice(message: impl Into<String>, now: DateTime<Local>) -> Self {
+        Self {
+            undoable: false,
+            ..Self::new(message, now)
+        }
+    }
+
+    /// Whether the toast should be gone at `now`.
+    pub fn is_expired(&self, now: DateTime<Local>) -> bool {
+        now >= self.until
+    }
+
+    /// Renders the toast over `bg`.
+    pub fn view<'a>(&self, bg: impl Into<Element<'a, AppMessage>>) -> Element<'a, AppMessage> {
+        let content = if self.undoable {
+            row![
+                text(format!("{} —", self.message)),
+                button("Undo")
+                    .style(button::text)
+                    .on_press(AppMessage::Undo),
+            ]
+        } else {
+            row![text(self.message.clone())]
+        };
+        let toast = container(
+            content
+                .push(
+                    button("×")
+                        .style(button::text)
+                        .on_press(AppMessage::DismissToast),
+                )
+                .spacing(4)
+                .align_y(Vertical::Center),
+        )
+        .padding([4, 12])
+        .style(container::rounded_box);
+
+        stack![
+            bg.into(),
+            container(toast)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .align_x(Horizontal::Center)
+                .align_y(Vertical::Bottom)
+                .padding(16)
+        ]
+        .into()
+    }
+}